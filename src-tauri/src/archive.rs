@@ -0,0 +1,129 @@
+use std::fs::File;
+use std::io::{BufReader, Read};
+use std::path::Path;
+
+use serde::Serialize;
+use zip::ZipArchive;
+
+use crate::error::{AtPath, PeekError};
+
+/// Largest image entry decompressed for a preview. Unlike text, an image
+/// can't be shown truncated.
+const MAX_IMAGE_ENTRY_BYTES: u64 = 32 * 1024 * 1024; // 32 MB
+
+// ── Types ─────────────────────────────────────────────────────────────────────
+
+#[derive(Serialize)]
+pub struct ArchiveEntry {
+    name: String, // Full path inside the archive, `/`-separated
+    size: u64,
+    compressed_size: u64,
+    is_dir: bool,
+}
+
+#[derive(Serialize)]
+pub struct ArchiveEntryResponse {
+    /// Decoded text, an image data URI, or "Binary file detected".
+    content: String,
+    name: String,
+    extension: String,
+    size: u64,
+    is_binary: bool,
+    is_image: bool,
+    /// Set when only the first `LARGE_FILE_THRESHOLD` bytes were read.
+    truncated: bool,
+    encoding: Option<String>,
+}
+
+// ── Helpers ───────────────────────────────────────────────────────────────────
+
+fn open_archive(path: &str) -> Result<ZipArchive<BufReader<File>>, PeekError> {
+    crate::regular_file_metadata(Path::new(path))?;
+    let file = File::open(path).at(path)?;
+    ZipArchive::new(BufReader::new(file)).map_err(|e| PeekError::from(format!("Not a readable zip archive: {}", e)))
+}
+
+// ── Commands ──────────────────────────────────────────────────────────────────
+
+/// Entries of a `.zip` (or `.jar`, `.apk`, ...) in archive order.
+#[tauri::command]
+pub fn list_archive(path: String) -> Result<Vec<ArchiveEntry>, PeekError> {
+    let path = crate::paths::resolve(&path);
+    let mut archive = open_archive(&path).at(&path)?;
+    let mut entries = Vec::with_capacity(archive.len());
+    for i in 0..archive.len() {
+        let entry = archive.by_index(i).map_err(|e| e.to_string())?;
+        entries.push(ArchiveEntry {
+            name: entry.name().to_string(),
+            size: entry.size(),
+            compressed_size: entry.compressed_size(),
+            is_dir: entry.is_dir(),
+        });
+    }
+    Ok(entries)
+}
+
+/// Read one entry for display, read-only. Text is decoded with its detected
+/// encoding; entries over the large-file threshold are decompressed only that
+/// far and come back `truncated`. Images become data URIs (SVGs sanitized);
+/// ones over `MAX_IMAGE_ENTRY_BYTES` are refused.
+#[tauri::command]
+pub fn read_archive_entry(path: String, entry: String) -> Result<ArchiveEntryResponse, PeekError> {
+    let path = crate::paths::resolve(&path);
+    let mut archive = open_archive(&path).at(&path)?;
+    let file = archive.by_name(&entry).map_err(|_| PeekError::not_found(format!("No entry {} in {}", entry, path)))?;
+    if file.is_dir() {
+        return Err(PeekError::is_directory("Is a directory"));
+    }
+    let size = file.size();
+    let extension = Path::new(&entry)
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or("")
+        .to_lowercase();
+    let is_image = crate::IMAGE_EXTENSIONS.contains(&extension.as_str());
+
+    let too_large = || PeekError::from(format!("Image is too large to preview ({} bytes)", size));
+    if is_image && size > MAX_IMAGE_ENTRY_BYTES {
+        return Err(too_large());
+    }
+
+    let mut bytes = Vec::new();
+    // The declared size may be wrong, so images are still read one byte past the cap
+    let limit = if is_image { MAX_IMAGE_ENTRY_BYTES + 1 } else { crate::LARGE_FILE_THRESHOLD };
+    file.take(limit).read_to_end(&mut bytes)?;
+    if is_image && bytes.len() as u64 > MAX_IMAGE_ENTRY_BYTES {
+        return Err(too_large());
+    }
+    let truncated = (bytes.len() as u64) < size;
+
+    let response = |content: String, is_binary: bool, encoding: Option<String>| ArchiveEntryResponse {
+        content,
+        name: entry.clone(),
+        extension: extension.clone(),
+        size,
+        is_binary,
+        is_image,
+        truncated,
+        encoding,
+    };
+
+    if is_image {
+        use base64::{Engine as _, engine::general_purpose};
+        let (mime, data) = match extension.as_str() {
+            "svg" => ("svg+xml", crate::svg::sanitize_svg(&String::from_utf8_lossy(&bytes)).into_bytes()),
+            "jpg" => ("jpeg", bytes),
+            "ico" => ("x-icon", bytes),
+            other => (other, bytes),
+        };
+        let uri = format!("data:image/{};base64,{}", mime, general_purpose::STANDARD.encode(data));
+        return Ok(response(uri, false, None));
+    }
+    if bytes[..bytes.len().min(1024)].contains(&0) {
+        return Ok(response("Binary file detected".to_string(), true, None));
+    }
+
+    let encoding = crate::encoding::detect(&bytes[..bytes.len().min(crate::encoding::SAMPLE_BYTES)]);
+    let content = crate::strip_crlf(crate::encoding::decode(&bytes, encoding, true));
+    Ok(response(content, false, Some(encoding.name().to_string())))
+}
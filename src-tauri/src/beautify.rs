@@ -0,0 +1,265 @@
+use crate::error::PeekError;
+
+const DEFAULT_INDENT: usize = 2;
+
+/// Extensions whose minified form `beautify_code` can reflow.
+pub const BEAUTIFY_EXTENSIONS: &[&str] = &[
+    "js", "mjs", "cjs", "jsx", "ts", "tsx", "css", "scss", "less", "json",
+];
+
+/// A file looks minified if any line is longer than this...
+const MINIFIED_LINE_LEN: usize = 10 * 1024;
+/// ...or its lines average more than this many bytes.
+const MINIFIED_AVG_LINE_LEN: usize = 300;
+
+/// Words after which a `/` starts a regex literal rather than a division.
+const REGEX_KEYWORDS: &[&str] = &[
+    "return", "typeof", "instanceof", "in", "of", "new", "delete", "void", "throw",
+    "case", "do", "else", "yield", "await",
+];
+
+// ── Detection ─────────────────────────────────────────────────────────────────
+
+/// Heuristic over (a sample of) a file's bytes: one huge line, or lines far
+/// longer than hand-written code has.
+pub fn looks_minified(sample: &[u8]) -> bool {
+    if sample.is_empty() {
+        return false;
+    }
+    let mut lines = 0;
+    let mut longest = 0;
+    for line in sample.split(|&b| b == b'\n') {
+        lines += 1;
+        longest = longest.max(line.len());
+    }
+    longest > MINIFIED_LINE_LEN || sample.len() / lines > MINIFIED_AVG_LINE_LEN
+}
+
+// ── Reflow ────────────────────────────────────────────────────────────────────
+
+/// Output buffer that indents lazily, so a line's indent is only written once
+/// its first character is known.
+struct Writer {
+    out: String,
+    unit: String,
+    depth: usize,
+}
+
+impl Writer {
+    fn at_line_start(&self) -> bool {
+        self.out.is_empty() || self.out.ends_with('\n')
+    }
+
+    fn push(&mut self, s: &str) {
+        if self.at_line_start() {
+            for _ in 0..self.depth {
+                self.out.push_str(&self.unit);
+            }
+        }
+        self.out.push_str(s);
+    }
+
+    fn space(&mut self) {
+        if !self.at_line_start() && !self.out.ends_with(' ') {
+            self.out.push(' ');
+        }
+    }
+
+    fn newline(&mut self) {
+        let trimmed = self.out.trim_end_matches(' ').len();
+        self.out.truncate(trimmed);
+        if !self.at_line_start() {
+            self.out.push('\n');
+        }
+    }
+}
+
+/// Index just past a quoted string starting at `start`. Plain quotes end at
+/// a newline too, so an unterminated string can't swallow the file.
+fn string_end(chars: &[char], start: usize) -> usize {
+    let quote = chars[start];
+    let mut i = start + 1;
+    while i < chars.len() {
+        match chars[i] {
+            '\\' => i += 2,
+            c if c == quote => return i + 1,
+            '\n' if quote != '`' => return i,
+            _ => i += 1,
+        }
+    }
+    chars.len()
+}
+
+/// Index just past a regex literal (including flags) starting at `start`.
+fn regex_end(chars: &[char], start: usize) -> usize {
+    let mut i = start + 1;
+    let mut in_class = false;
+    while i < chars.len() {
+        match chars[i] {
+            '\\' => i += 1,
+            '[' => in_class = true,
+            ']' => in_class = false,
+            '/' if !in_class => {
+                i += 1;
+                while i < chars.len() && chars[i].is_ascii_alphabetic() {
+                    i += 1;
+                }
+                return i;
+            }
+            '\n' => return i,
+            _ => {}
+        }
+        i += 1;
+    }
+    chars.len()
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '$'
+}
+
+/// Re-indent brace-delimited code: a line break after `{`, `;` and `}`, one
+/// indent level per open brace. Strings, comments and (for JS) regex literals
+/// are copied verbatim, and `;` inside parentheses (`for (;;)`) doesn't break.
+/// Existing line breaks are kept, since JS semicolon insertion may rely on them.
+fn reflow(content: &str, unit: String, js: bool) -> String {
+    let chars: Vec<char> = content.chars().collect();
+    let mut w = Writer { out: String::with_capacity(content.len() * 2), unit, depth: 0 };
+    let mut parens = 0usize;
+    let mut paren_stack: Vec<usize> = Vec::new();
+    // Last significant token, for telling regex literals from division
+    let mut last_char: Option<char> = None;
+    let mut last_word = String::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        let next = chars.get(i + 1).copied();
+
+        if c == '\n' {
+            w.newline();
+            i += 1;
+            continue;
+        }
+        if c.is_whitespace() {
+            w.space();
+            i += 1;
+            continue;
+        }
+
+        if c == '"' || c == '\'' || (js && c == '`') {
+            let end = string_end(&chars, i);
+            w.push(&chars[i..end].iter().collect::<String>());
+            last_char = Some(c);
+            i = end;
+            continue;
+        }
+        if c == '/' && next == Some('*') {
+            let end = (i + 2..chars.len().saturating_sub(1))
+                .find(|&j| chars[j] == '*' && chars[j + 1] == '/')
+                .map_or(chars.len(), |j| j + 2);
+            w.push(&chars[i..end].iter().collect::<String>());
+            i = end;
+            continue;
+        }
+        if js && c == '/' && next == Some('/') {
+            let end = chars[i..].iter().position(|&ch| ch == '\n').map_or(chars.len(), |p| i + p);
+            w.push(&chars[i..end].iter().collect::<String>());
+            w.newline();
+            i = end;
+            continue;
+        }
+        if js && c == '/' {
+            let regex_allowed = match last_char {
+                None => true,
+                Some('a') => REGEX_KEYWORDS.contains(&last_word.as_str()),
+                Some(prev) => "(,=:[!&|?{};+-*%<>~^".contains(prev),
+            };
+            if regex_allowed {
+                let end = regex_end(&chars, i);
+                w.push(&chars[i..end].iter().collect::<String>());
+                last_char = Some('/');
+                i = end;
+                continue;
+            }
+        }
+
+        if is_word_char(c) {
+            let end = chars[i..].iter().position(|&ch| !is_word_char(ch)).map_or(chars.len(), |p| i + p);
+            last_word = chars[i..end].iter().collect();
+            w.push(&last_word);
+            last_char = Some('a');
+            i = end;
+            continue;
+        }
+
+        match c {
+            '{' => {
+                if w.out.ends_with(|ch: char| is_word_char(ch) || ")]\"'".contains(ch)) {
+                    w.space();
+                }
+                w.push("{");
+                w.depth += 1;
+                paren_stack.push(parens);
+                parens = 0;
+                w.newline();
+            }
+            '}' => {
+                w.depth = w.depth.saturating_sub(1);
+                parens = paren_stack.pop().unwrap_or(0);
+                w.newline();
+                w.push("}");
+
+                // Keep `},` `})` `};` `}(` and `} else` together
+                let mut j = i + 1;
+                while j < chars.len() && chars[j] == ' ' {
+                    j += 1;
+                }
+                let word: String = chars[j..].iter().take_while(|&&ch| is_word_char(ch)).collect();
+                match chars.get(j) {
+                    Some(',' | ';' | ')' | ']' | '(' | '.') => {}
+                    _ if ["else", "catch", "finally", "while"].contains(&word.as_str()) => w.space(),
+                    _ => w.newline(),
+                }
+            }
+            ';' => {
+                w.push(";");
+                if parens == 0 {
+                    w.newline();
+                }
+            }
+            '(' => {
+                parens += 1;
+                w.push("(");
+            }
+            ')' => {
+                parens = parens.saturating_sub(1);
+                w.push(")");
+            }
+            _ => w.push(&c.to_string()),
+        }
+        last_char = Some(c);
+        i += 1;
+    }
+
+    w.newline();
+    w.out
+}
+
+// ── Commands ──────────────────────────────────────────────────────────────────
+
+/// Pretty-print minified JavaScript/TypeScript, CSS or JSON. JS and CSS get a
+/// brace/semicolon reflow (no full parse, so it never rejects odd input); JSON
+/// is re-serialized like `format_structured`.
+#[tauri::command]
+pub fn beautify_code(content: String, language: String, indent: Option<usize>) -> Result<String, PeekError> {
+    let indent = indent.unwrap_or(DEFAULT_INDENT);
+    match language.to_lowercase().as_str() {
+        "json" => Ok(crate::format::format_json(&content, indent, false)?),
+        "javascript" | "js" | "mjs" | "cjs" | "jsx" | "typescript" | "ts" | "tsx" => {
+            Ok(reflow(&content, " ".repeat(indent), true))
+        }
+        "css" | "scss" | "less" => Ok(reflow(&content, " ".repeat(indent), false)),
+        other => Err(format!("Unsupported language: {}", other).into()),
+    }
+}
@@ -0,0 +1,98 @@
+use std::fs;
+use std::path::Path;
+use std::sync::Mutex;
+
+use serde::Serialize;
+
+use crate::error::{AtPath, PeekError};
+
+// ── Types ─────────────────────────────────────────────────────────────────────
+
+/// A file to open, optionally at a 1-based line and column.
+#[derive(Serialize, Clone, Debug)]
+pub struct OpenTarget {
+    pub path: String,
+    pub line: Option<usize>,
+    pub column: Option<usize>,
+}
+
+/// Managed state: targets from the command line, emitted once the UI is ready.
+pub struct LaunchTargets(pub Mutex<Vec<OpenTarget>>);
+
+impl LaunchTargets {
+    /// Parse the process arguments, skipping any that don't name an existing file.
+    pub fn from_args() -> Self {
+        let targets = std::env::args()
+            .skip(1)
+            .filter(|arg| !arg.starts_with('-'))
+            .filter_map(|arg| resolve_open_target(&arg).ok())
+            .collect();
+        LaunchTargets(Mutex::new(targets))
+    }
+}
+
+// ── Parsing ───────────────────────────────────────────────────────────────────
+
+/// Split `path[:line[:col]]`. Only trailing numeric segments are taken, and a
+/// lone drive letter (`C:`) is never treated as a path/line separator.
+fn split_location(arg: &str) -> (&str, Vec<usize>) {
+    let mut path = arg;
+    let mut numbers = Vec::new();
+
+    for _ in 0..2 {
+        match path.rsplit_once(':') {
+            Some((rest, n))
+                if !n.is_empty()
+                    && n.bytes().all(|b| b.is_ascii_digit())
+                    && !is_drive_letter(rest) =>
+            {
+                match n.parse() {
+                    Ok(number) => numbers.push(number),
+                    Err(_) => break,
+                }
+                path = rest;
+            }
+            _ => break,
+        }
+    }
+
+    numbers.reverse();
+    (path, numbers)
+}
+
+fn is_drive_letter(s: &str) -> bool {
+    s.len() == 1 && s.as_bytes()[0].is_ascii_alphabetic()
+}
+
+/// Resolve a CLI-style open spec to an existing file.
+pub fn resolve_open_target(arg: &str) -> Result<OpenTarget, PeekError> {
+    // A file whose name really contains `:N` wins over the location syntax
+    let (path, numbers) = if Path::new(arg).is_file() {
+        (arg, Vec::new())
+    } else {
+        split_location(arg)
+    };
+
+    let metadata = fs::metadata(path).at(path)?;
+    if !metadata.is_file() {
+        return Err(PeekError::from("Not a file").at(path));
+    }
+
+    let resolved = fs::canonicalize(path)
+        .map(|p| p.to_string_lossy().to_string())
+        .unwrap_or_else(|_| path.to_string());
+
+    Ok(OpenTarget {
+        path: resolved,
+        line: numbers.first().copied(),
+        column: numbers.get(1).copied(),
+    })
+}
+
+// ── Commands ──────────────────────────────────────────────────────────────────
+
+/// Parse a `path:line:col` spec and validate that the file exists.
+#[tauri::command]
+pub fn parse_open_target(arg: String) -> Result<OpenTarget, PeekError> {
+    resolve_open_target(&arg)
+}
@@ -0,0 +1,78 @@
+use std::borrow::Cow;
+use std::fs;
+use std::sync::Mutex;
+
+use arboard::{Clipboard, ImageData};
+
+use crate::error::{AtPath, PeekError};
+use crate::LineIndexCache;
+
+/// Managed state: the system clipboard handle, opened on first use. It is
+/// kept for the app's lifetime because on X11/Wayland copied data is served
+/// by its owner and would vanish if the handle were dropped.
+pub struct ClipboardState(Mutex<Option<Clipboard>>);
+
+impl ClipboardState {
+    pub fn new() -> Self {
+        ClipboardState(Mutex::new(None))
+    }
+
+    /// Run `f` with the clipboard, opening it if needed. Fails with
+    /// `CLIPBOARD_UNAVAILABLE` where there is none (e.g. headless).
+    fn with<T>(&self, f: impl FnOnce(&mut Clipboard) -> Result<T, arboard::Error>) -> Result<T, PeekError> {
+        let mut clipboard = self.0.lock()?;
+        if clipboard.is_none() {
+            *clipboard = Some(Clipboard::new().map_err(|e| PeekError::clipboard_unavailable(e.to_string()))?);
+        }
+        let clipboard = clipboard.as_mut().ok_or_else(|| PeekError::clipboard_unavailable("No clipboard"))?;
+        f(clipboard).map_err(|e| PeekError::from(e.to_string()))
+    }
+}
+
+// ── Commands ──────────────────────────────────────────────────────────────────
+
+#[tauri::command]
+pub fn copy_to_clipboard(text: String, state: tauri::State<'_, ClipboardState>) -> Result<(), PeekError> {
+    state.with(|clipboard| clipboard.set_text(text))
+}
+
+/// Copy a line range of an indexed file straight from disk, so a multi-MB
+/// selection never passes through the webview. Returns the bytes copied.
+#[tauri::command]
+pub fn copy_lines_to_clipboard(
+    path: String,
+    start_line: usize,
+    line_count: usize,
+    cache: tauri::State<'_, LineIndexCache>,
+    state: tauri::State<'_, ClipboardState>,
+) -> Result<usize, PeekError> {
+    let path = crate::paths::resolve(&path);
+    let lines = {
+        let mut cache = cache.0.lock()?;
+        let index = cache.get(&path).ok_or("File not indexed. Call index_file first.")?;
+        crate::read_indexed_lines(&path, index, start_line, line_count)?
+    };
+    let length = lines.content.len();
+    state.with(|clipboard| clipboard.set_text(lines.content))?;
+    Ok(length)
+}
+
+/// Put a PNG image file on the clipboard.
+#[tauri::command]
+pub fn copy_image_to_clipboard(path: String, state: tauri::State<'_, ClipboardState>) -> Result<(), PeekError> {
+    let path = crate::paths::resolve(&path);
+    let bytes = fs::read(&path).at(&path)?;
+    let image = tauri::image::Image::from_bytes(&bytes).map_err(|e| format!("Unsupported image: {}", e))?;
+    let data = ImageData {
+        width: image.width() as usize,
+        height: image.height() as usize,
+        bytes: Cow::Borrowed(image.rgba()),
+    };
+    state.with(|clipboard| clipboard.set_image(data))
+}
+
+/// The clipboard's text content. Fails if it holds no text.
+#[tauri::command]
+pub fn read_clipboard(state: tauri::State<'_, ClipboardState>) -> Result<String, PeekError> {
+    state.with(|clipboard| clipboard.get_text())
+}
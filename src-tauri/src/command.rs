@@ -0,0 +1,150 @@
+use std::collections::HashMap;
+use std::io::Read;
+use std::process::{Child, Command, Stdio};
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Mutex;
+use std::thread;
+
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::error::PeekError;
+
+// ── Types ─────────────────────────────────────────────────────────────────────
+
+#[derive(Serialize, Clone)]
+struct CommandOutput {
+    handle: u32,
+    stream: &'static str, // "stdout" | "stderr"
+    data: String,
+}
+
+#[derive(Serialize, Clone)]
+struct CommandExit {
+    handle: u32,
+    /// `None` if the process was killed by a signal (including `stop_command`).
+    code: Option<i32>,
+}
+
+/// Managed state: running `stream_command` processes by handle.
+pub struct CommandStreams {
+    children: Mutex<HashMap<u32, Child>>,
+    next_id: AtomicU32,
+}
+
+impl CommandStreams {
+    pub fn new() -> Self {
+        CommandStreams {
+            children: Mutex::new(HashMap::new()),
+            next_id: AtomicU32::new(1),
+        }
+    }
+}
+
+// ── Helpers ───────────────────────────────────────────────────────────────────
+
+/// Forward everything `source` produces as `command-output` events until it
+/// closes. A character split across reads is held back until it's whole.
+fn spawn_reader(app: AppHandle, handle: u32, stream: &'static str, mut source: impl Read + Send + 'static) -> thread::JoinHandle<()> {
+    thread::spawn(move || {
+        let mut buf = [0u8; 4096];
+        let mut pending = Vec::new();
+        loop {
+            match source.read(&mut buf) {
+                Ok(0) | Err(_) => break,
+                Ok(n) => {
+                    pending.extend_from_slice(&buf[..n]);
+                    let complete = pending.len() - crate::incomplete_utf8_tail(&pending);
+                    if complete == 0 {
+                        continue;
+                    }
+                    let data = String::from_utf8_lossy(&pending[..complete]).to_string();
+                    pending.drain(..complete);
+                    let _ = app.emit("command-output", CommandOutput { handle, stream, data });
+                }
+            }
+        }
+        // Whatever is left never became a whole character
+        if !pending.is_empty() {
+            let data = String::from_utf8_lossy(&pending).to_string();
+            let _ = app.emit("command-output", CommandOutput { handle, stream, data });
+        }
+    })
+}
+
+// ── Commands ──────────────────────────────────────────────────────────────────
+
+/// Run a program with piped output, for read-only monitoring (`kubectl logs
+/// -f`, `tail -f`) where a terminal's PTY would get in the way. Output arrives
+/// as `command-output` events tagged with the returned handle and the stream
+/// it came from; `command-exit` follows once the program has exited and both
+/// streams are drained. stdin is closed. `stop_command` kills it.
+#[tauri::command]
+pub fn stream_command(
+    program: String,
+    args: Option<Vec<String>>,
+    cwd: Option<String>,
+    app: AppHandle,
+    state: tauri::State<'_, CommandStreams>,
+) -> Result<u32, PeekError> {
+    let mut command = Command::new(&program);
+    command
+        .args(args.unwrap_or_default())
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    if let Some(dir) = cwd {
+        command.current_dir(crate::paths::resolve_path(&dir));
+    }
+    #[cfg(windows)]
+    {
+        use std::os::windows::process::CommandExt;
+        const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+        command.creation_flags(CREATE_NO_WINDOW);
+    }
+    let mut child = command.spawn().map_err(|e| match e.kind() {
+        std::io::ErrorKind::NotFound => PeekError::not_found(format!("Program not found: {}", program)),
+        _ => format!("Failed to start {}: {}", program, e).into(),
+    })?;
+
+    let handle = state.next_id.fetch_add(1, Ordering::Relaxed);
+    let mut readers = Vec::new();
+    if let Some(stdout) = child.stdout.take() {
+        readers.push(spawn_reader(app.clone(), handle, "stdout", stdout));
+    }
+    if let Some(stderr) = child.stderr.take() {
+        readers.push(spawn_reader(app.clone(), handle, "stderr", stderr));
+    }
+    state.children.lock()?.insert(handle, child);
+
+    // Report the exit once all output is out, so it's the last event
+    thread::spawn(move || {
+        for reader in readers {
+            let _ = reader.join();
+        }
+        let child = app
+            .state::<CommandStreams>()
+            .children
+            .lock()
+            .ok()
+            .and_then(|mut children| children.remove(&handle));
+        let code = child
+            .and_then(|mut child| child.wait().ok())
+            .and_then(|status| status.code());
+        let _ = app.emit("command-exit", CommandExit { handle, code });
+    });
+
+    Ok(handle)
+}
+
+/// Kill a program started with `stream_command`. Its `command-exit` event
+/// still follows.
+#[tauri::command]
+pub fn stop_command(handle: u32, state: tauri::State<'_, CommandStreams>) -> Result<(), PeekError> {
+    let mut children = state.children.lock()?;
+    let child = children
+        .get_mut(&handle)
+        .ok_or_else(|| format!("Command {} is not running", handle))?;
+    child.kill()?;
+    Ok(())
+}
@@ -0,0 +1,85 @@
+use std::fs;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+use similar::{capture_diff_slices_deadline, Algorithm};
+
+use crate::error::{AtPath, PeekError};
+
+/// Above this many lines (old + new) Myers gets slow; patience copes better.
+const PATIENCE_THRESHOLD: usize = 20_000;
+/// Give up refining the diff after this long and return a coarser result.
+const DIFF_DEADLINE: Duration = Duration::from_secs(2);
+
+// ── Types ─────────────────────────────────────────────────────────────────────
+
+/// One diff segment over 0-based line ranges of the old and new text.
+#[derive(Serialize, Clone, Debug)]
+pub struct DiffOp {
+    pub op: &'static str, // "equal" | "insert" | "delete" | "replace"
+    old_start: usize,
+    old_len: usize,
+    new_start: usize,
+    new_len: usize,
+}
+
+// ── Helpers ───────────────────────────────────────────────────────────────────
+
+fn read_text(path: &Path) -> Result<String, PeekError> {
+    crate::regular_file_metadata(path).at(path)?;
+    let bytes = fs::read(path).at(path)?;
+    Ok(String::from_utf8_lossy(&bytes).to_string())
+}
+
+/// Line diff of two texts. Line endings are ignored, so a CRLF file compares
+/// equal to the same text with LF.
+pub fn diff_lines(old: &str, new: &str) -> Vec<DiffOp> {
+    let old_lines: Vec<&str> = old.lines().collect();
+    let new_lines: Vec<&str> = new.lines().collect();
+
+    let algorithm = if old_lines.len() + new_lines.len() > PATIENCE_THRESHOLD {
+        Algorithm::Patience
+    } else {
+        Algorithm::Myers
+    };
+    let deadline = Some(Instant::now() + DIFF_DEADLINE);
+
+    capture_diff_slices_deadline(algorithm, &old_lines, &new_lines, deadline)
+        .into_iter()
+        .map(|op| {
+            let (tag, old_range, new_range) = op.as_tag_tuple();
+            DiffOp {
+                op: match tag {
+                    similar::DiffTag::Equal => "equal",
+                    similar::DiffTag::Insert => "insert",
+                    similar::DiffTag::Delete => "delete",
+                    similar::DiffTag::Replace => "replace",
+                },
+                old_start: old_range.start,
+                old_len: old_range.len(),
+                new_start: new_range.start,
+                new_len: new_range.len(),
+            }
+        })
+        .collect()
+}
+
+// ── Commands ──────────────────────────────────────────────────────────────────
+
+/// Line diff between two files on disk.
+#[tauri::command]
+pub fn diff_files(path_a: String, path_b: String) -> Result<Vec<DiffOp>, PeekError> {
+    let (path_a, path_b) = (crate::paths::resolve(&path_a), crate::paths::resolve(&path_b));
+    let a = read_text(Path::new(&path_a))?;
+    let b = read_text(Path::new(&path_b))?;
+    Ok(diff_lines(&a, &b))
+}
+
+/// Line diff between a file on disk (old) and an editor buffer (new).
+#[tauri::command]
+pub fn diff_file_with_content(path: String, content: String) -> Result<Vec<DiffOp>, PeekError> {
+    let path = crate::paths::resolve(&path);
+    let on_disk = read_text(Path::new(&path))?;
+    Ok(diff_lines(&on_disk, &content))
+}
@@ -0,0 +1,161 @@
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+use tauri::{AppHandle, Emitter};
+
+use crate::error::PeekError;
+
+/// Minimum time between `hash-progress` events.
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(100);
+
+// ── Types ─────────────────────────────────────────────────────────────────────
+
+#[derive(Serialize)]
+pub struct FileHash {
+    /// Relative to the root, `/`-separated on every platform.
+    path: String,
+    hash: String, // blake3, hex
+    size: u64,
+}
+
+#[derive(Serialize)]
+pub struct DirectoryHash {
+    /// blake3 over every file's path and hash in path order: equal tree hashes
+    /// mean the same files with the same contents.
+    tree_hash: String,
+    files: Vec<FileHash>,
+    /// Files that couldn't be read, left out of `tree_hash`.
+    skipped: Vec<String>,
+}
+
+#[derive(Serialize, Clone)]
+struct HashProgress {
+    root: String,
+    files_hashed: usize,
+    bytes_hashed: u64,
+}
+
+/// Managed state: cancel flags for running `hash_directory` calls by root.
+pub struct HashJobs(Mutex<HashMap<String, Arc<AtomicBool>>>);
+
+impl HashJobs {
+    pub fn new() -> Self {
+        HashJobs(Mutex::new(HashMap::new()))
+    }
+}
+
+// ── Helpers ───────────────────────────────────────────────────────────────────
+
+fn hash_file(path: &Path) -> std::io::Result<(String, u64)> {
+    let mut file = std::fs::File::open(path)?;
+    let mut hasher = blake3::Hasher::new();
+    let size = std::io::copy(&mut file, &mut hasher)?;
+    Ok((hasher.finalize().to_hex().to_string(), size))
+}
+
+fn hash_tree(app: &AppHandle, root: &str, respect_gitignore: bool, cancel: &AtomicBool) -> Result<DirectoryHash, PeekError> {
+    let root_path = Path::new(root);
+    let walker = ignore::WalkBuilder::new(root_path)
+        .hidden(false)
+        .git_ignore(respect_gitignore)
+        .git_global(respect_gitignore)
+        .git_exclude(respect_gitignore)
+        .ignore(respect_gitignore)
+        .filter_entry(|entry| entry.file_name() != ".git")
+        .build();
+
+    let mut files = Vec::new();
+    let mut skipped = Vec::new();
+    let mut bytes_hashed = 0;
+    let mut last_progress = Instant::now();
+    let progress = |files_hashed: usize, bytes_hashed: u64| {
+        let _ = app.emit("hash-progress", HashProgress { root: root.to_string(), files_hashed, bytes_hashed });
+    };
+
+    for entry in walker {
+        if cancel.load(Ordering::Relaxed) {
+            return Err(PeekError::cancelled("Hashing was cancelled"));
+        }
+        let Ok(entry) = entry else { continue };
+        if !entry.file_type().is_some_and(|kind| kind.is_file()) {
+            continue;
+        }
+        let relative = entry.path().strip_prefix(root_path).unwrap_or(entry.path());
+        let relative = relative.components().map(|c| c.as_os_str().to_string_lossy()).collect::<Vec<_>>().join("/");
+        match hash_file(entry.path()) {
+            Ok((hash, size)) => {
+                bytes_hashed += size;
+                files.push(FileHash { path: relative, hash, size });
+            }
+            Err(_) => skipped.push(relative),
+        }
+        if last_progress.elapsed() >= PROGRESS_INTERVAL {
+            progress(files.len(), bytes_hashed);
+            last_progress = Instant::now();
+        }
+    }
+    progress(files.len(), bytes_hashed);
+
+    // Walk order depends on the file system; the tree hash mustn't
+    files.sort_by(|a, b| a.path.cmp(&b.path));
+    let mut tree = blake3::Hasher::new();
+    for file in &files {
+        tree.update(file.path.as_bytes());
+        tree.update(&[0]);
+        tree.update(file.hash.as_bytes());
+        tree.update(&[0]);
+    }
+    Ok(DirectoryHash { tree_hash: tree.finalize().to_hex().to_string(), files, skipped })
+}
+
+// ── Commands ──────────────────────────────────────────────────────────────────
+
+/// Fingerprint every file under `root` with blake3, plus a combined tree
+/// hash for a cheap "did anything change?" check between runs. With
+/// `respect_gitignore` (default on) files ignored by `.gitignore`, `.ignore`
+/// and git's excludes are left out; `.git` itself always is. Reports
+/// `hash-progress` events while it runs; `cancel_hash_directory` stops it with
+/// `CANCELLED`.
+#[tauri::command]
+pub async fn hash_directory(
+    root: String,
+    respect_gitignore: Option<bool>,
+    app: AppHandle,
+    state: tauri::State<'_, HashJobs>,
+) -> Result<DirectoryHash, PeekError> {
+    let root = crate::paths::resolve(&root);
+    if !Path::new(&root).is_dir() {
+        return Err(format!("Not a directory: {}", root).into());
+    }
+    let cancel = Arc::new(AtomicBool::new(false));
+    state.0.lock()?.insert(root.clone(), cancel.clone());
+
+    let job_root = root.clone();
+    let job_cancel = cancel.clone();
+    let hashed = tauri::async_runtime::spawn_blocking(move || {
+        hash_tree(&app, &job_root, respect_gitignore.unwrap_or(true), &job_cancel)
+    })
+    .await
+    ;
+
+    // A newer call for the same root owns the slot now
+    let mut jobs = state.0.lock()?;
+    if jobs.get(&root).is_some_and(|current| Arc::ptr_eq(current, &cancel)) {
+        jobs.remove(&root);
+    }
+    hashed?
+}
+
+/// Stop a running `hash_directory` for `root`. Does nothing if none is running.
+#[tauri::command]
+pub fn cancel_hash_directory(root: String, state: tauri::State<'_, HashJobs>) -> Result<(), PeekError> {
+    let root = crate::paths::resolve(&root);
+    if let Some(cancel) = state.0.lock()?.get(&root) {
+        cancel.store(true, Ordering::Relaxed);
+    }
+    Ok(())
+}
@@ -0,0 +1,258 @@
+use std::io::Read;
+use std::path::Path;
+
+use chardetng::EncodingDetector;
+use encoding_rs::{Encoding, UTF_16BE, UTF_16LE, UTF_8};
+use serde::Serialize;
+
+use crate::error::{AtPath, PeekError};
+
+/// How much of a file's head is sampled to guess its encoding.
+pub const SAMPLE_BYTES: usize = 64 * 1024;
+
+/// Top-level domains whose regional expectations make the detector favor a
+/// different legacy encoding, used to find runner-up guesses.
+const REGIONAL_HINTS: &[&str] = &[
+    "ru", "jp", "cn", "tw", "kr", "pl", "cz", "gr", "tr", "il", "lt", "vn", "th", "sa",
+];
+
+/// Past this many invalid sequences the file is plainly not UTF-8; stop.
+const MAX_ENCODING_ERRORS: usize = 1000;
+/// Bytes of context shown on each side of an invalid sequence.
+const ERROR_CONTEXT_BYTES: usize = 16;
+
+#[derive(Serialize)]
+pub struct EncodingCandidate {
+    encoding: String, // WHATWG name, for `read_file_content`'s `force_encoding`
+    /// Relative, for ranking: 1.0 for a BOM or valid non-ASCII UTF-8, lower
+    /// for guesses the detector is less sure of. Not a probability.
+    confidence: f32,
+}
+
+#[derive(Serialize)]
+pub struct EncodingCandidates {
+    /// Encoding named by a byte order mark at the start of the file, if any.
+    bom: Option<String>,
+    candidates: Vec<EncodingCandidate>,
+}
+
+/// One invalid UTF-8 sequence.
+#[derive(Serialize)]
+pub struct EncodingError {
+    byte_offset: u64,
+    length: usize, // bytes in the invalid sequence
+    line: usize,   // 0-based
+    /// The offending bytes in hex, e.g. "C3 28".
+    bytes: String,
+    /// Up to `ERROR_CONTEXT_BYTES` on either side, decoded lossily.
+    context: String,
+}
+
+#[derive(Serialize)]
+pub struct EncodingErrors {
+    errors: Vec<EncodingError>,
+    /// Stopped at `MAX_ENCODING_ERRORS`.
+    truncated: bool,
+}
+
+// ── Detection ─────────────────────────────────────────────────────────────────
+
+fn is_utf8_sample(sample: &[u8]) -> bool {
+    match std::str::from_utf8(sample) {
+        Ok(_) => true,
+        // A character cut off by the end of the sample doesn't count against it
+        Err(e) => e.error_len().is_none(),
+    }
+}
+
+fn detector_for(sample: &[u8]) -> EncodingDetector {
+    let mut detector = EncodingDetector::new();
+    // Not `last`: the sample is usually only the head of the file
+    detector.feed(sample, false);
+    detector
+}
+
+/// Guess the encoding of a file from its first bytes: a BOM wins, then UTF-8
+/// if the sample is valid UTF-8, and otherwise the legacy encoding whose byte
+/// statistics fit best (chardetng, as browsers use for unlabeled pages).
+pub fn detect(sample: &[u8]) -> &'static Encoding {
+    if let Some((encoding, _)) = Encoding::for_bom(sample) {
+        return encoding;
+    }
+    if is_utf8_sample(sample) {
+        return UTF_8;
+    }
+    detector_for(sample).guess(None, false)
+}
+
+/// Every plausible encoding for a sample, best first. The detector's own
+/// guess leads, then what it would pick given each regional expectation,
+/// then any other encoding the sample decodes in without errors.
+fn rank_candidates(sample: &[u8]) -> Vec<EncodingCandidate> {
+    let mut ranked: Vec<(&'static Encoding, f32)> = Vec::new();
+    let mut push = |encoding: &'static Encoding, confidence: f32| {
+        if !ranked.iter().any(|(seen, _)| *seen == encoding) {
+            ranked.push((encoding, confidence));
+        }
+    };
+
+    if let Some((encoding, _)) = Encoding::for_bom(sample) {
+        push(encoding, 1.0);
+    }
+    if is_utf8_sample(sample) {
+        push(UTF_8, if sample.is_ascii() { 0.9 } else { 1.0 });
+    }
+
+    let detector = detector_for(sample);
+    let (guess, sure) = detector.guess_assess(None, false);
+    push(guess, if sure { 0.8 } else { 0.5 });
+    for tld in REGIONAL_HINTS {
+        let (encoding, sure) = detector.guess_assess(Some(tld.as_bytes()), false);
+        push(encoding, if sure { 0.4 } else { 0.2 });
+    }
+
+    for encoding in [
+        encoding_rs::WINDOWS_1252, encoding_rs::ISO_8859_15, encoding_rs::WINDOWS_1250,
+        encoding_rs::WINDOWS_1251, encoding_rs::KOI8_R, encoding_rs::SHIFT_JIS,
+        encoding_rs::EUC_JP, encoding_rs::GBK, encoding_rs::BIG5, encoding_rs::EUC_KR,
+    ] {
+        if encoding.decode_without_bom_handling_and_without_replacement(sample).is_some() {
+            push(encoding, 0.1);
+        }
+    }
+
+    ranked
+        .into_iter()
+        .map(|(encoding, confidence)| EncodingCandidate { encoding: encoding.name().to_string(), confidence })
+        .collect()
+}
+
+/// Whether byte-level `\n` scanning finds exactly the line breaks of text in
+/// this encoding (not so for UTF-16, where `0x0A` is half a character).
+pub fn is_line_indexable(encoding: &'static Encoding) -> bool {
+    encoding.is_ascii_compatible()
+}
+
+// ── Conversion ────────────────────────────────────────────────────────────────
+
+/// Decode a slice of a file. `at_start` says the slice begins at byte 0, where
+/// a BOM for `encoding` is dropped rather than shown as a character.
+pub fn decode(bytes: &[u8], encoding: &'static Encoding, at_start: bool) -> String {
+    let bytes = match Encoding::for_bom(bytes) {
+        Some((bom_encoding, bom_len)) if at_start && bom_encoding == encoding => &bytes[bom_len..],
+        _ => bytes,
+    };
+    encoding.decode_without_bom_handling(bytes).0.into_owned()
+}
+
+/// The encoding a WHATWG label (e.g. from `detect_encoding_candidates`)
+/// names; an unknown label fails with `UNKNOWN_ENCODING`.
+pub fn for_label(label: &str) -> Result<&'static Encoding, PeekError> {
+    Encoding::for_label(label.trim().as_bytes()).ok_or_else(|| PeekError::unknown_encoding(label))
+}
+
+/// Encode editor text back into the file's encoding. Fails rather than
+/// writing a lossy substitute for characters the encoding can't represent.
+/// A BOM kept in the text as U+FEFF comes out as the encoding's BOM.
+pub fn encode(text: &str, encoding: &'static Encoding) -> Result<Vec<u8>, PeekError> {
+    // encoding_rs decodes UTF-16 but encodes it as UTF-8
+    if encoding == UTF_16LE {
+        return Ok(text.encode_utf16().flat_map(u16::to_le_bytes).collect());
+    }
+    if encoding == UTF_16BE {
+        return Ok(text.encode_utf16().flat_map(u16::to_be_bytes).collect());
+    }
+    let (bytes, used, unmappable) = encoding.encode(text);
+    if unmappable || used != encoding {
+        return Err(format!("Content can't be represented in {}", encoding.name()).into());
+    }
+    Ok(bytes.into_owned())
+}
+
+// ── Commands ──────────────────────────────────────────────────────────────────
+
+/// Ranked encoding guesses for a file (from its first `SAMPLE_BYTES`), for a
+/// "Reopen with Encoding" menu: the first candidate is what detection picks.
+#[tauri::command]
+pub fn detect_encoding_candidates(path: String) -> Result<EncodingCandidates, PeekError> {
+    let path = crate::paths::resolve(&path);
+    let file_path = Path::new(&path);
+    crate::regular_file_metadata(file_path).at(file_path)?;
+    let mut sample = Vec::new();
+    std::fs::File::open(file_path)
+        .at(file_path)?
+        .take(SAMPLE_BYTES as u64)
+        .read_to_end(&mut sample)?;
+
+    Ok(EncodingCandidates {
+        bom: Encoding::for_bom(&sample).map(|(encoding, _)| encoding.name().to_string()),
+        candidates: rank_candidates(&sample),
+    })
+}
+
+/// Every place a file isn't valid UTF-8: byte offset, line, the bad bytes and
+/// some text around them, so the editor can point at them and offer to
+/// reopen in another encoding rather than silently showing U+FFFD. Streams
+/// the file; an empty list means it's valid UTF-8.
+#[tauri::command]
+pub fn find_encoding_errors(path: String) -> Result<EncodingErrors, PeekError> {
+    let path = crate::paths::resolve(&path);
+    let file_path = Path::new(&path);
+    crate::regular_file_metadata(file_path).at(file_path)?;
+    let mut file = std::fs::File::open(file_path).at(file_path)?;
+
+    let mut errors = Vec::new();
+    let mut chunk = vec![0u8; SAMPLE_BYTES];
+    // Bytes not yet checked: an incomplete sequence from the last chunk plus the new one
+    let mut pending: Vec<u8> = Vec::new();
+    let mut pending_offset: u64 = 0;
+    let mut line = 0;
+    loop {
+        let n = file.read(&mut chunk)?;
+        let at_end = n == 0;
+        pending.extend_from_slice(&chunk[..n]);
+
+        let mut checked = 0;
+        let mut counted = 0; // newlines are counted up to here
+        while checked < pending.len() {
+            let error = match std::str::from_utf8(&pending[checked..]) {
+                Ok(_) => {
+                    checked = pending.len();
+                    break;
+                }
+                Err(error) => error,
+            };
+            let bad = checked + error.valid_up_to();
+            let length = match error.error_len() {
+                Some(length) => length,
+                // Cut off by the chunk: finish it with the next one
+                None if !at_end => {
+                    checked = bad;
+                    break;
+                }
+                None => pending.len() - bad,
+            };
+            line += memchr::memchr_iter(b'\n', &pending[counted..bad]).count();
+            counted = bad;
+            let context = &pending[bad.saturating_sub(ERROR_CONTEXT_BYTES)..(bad + length + ERROR_CONTEXT_BYTES).min(pending.len())];
+            errors.push(EncodingError {
+                byte_offset: pending_offset + bad as u64,
+                length,
+                line,
+                bytes: pending[bad..bad + length].iter().map(|b| format!("{:02X}", b)).collect::<Vec<_>>().join(" "),
+                context: String::from_utf8_lossy(context).to_string(),
+            });
+            if errors.len() >= MAX_ENCODING_ERRORS {
+                return Ok(EncodingErrors { errors, truncated: true });
+            }
+            checked = bad + length;
+        }
+        line += memchr::memchr_iter(b'\n', &pending[counted..checked]).count();
+
+        if at_end {
+            return Ok(EncodingErrors { errors, truncated: false });
+        }
+        pending.drain(..checked);
+        pending_offset += checked as u64;
+    }
+}
@@ -0,0 +1,182 @@
+use std::path::Path;
+
+use serde::Serialize;
+
+/// Error returned by every command and the helpers behind them, serialized
+/// as `{ code, message, path }` so the frontend can branch on `code` instead
+/// of parsing English text.
+///
+/// Helpers build the variant that fits with its constructor
+/// (`PeekError::conflict(...)`) and let `?` turn `io::Error`s into
+/// `NotFound` / `PermissionDenied` / `Io` by kind; plain strings become
+/// `Other`. `message` is the detail (e.g. `<mtime>:<hash>` for `CONFLICT`);
+/// `PARSE_ERROR` also carries `line` and `column`.
+/// `path` names the file the error is about: commands set it to the file
+/// they were given (see `AtPath`), unless a helper already named another
+/// (e.g. the destination of a copy).
+#[derive(Debug, Serialize)]
+#[serde(tag = "code", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum PeekError {
+    NotFound { message: String, path: Option<String> },
+    AlreadyExists { message: String, path: Option<String> },
+    PermissionDenied { message: String, path: Option<String> },
+    IsDirectory { message: String, path: Option<String> },
+    IsFifo { message: String, path: Option<String> },
+    IsSocket { message: String, path: Option<String> },
+    IsDevice { message: String, path: Option<String> },
+    /// A generated file (`/proc`, `/sys`) that reports no size; read it with
+    /// `read_stream_snapshot`, as for FIFOs and devices.
+    IsStream { message: String, path: Option<String> },
+    /// `read_lines` and friends before `index_file`.
+    NotIndexed { message: String, path: Option<String> },
+    /// The file changed on disk since it was indexed.
+    StaleIndex { message: String, path: Option<String> },
+    /// The file changed under the editor; saves give the current
+    /// `<mtime>:<hash>` as the message.
+    Conflict { message: String, path: Option<String> },
+    NotLocked { message: String, path: Option<String> },
+    NoSpace { message: String, path: Option<String> },
+    Timeout { message: String, path: Option<String> },
+    /// A long-running command stopped by its cancel command.
+    Cancelled { message: String, path: Option<String> },
+    /// Content that doesn't parse, at a 1-based `line` and `column`.
+    ParseError { message: String, line: usize, column: usize, path: Option<String> },
+    UnsupportedEncoding { message: String, path: Option<String> },
+    UnknownEncoding { message: String, path: Option<String> },
+    BackupFailed { message: String, path: Option<String> },
+    ClipboardUnavailable { message: String, path: Option<String> },
+    /// Any other I/O failure.
+    Io { message: String, path: Option<String> },
+    Other { message: String, path: Option<String> },
+}
+
+/// Constructors for the variants helpers raise themselves, one per variant:
+/// `PeekError::not_found("...")` and so on, with no path yet.
+macro_rules! constructors {
+    ($($name:ident => $variant:ident),* $(,)?) => {
+        impl PeekError {
+            $(
+                pub fn $name(message: impl Into<String>) -> Self {
+                    PeekError::$variant { message: message.into(), path: None }
+                }
+            )*
+        }
+    };
+}
+
+constructors! {
+    not_found => NotFound,
+    already_exists => AlreadyExists,
+    is_directory => IsDirectory,
+    is_fifo => IsFifo,
+    is_socket => IsSocket,
+    is_device => IsDevice,
+    is_stream => IsStream,
+    not_indexed => NotIndexed,
+    stale_index => StaleIndex,
+    conflict => Conflict,
+    not_locked => NotLocked,
+    no_space => NoSpace,
+    timeout => Timeout,
+    cancelled => Cancelled,
+    unsupported_encoding => UnsupportedEncoding,
+    unknown_encoding => UnknownEncoding,
+    backup_failed => BackupFailed,
+    clipboard_unavailable => ClipboardUnavailable,
+}
+
+impl PeekError {
+    fn from_io_kind(kind: std::io::ErrorKind, message: String) -> Self {
+        let path = None;
+        match kind {
+            std::io::ErrorKind::NotFound => PeekError::NotFound { message, path },
+            std::io::ErrorKind::AlreadyExists => PeekError::AlreadyExists { message, path },
+            std::io::ErrorKind::PermissionDenied => PeekError::PermissionDenied { message, path },
+            _ => PeekError::Io { message, path },
+        }
+    }
+
+    /// Content that doesn't parse, so the editor can place a diagnostic at
+    /// `line`:`column` (1-based).
+    pub fn parse_error(line: usize, column: usize, message: impl std::fmt::Display) -> Self {
+        PeekError::ParseError { message: message.to_string(), line, column, path: None }
+    }
+
+    /// `read_lines` and friends on a path `index_file` hasn't seen.
+    pub fn not_indexed_yet() -> Self {
+        PeekError::not_indexed("File not indexed. Call index_file first.")
+    }
+
+    /// Name the file the error is about, unless it already names one.
+    pub fn at(mut self, file: impl AsRef<Path>) -> Self {
+        match &mut self {
+            PeekError::NotFound { path, .. }
+            | PeekError::AlreadyExists { path, .. }
+            | PeekError::PermissionDenied { path, .. }
+            | PeekError::IsDirectory { path, .. }
+            | PeekError::IsFifo { path, .. }
+            | PeekError::IsSocket { path, .. }
+            | PeekError::IsDevice { path, .. }
+            | PeekError::IsStream { path, .. }
+            | PeekError::NotIndexed { path, .. }
+            | PeekError::StaleIndex { path, .. }
+            | PeekError::Conflict { path, .. }
+            | PeekError::NotLocked { path, .. }
+            | PeekError::NoSpace { path, .. }
+            | PeekError::Timeout { path, .. }
+            | PeekError::Cancelled { path, .. }
+            | PeekError::ParseError { path, .. }
+            | PeekError::UnsupportedEncoding { path, .. }
+            | PeekError::UnknownEncoding { path, .. }
+            | PeekError::BackupFailed { path, .. }
+            | PeekError::ClipboardUnavailable { path, .. }
+            | PeekError::Io { path, .. }
+            | PeekError::Other { path, .. } => {
+                path.get_or_insert_with(|| file.as_ref().to_string_lossy().into_owned());
+            }
+        }
+        self
+    }
+}
+
+impl From<String> for PeekError {
+    fn from(message: String) -> Self {
+        PeekError::Other { message, path: None }
+    }
+}
+
+impl From<&str> for PeekError {
+    fn from(error: &str) -> Self {
+        PeekError::from(error.to_string())
+    }
+}
+
+impl From<std::io::Error> for PeekError {
+    fn from(error: std::io::Error) -> Self {
+        PeekError::from_io_kind(error.kind(), error.to_string())
+    }
+}
+
+impl<T> From<std::sync::PoisonError<T>> for PeekError {
+    fn from(error: std::sync::PoisonError<T>) -> Self {
+        PeekError::from(error.to_string())
+    }
+}
+
+/// A blocking task that panicked or was cancelled.
+impl From<tauri::Error> for PeekError {
+    fn from(error: tauri::Error) -> Self {
+        PeekError::from(error.to_string())
+    }
+}
+
+/// `.at(path)` on the result of an operation on a file, to name the file.
+pub trait AtPath<T> {
+    fn at(self, file: impl AsRef<Path>) -> Result<T, PeekError>;
+}
+
+impl<T, E: Into<PeekError>> AtPath<T> for Result<T, E> {
+    fn at(self, file: impl AsRef<Path>) -> Result<T, PeekError> {
+        self.map_err(|e| e.into().at(file))
+    }
+}
@@ -0,0 +1,173 @@
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+
+use serde::Serialize;
+
+use crate::error::{AtPath, PeekError};
+
+/// Languages (ids or extensions) whose blocks are delimited by braces.
+const BRACE_LANGUAGES: &[&str] = &[
+    "c", "h", "cpp", "cc", "hpp", "cs", "csharp", "java", "javascript", "js", "jsx",
+    "typescript", "ts", "tsx", "rust", "rs", "go", "swift", "kotlin", "kt", "scala",
+    "php", "css", "scss", "less", "json", "dart",
+];
+
+const TAB_WIDTH: usize = 4;
+
+// ── Types ─────────────────────────────────────────────────────────────────────
+
+/// A foldable region over 0-based lines. `end_line` is the last line of the
+/// block (for braces, the line holding the closing bracket).
+#[derive(Serialize, Clone, Debug)]
+pub struct FoldRange {
+    start_line: usize,
+    end_line: usize,
+}
+
+// ── Indentation ───────────────────────────────────────────────────────────────
+
+/// Indent width of a line, or `None` for blank lines (which never end a block).
+fn indent_of(line: &str) -> Option<usize> {
+    let mut width = 0;
+    for ch in line.chars() {
+        match ch {
+            ' ' => width += 1,
+            '\t' => width += TAB_WIDTH - width % TAB_WIDTH,
+            '\r' | '\n' => return None,
+            _ => return Some(width),
+        }
+    }
+    None
+}
+
+/// A line starts a fold when the following non-blank lines are indented deeper.
+fn indentation_ranges<I: Iterator<Item = String>>(lines: I) -> Vec<FoldRange> {
+    let mut ranges = Vec::new();
+    let mut stack: Vec<(usize, usize)> = Vec::new(); // (indent, line)
+    let mut last_non_blank = 0;
+
+    for (i, line) in lines.enumerate() {
+        let Some(indent) = indent_of(&line) else { continue };
+        while let Some(&(top_indent, top_line)) = stack.last() {
+            if indent > top_indent {
+                break;
+            }
+            stack.pop();
+            if last_non_blank > top_line {
+                ranges.push(FoldRange { start_line: top_line, end_line: last_non_blank });
+            }
+        }
+        stack.push((indent, i));
+        last_non_blank = i;
+    }
+
+    while let Some((_, top_line)) = stack.pop() {
+        if last_non_blank > top_line {
+            ranges.push(FoldRange { start_line: top_line, end_line: last_non_blank });
+        }
+    }
+
+    ranges
+}
+
+// ── Braces ────────────────────────────────────────────────────────────────────
+
+/// Match `{}` / `[]` pairs across lines, skipping strings, char literals and
+/// `//` / `/* */` comments.
+fn brace_ranges<I: Iterator<Item = String>>(lines: I) -> Vec<FoldRange> {
+    let mut ranges = Vec::new();
+    let mut stack: Vec<(char, usize)> = Vec::new();
+    let mut in_block_comment = false;
+    let mut in_template = false; // backtick strings may span lines
+
+    for (i, line) in lines.enumerate() {
+        let chars: Vec<char> = line.chars().collect();
+        let mut j = 0;
+        let mut in_string: Option<char> = None;
+
+        while j < chars.len() {
+            let ch = chars[j];
+            let next = chars.get(j + 1).copied();
+
+            if in_block_comment {
+                if ch == '*' && next == Some('/') {
+                    in_block_comment = false;
+                    j += 1;
+                }
+            } else if in_template {
+                if ch == '\\' {
+                    j += 1;
+                } else if ch == '`' {
+                    in_template = false;
+                }
+            } else if let Some(quote) = in_string {
+                if ch == '\\' {
+                    j += 1;
+                } else if ch == quote {
+                    in_string = None;
+                }
+            } else {
+                match ch {
+                    '/' if next == Some('/') => break,
+                    '/' if next == Some('*') => {
+                        in_block_comment = true;
+                        j += 1;
+                    }
+                    '"' => in_string = Some('"'),
+                    '`' => in_template = true,
+                    // 'x' or '\x' char literals; anything else (e.g. Rust lifetimes) is ignored
+                    '\'' => {
+                        if chars.get(j + 2) == Some(&'\'') {
+                            j += 2;
+                        } else if next == Some('\\') && chars.get(j + 3) == Some(&'\'') {
+                            j += 3;
+                        }
+                    }
+                    '{' | '[' => stack.push((ch, i)),
+                    '}' | ']' => {
+                        let open = if ch == '}' { '{' } else { '[' };
+                        // Drop unmatched openers of the other kind rather than misfolding
+                        while let Some((top, start_line)) = stack.pop() {
+                            if top == open {
+                                if i > start_line {
+                                    ranges.push(FoldRange { start_line, end_line: i });
+                                }
+                                break;
+                            }
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            j += 1;
+        }
+    }
+
+    ranges
+}
+
+// ── Commands ──────────────────────────────────────────────────────────────────
+
+/// Compute fold ranges for a file, sorted by start line (outer ranges first).
+/// Brace matching is used for C-like `language`s, indentation otherwise.
+/// The file is streamed line by line, so this works on large files too.
+#[tauri::command]
+pub fn compute_fold_ranges(path: String, language: Option<String>) -> Result<Vec<FoldRange>, PeekError> {
+    let path = crate::paths::resolve(&path);
+    let file_path = Path::new(&path);
+    crate::regular_file_metadata(file_path).at(file_path)?;
+    let file = std::fs::File::open(file_path).at(file_path)?;
+
+    let lines = BufReader::with_capacity(64 * 1024, file)
+        .split(b'\n')
+        .map_while(Result::ok)
+        .map(|bytes| String::from_utf8_lossy(&bytes).to_string());
+
+    let use_braces = language
+        .map(|l| BRACE_LANGUAGES.contains(&l.to_lowercase().as_str()))
+        .unwrap_or(false);
+
+    let mut ranges = if use_braces { brace_ranges(lines) } else { indentation_ranges(lines) };
+    ranges.sort_by(|a, b| a.start_line.cmp(&b.start_line).then(b.end_line.cmp(&a.end_line)));
+    Ok(ranges)
+}
@@ -0,0 +1,83 @@
+use serde::{Deserialize, Serialize};
+
+use crate::error::PeekError;
+
+const DEFAULT_INDENT: usize = 2;
+
+// ── Helpers ───────────────────────────────────────────────────────────────────
+
+/// 1-based line and column of a byte offset.
+pub fn line_column(content: &str, offset: usize) -> (usize, usize) {
+    let before = &content[..offset.min(content.len())];
+    let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+    (before.matches('\n').count() + 1, before[line_start..].chars().count() + 1)
+}
+
+pub fn format_json(content: &str, indent: usize, minify: bool) -> Result<String, PeekError> {
+    let value: serde_json::Value = serde_json::from_str(content)
+        .map_err(|e| PeekError::parse_error(e.line(), e.column(), e))?;
+    if minify {
+        return serde_json::to_string(&value).map_err(|e| e.to_string().into());
+    }
+
+    let indent = " ".repeat(indent);
+    let mut out = Vec::new();
+    let formatter = serde_json::ser::PrettyFormatter::with_indent(indent.as_bytes());
+    let mut serializer = serde_json::Serializer::with_formatter(&mut out, formatter);
+    value.serialize(&mut serializer).map_err(|e| e.to_string())?;
+    String::from_utf8(out).map_err(|e| e.to_string().into())
+}
+
+/// TOML has a fixed layout: `minify` only drops the pretty multi-line arrays.
+fn format_toml(content: &str, minify: bool) -> Result<String, PeekError> {
+    let table: toml::Table = content.parse().map_err(|e: toml::de::Error| {
+        let (line, column) = line_column(content, e.span().map_or(0, |span| span.start));
+        PeekError::parse_error(line, column, e.message())
+    })?;
+    if minify {
+        toml::to_string(&table).map_err(|e| e.to_string().into())
+    } else {
+        toml::to_string_pretty(&table).map_err(|e| e.to_string().into())
+    }
+}
+
+/// YAML is always written with two-space block indentation; `minify` writes
+/// each document in flow style (JSON, which is valid YAML). Multi-document
+/// streams keep their `---` separators.
+fn format_yaml(content: &str, minify: bool) -> Result<String, PeekError> {
+    let mut documents = Vec::new();
+    for document in serde_yaml::Deserializer::from_str(content) {
+        let value = serde_yaml::Value::deserialize(document).map_err(|e| match e.location() {
+            Some(location) => PeekError::parse_error(location.line(), location.column(), &e),
+            None => PeekError::parse_error(1, 1, &e),
+        })?;
+        let formatted = if minify {
+            serde_json::to_string(&value).map_err(|e| e.to_string())? + "\n"
+        } else {
+            serde_yaml::to_string(&value).map_err(|e| e.to_string())?
+        };
+        documents.push(formatted);
+    }
+    Ok(documents.join("---\n"))
+}
+
+// ── Commands ──────────────────────────────────────────────────────────────────
+
+/// Pretty-print or minify JSON, TOML or YAML `content` (an editor buffer, so
+/// unsaved changes work too). `indent` (default 2) applies to JSON; TOML and
+/// YAML use their serializer's layout. Parse failures are `PARSE_ERROR`.
+#[tauri::command]
+pub fn format_structured(
+    content: String,
+    format: String,
+    indent: Option<usize>,
+    minify: Option<bool>,
+) -> Result<String, PeekError> {
+    let minify = minify.unwrap_or(false);
+    match format.to_lowercase().as_str() {
+        "json" => Ok(format_json(&content, indent.unwrap_or(DEFAULT_INDENT), minify)?),
+        "toml" => Ok(format_toml(&content, minify)?),
+        "yaml" | "yml" => Ok(format_yaml(&content, minify)?),
+        other => Err(format!("Unsupported format: {}", other).into()),
+    }
+}
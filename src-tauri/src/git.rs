@@ -0,0 +1,304 @@
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Write};
+use std::path::Path;
+use std::process::{Command, Output, Stdio};
+
+use serde::Serialize;
+
+use crate::error::{AtPath, PeekError};
+use crate::lock::FileLocks;
+use crate::patch::Hunk;
+use crate::LineIndexCache;
+
+/// How much of a commit hash is shown.
+const SHORT_HASH_LEN: usize = 8;
+/// What `git blame` reports for lines that differ from `HEAD`.
+const UNCOMMITTED_HASH: &str = "0000000000000000000000000000000000000000";
+
+// ── Types ─────────────────────────────────────────────────────────────────────
+
+/// Blame for one line. Lines changed in the working tree have `uncommitted`
+/// set and no commit fields.
+#[derive(Serialize)]
+pub struct BlameLine {
+    line: usize, // 0-based
+    hash: Option<String>,
+    author: Option<String>,
+    timestamp: Option<i64>, // Unix seconds, author time
+    summary: Option<String>,
+    uncommitted: bool,
+}
+
+#[derive(Serialize)]
+pub struct GitBlame {
+    /// "ok" | "not_in_repo" | "untracked" (the file isn't in `HEAD` yet).
+    status: String,
+    lines: Vec<BlameLine>,
+}
+
+/// Fields `--porcelain` gives once per commit, on its first line.
+#[derive(Default, Clone)]
+struct CommitInfo {
+    author: String,
+    timestamp: i64,
+    summary: String,
+}
+
+// ── Helpers ───────────────────────────────────────────────────────────────────
+
+fn git_command(dir: &Path, args: &[&str]) -> Command {
+    let mut command = Command::new("git");
+    command.args(args).current_dir(dir).stdin(Stdio::null());
+    #[cfg(windows)]
+    {
+        use std::os::windows::process::CommandExt;
+        const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+        command.creation_flags(CREATE_NO_WINDOW);
+    }
+    command
+}
+
+fn spawn_error(e: std::io::Error) -> PeekError {
+    match e.kind() {
+        std::io::ErrorKind::NotFound => PeekError::not_found("git isn't installed or isn't on PATH"),
+        _ => format!("Failed to run git: {}", e).into(),
+    }
+}
+
+/// Run git in `dir`. Fails only if git can't be started; the caller looks at
+/// the exit status.
+fn run_git(dir: &Path, args: &[&str]) -> Result<Output, PeekError> {
+    git_command(dir, args).output().map_err(spawn_error)
+}
+
+/// Run git in `dir` with `input` on stdin, failing with its stderr if it does.
+fn run_git_with_input(dir: &Path, args: &[&str], input: &str) -> Result<(), PeekError> {
+    let mut child = git_command(dir, args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(spawn_error)?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(input.as_bytes())?;
+    }
+    let output = child.wait_with_output()?;
+    if !output.status.success() {
+        return Err(format!("git {} failed: {}", args[0], String::from_utf8_lossy(&output.stderr).trim()).into());
+    }
+    Ok(())
+}
+
+/// The directory a file lives in and its name there, for running git beside it.
+fn split_path(path: &Path) -> Result<(&Path, &str), PeekError> {
+    let name = path.file_name().and_then(|n| n.to_str()).ok_or_else(|| format!("Not a file path: {}", path.display()))?;
+    Ok((crate::parent_dir(path), name))
+}
+
+fn in_work_tree(dir: &Path) -> Result<bool, PeekError> {
+    let output = run_git(dir, &["rev-parse", "--is-inside-work-tree"])?;
+    Ok(output.status.success() && String::from_utf8_lossy(&output.stdout).trim() == "true")
+}
+
+/// A file's path from the top of its repository, as patches name it.
+fn repo_path(dir: &Path, name: &str) -> Result<String, PeekError> {
+    let output = run_git(dir, &["rev-parse", "--show-prefix"])?;
+    if !output.status.success() {
+        return Err(format!("Not in a git repository: {}", dir.display()).into());
+    }
+    Ok(format!("{}{}", String::from_utf8_lossy(&output.stdout).trim(), name))
+}
+
+/// Unstaged changes to a file (index → working tree) without context lines,
+/// the granularity of a change gutter.
+fn unstaged_hunks(file_path: &Path) -> Result<Vec<Hunk>, PeekError> {
+    let (dir, name) = split_path(file_path)?;
+    if !in_work_tree(dir)? {
+        return Ok(Vec::new());
+    }
+    let output = run_git(dir, &["diff", "--no-color", "--no-ext-diff", "-U0", "--", name])?;
+    if !output.status.success() {
+        return Err(format!("git diff failed: {}", String::from_utf8_lossy(&output.stderr).trim()).into());
+    }
+    let files = crate::patch::parse_patch(&String::from_utf8_lossy(&output.stdout))?;
+    Ok(files.into_iter().flat_map(|file| file.hunks).collect())
+}
+
+/// Lines in a file as git counts them: a last line without a newline counts,
+/// nothing after a trailing newline does. `None` unless the file has a cached
+/// line index that is still current.
+fn indexed_line_count(path: &str, cache: &LineIndexCache) -> Result<Option<usize>, PeekError> {
+    let metadata = crate::regular_file_metadata(Path::new(path)).at(path)?;
+    let mut store = cache.0.lock()?;
+    let Some(index) = store.get(path) else { return Ok(None) };
+    // A `.gz` index counts decompressed lines, but git blames the raw file
+    if index.decompressed_size.is_some()
+        || index.file_size != metadata.len()
+        || index.mtime != crate::unix_mtime(&metadata)
+    {
+        return Ok(None);
+    }
+    let ends_with_newline = index.offsets.last() == Some(&index.file_size);
+    Ok(Some(index.offsets.len() - usize::from(ends_with_newline)))
+}
+
+/// `indexed_line_count` for a file that isn't indexed, counted a buffer at a time.
+fn count_lines(file_path: &Path) -> Result<usize, PeekError> {
+    let mut reader = BufReader::new(std::fs::File::open(file_path).at(file_path)?);
+    let (mut newlines, mut last_byte) = (0, b'\n');
+    loop {
+        let buf = reader.fill_buf()?;
+        let Some(&last) = buf.last() else { break };
+        newlines += memchr::memchr_iter(b'\n', buf).count();
+        last_byte = last;
+        let consumed = buf.len();
+        reader.consume(consumed);
+    }
+    Ok(newlines + usize::from(last_byte != b'\n'))
+}
+
+/// Parse `git blame --porcelain` output into per-line blame.
+fn parse_porcelain(output: &str) -> Vec<BlameLine> {
+    let mut commits: HashMap<String, CommitInfo> = HashMap::new();
+    let mut lines = Vec::new();
+    let mut current: Option<(String, usize)> = None; // hash, final line (1-based)
+
+    for row in output.lines() {
+        if row.starts_with('\t') {
+            // The line's content ends its entry
+            let Some((hash, final_line)) = current.take() else { continue };
+            let uncommitted = hash == UNCOMMITTED_HASH;
+            let info = (!uncommitted).then(|| commits.get(&hash).cloned().unwrap_or_default());
+            lines.push(BlameLine {
+                line: final_line.saturating_sub(1),
+                hash: info.as_ref().map(|_| hash[..SHORT_HASH_LEN.min(hash.len())].to_string()),
+                author: info.as_ref().map(|info| info.author.clone()),
+                timestamp: info.as_ref().map(|info| info.timestamp),
+                summary: info.map(|info| info.summary),
+                uncommitted,
+            });
+            continue;
+        }
+        let mut fields = row.splitn(2, ' ');
+        let key = fields.next().unwrap_or_default();
+        let value = fields.next().unwrap_or_default();
+        if current.is_none() && key.len() == 40 && key.bytes().all(|b| b.is_ascii_hexdigit()) {
+            // `<hash> <orig line> <final line> [<group size>]`
+            let final_line = value.split(' ').nth(1).and_then(|n| n.parse().ok()).unwrap_or(0);
+            commits.entry(key.to_string()).or_default();
+            current = Some((key.to_string(), final_line));
+            continue;
+        }
+        let Some((hash, _)) = &current else { continue };
+        let Some(info) = commits.get_mut(hash) else { continue };
+        match key {
+            "author" => info.author = value.to_string(),
+            "author-time" => info.timestamp = value.parse().unwrap_or(0),
+            "summary" => info.summary = value.to_string(),
+            _ => {}
+        }
+    }
+    lines
+}
+
+// ── Commands ──────────────────────────────────────────────────────────────────
+
+/// Who last changed each line of a file, by `git blame` on the working copy.
+/// With `start_line`/`end_line` (0-based, inclusive) only that range is
+/// blamed, which keeps large files fast. A file outside any repository or not
+/// yet committed comes back with that `status` and no lines.
+#[tauri::command]
+pub async fn git_blame(
+    path: String,
+    start_line: Option<usize>,
+    end_line: Option<usize>,
+    cache: tauri::State<'_, LineIndexCache>,
+) -> Result<GitBlame, PeekError> {
+    let path = crate::paths::resolve(&path);
+    let indexed_lines = indexed_line_count(&path, &cache)?;
+    tauri::async_runtime::spawn_blocking(move || -> Result<GitBlame, PeekError> {
+        let file_path = Path::new(&path);
+        let (dir, name) = split_path(file_path)?;
+        if !in_work_tree(dir)? {
+            return Ok(GitBlame { status: "not_in_repo".to_string(), lines: Vec::new() });
+        }
+
+        // git rejects a range past the end of the file, which the last screen of
+        // a viewport can ask for
+        let line_count = match indexed_lines {
+            Some(count) => count,
+            None => count_lines(file_path)?,
+        };
+        if line_count == 0 {
+            return Ok(GitBlame { status: "ok".to_string(), lines: Vec::new() });
+        }
+        let range = match (start_line, end_line) {
+            (None, None) => None,
+            (start, end) => {
+                let start = start.unwrap_or(0).min(line_count.saturating_sub(1)) + 1;
+                let end = end.map_or(line_count, |end| (end + 1).min(line_count)).max(start);
+                Some(format!("{},{}", start, end))
+            }
+        };
+        let mut args = vec!["blame", "--porcelain"];
+        if let Some(range) = &range {
+            args.extend(["-L", range]);
+        }
+        args.extend(["--", name]);
+
+        let output = run_git(dir, &args)?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            if stderr.contains("no such path") {
+                return Ok(GitBlame { status: "untracked".to_string(), lines: Vec::new() });
+            }
+            return Err(format!("git blame failed: {}", stderr.trim()).into());
+        }
+        Ok(GitBlame { status: "ok".to_string(), lines: parse_porcelain(&String::from_utf8_lossy(&output.stdout)) })
+    })
+    .await?
+}
+
+/// Unstaged changes to a file as zero-context hunks, for a change gutter and
+/// for `git_stage_hunk` / `git_revert_hunk`. Empty outside a repository.
+#[tauri::command]
+pub async fn git_diff_hunks(path: String) -> Result<Vec<Hunk>, PeekError> {
+    let path = crate::paths::resolve(&path);
+    tauri::async_runtime::spawn_blocking(move || unstaged_hunks(Path::new(&path)))
+        .await?
+}
+
+/// Stage one hunk from `git_diff_hunks`, leaving the rest of the file's
+/// changes unstaged. Returns the hunks still unstaged.
+#[tauri::command]
+pub fn git_stage_hunk(path: String, hunk: Hunk) -> Result<Vec<Hunk>, PeekError> {
+    let path = crate::paths::resolve(&path);
+    hunk.validate()?;
+    let file_path = Path::new(&path);
+    let (dir, name) = split_path(file_path)?;
+    let repo_path = repo_path(dir, name)?;
+    let patch = format!("--- a/{0}\n+++ b/{0}\n{1}", repo_path, hunk.to_patch_text());
+    // Patch paths are from the top of the repository, wherever git runs
+    run_git_with_input(dir, &["apply", "--cached", "--unidiff-zero", "--whitespace=nowarn", "-"], &patch)?;
+    unstaged_hunks(file_path)
+}
+
+/// Discard one hunk from `git_diff_hunks` in the working file, putting back
+/// what the index has there. The file is rewritten atomically and its line
+/// index rebuilt, like `apply_patch_to_file`. Returns the hunks still unstaged.
+#[tauri::command]
+pub fn git_revert_hunk(
+    path: String,
+    hunk: Hunk,
+    cache: tauri::State<'_, LineIndexCache>,
+    locks: tauri::State<'_, FileLocks>,
+) -> Result<Vec<Hunk>, PeekError> {
+    let path = crate::paths::resolve(&path);
+    hunk.validate()?;
+    let file_path = Path::new(&path);
+    let result = crate::patch::apply_hunks_to_file(path.clone(), &[hunk.reversed()], &cache, &locks)?;
+    if !result.written {
+        return Err(PeekError::conflict("The hunk no longer matches the file; refresh the diff"));
+    }
+    unstaged_hunks(file_path)
+}
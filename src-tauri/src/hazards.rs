@@ -0,0 +1,166 @@
+use std::io::{BufRead, BufReader, Read};
+use std::path::Path;
+
+use serde::Serialize;
+
+use crate::error::{AtPath, PeekError};
+
+/// Past this many findings the file is plainly not hand-written code; stop.
+const MAX_HAZARDS: usize = 10_000;
+
+/// Characters that reorder how text is displayed (the "Trojan Source" attack,
+/// CVE-2021-42574): code can read differently from how it compiles.
+const BIDI_CONTROLS: &[(char, &str)] = &[
+    ('\u{061C}', "ARABIC LETTER MARK"),
+    ('\u{200E}', "LEFT-TO-RIGHT MARK"),
+    ('\u{200F}', "RIGHT-TO-LEFT MARK"),
+    ('\u{202A}', "LEFT-TO-RIGHT EMBEDDING"),
+    ('\u{202B}', "RIGHT-TO-LEFT EMBEDDING"),
+    ('\u{202C}', "POP DIRECTIONAL FORMATTING"),
+    ('\u{202D}', "LEFT-TO-RIGHT OVERRIDE"),
+    ('\u{202E}', "RIGHT-TO-LEFT OVERRIDE"),
+    ('\u{2066}', "LEFT-TO-RIGHT ISOLATE"),
+    ('\u{2067}', "RIGHT-TO-LEFT ISOLATE"),
+    ('\u{2068}', "FIRST STRONG ISOLATE"),
+    ('\u{2069}', "POP DIRECTIONAL ISOLATE"),
+];
+
+/// Characters that render as nothing, so two identifiers can look the same.
+const INVISIBLE: &[(char, &str)] = &[
+    ('\u{00AD}', "SOFT HYPHEN"),
+    ('\u{034F}', "COMBINING GRAPHEME JOINER"),
+    ('\u{115F}', "HANGUL CHOSEONG FILLER"),
+    ('\u{1160}', "HANGUL JUNGSEONG FILLER"),
+    ('\u{180E}', "MONGOLIAN VOWEL SEPARATOR"),
+    ('\u{200B}', "ZERO WIDTH SPACE"),
+    ('\u{200C}', "ZERO WIDTH NON-JOINER"),
+    ('\u{200D}', "ZERO WIDTH JOINER"),
+    ('\u{2060}', "WORD JOINER"),
+    ('\u{2061}', "FUNCTION APPLICATION"),
+    ('\u{2062}', "INVISIBLE TIMES"),
+    ('\u{2063}', "INVISIBLE SEPARATOR"),
+    ('\u{2064}', "INVISIBLE PLUS"),
+    ('\u{3164}', "HANGUL FILLER"),
+    ('\u{FEFF}', "ZERO WIDTH NO-BREAK SPACE"),
+    ('\u{FFA0}', "HALFWIDTH HANGUL FILLER"),
+];
+
+/// Common Cyrillic and Greek look-alikes of Latin letters (from Unicode's
+/// confusables.txt), with the ASCII letter each passes for.
+const CONFUSABLES: &[(char, char)] = &[
+    ('\u{0430}', 'a'), ('\u{0435}', 'e'), ('\u{043E}', 'o'), ('\u{0440}', 'p'), ('\u{0441}', 'c'),
+    ('\u{0443}', 'y'), ('\u{0445}', 'x'), ('\u{0455}', 's'), ('\u{0456}', 'i'), ('\u{0458}', 'j'),
+    ('\u{04BB}', 'h'), ('\u{0501}', 'd'), ('\u{051B}', 'q'), ('\u{051D}', 'w'),
+    ('\u{0410}', 'A'), ('\u{0412}', 'B'), ('\u{0415}', 'E'), ('\u{041A}', 'K'), ('\u{041C}', 'M'),
+    ('\u{041D}', 'H'), ('\u{041E}', 'O'), ('\u{0420}', 'P'), ('\u{0421}', 'C'), ('\u{0422}', 'T'),
+    ('\u{0425}', 'X'), ('\u{0405}', 'S'), ('\u{0406}', 'I'), ('\u{0408}', 'J'),
+    ('\u{03B1}', 'a'), ('\u{03BF}', 'o'), ('\u{03C1}', 'p'), ('\u{03BD}', 'v'), ('\u{03B9}', 'i'),
+    ('\u{0391}', 'A'), ('\u{0392}', 'B'), ('\u{0395}', 'E'), ('\u{0396}', 'Z'), ('\u{0397}', 'H'),
+    ('\u{0399}', 'I'), ('\u{039A}', 'K'), ('\u{039C}', 'M'), ('\u{039D}', 'N'), ('\u{039F}', 'O'),
+    ('\u{03A1}', 'P'), ('\u{03A4}', 'T'), ('\u{03A5}', 'Y'), ('\u{03A7}', 'X'),
+];
+
+// ── Types ─────────────────────────────────────────────────────────────────────
+
+#[derive(Serialize)]
+pub struct UnicodeHazard {
+    line: usize,   // 0-based
+    column: usize, // 0-based, in Unicode scalar values
+    code_point: String, // "U+202E"
+    name: String,
+    category: String, // "bidi_control" | "invisible" | "confusable"
+    /// For confusables: the ASCII letter it imitates.
+    looks_like: Option<char>,
+}
+
+#[derive(Serialize)]
+pub struct HazardReport {
+    hazards: Vec<UnicodeHazard>,
+    /// Stopped at `MAX_HAZARDS`.
+    truncated: bool,
+}
+
+// ── Helpers ───────────────────────────────────────────────────────────────────
+
+fn lookup<T: Copy>(table: &[(char, T)], c: char) -> Option<T> {
+    table.iter().find(|(entry, _)| *entry == c).map(|(_, value)| *value)
+}
+
+/// Hazards in one decoded line. Look-alike letters only count inside a word
+/// that also has ASCII letters: `pаypal` is suspicious, Russian prose isn't.
+fn scan_line(line: &str, line_number: usize, hazards: &mut Vec<UnicodeHazard>) {
+    let chars: Vec<char> = line.chars().collect();
+    let word_bounds = |at: usize| {
+        let start = chars[..at].iter().rposition(|c| !c.is_alphanumeric()).map_or(0, |i| i + 1);
+        let end = chars[at..].iter().position(|c| !c.is_alphanumeric()).map_or(chars.len(), |i| at + i);
+        start..end
+    };
+
+    for (column, &c) in chars.iter().enumerate() {
+        if c.is_ascii() {
+            continue;
+        }
+        let (category, name, looks_like) = if let Some(name) = lookup(BIDI_CONTROLS, c) {
+            ("bidi_control", name.to_string(), None)
+        } else if let Some(name) = lookup(INVISIBLE, c) {
+            ("invisible", name.to_string(), None)
+        } else if let Some(ascii) = lookup(CONFUSABLES, c) {
+            if !chars[word_bounds(column)].iter().any(char::is_ascii_alphabetic) {
+                continue;
+            }
+            ("confusable", format!("look-alike of '{}'", ascii), Some(ascii))
+        } else {
+            continue;
+        };
+        hazards.push(UnicodeHazard {
+            line: line_number,
+            column,
+            code_point: format!("U+{:04X}", c as u32),
+            name,
+            category: category.to_string(),
+            looks_like,
+        });
+    }
+}
+
+// ── Commands ──────────────────────────────────────────────────────────────────
+
+/// Find characters that make source code look different from what it is:
+/// bidirectional controls (Trojan Source), invisible characters, and Cyrillic
+/// or Greek letters passing for Latin ones inside otherwise-Latin words.
+/// A BOM at the very start of the file is not reported. Streams the file, so
+/// it works on large files too.
+#[tauri::command]
+pub fn scan_unicode_hazards(path: String) -> Result<HazardReport, PeekError> {
+    let path = crate::paths::resolve(&path);
+    let file_path = Path::new(&path);
+    crate::regular_file_metadata(file_path).at(file_path)?;
+    let mut sample = Vec::new();
+    std::fs::File::open(file_path)
+        .at(file_path)?
+        .take(crate::encoding::SAMPLE_BYTES as u64)
+        .read_to_end(&mut sample)?;
+    let file_encoding = crate::encoding::detect(&sample);
+    if !crate::encoding::is_line_indexable(file_encoding) {
+        return Err(PeekError::unsupported_encoding(file_encoding.name()));
+    }
+
+    let mut reader = BufReader::new(std::fs::File::open(file_path).at(file_path)?);
+    let mut hazards = Vec::new();
+    let mut raw = Vec::new();
+    let mut line_number = 0;
+    loop {
+        raw.clear();
+        if reader.read_until(b'\n', &mut raw)? == 0 {
+            break;
+        }
+        let line = crate::encoding::decode(&raw, file_encoding, line_number == 0);
+        scan_line(&line, line_number, &mut hazards);
+        if hazards.len() >= MAX_HAZARDS {
+            hazards.truncate(MAX_HAZARDS);
+            return Ok(HazardReport { hazards, truncated: true });
+        }
+        line_number += 1;
+    }
+    Ok(HazardReport { hazards, truncated: false })
+}
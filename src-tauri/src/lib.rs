@@ -1,16 +1,156 @@
 use std::fs;
 use std::path::Path;
 use std::io::{Read, Seek, SeekFrom, BufRead, BufReader, Write};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::sync::Mutex;
 use serde::{Serialize, Deserialize};
-use tauri::Manager;
+use sha2::{Digest, Sha256};
+use flate2::read::MultiGzDecoder;
+use flate2::write::GzEncoder;
+use tauri::{Emitter, Manager};
+use tauri::webview::PageLoadEvent;
+use error::{AtPath, PeekError};
 
+mod archive;
+mod beautify;
+mod cli;
+mod clipboard;
+mod command;
+mod diff;
+mod dirhash;
+mod encoding;
+mod error;
+mod fold;
+mod format;
+mod git;
+mod hazards;
+mod lock;
+mod patch;
+mod paths;
+mod probe;
+mod protocol;
+mod quickopen;
+mod recent;
+mod rename;
+mod schema;
+mod search;
+mod shells;
+mod svg;
+mod symbols;
 mod terminal;
+mod trash;
+mod watch;
 
 // ── Shared State ──────────────────────────────────────────────────────────────
-// Stores line-offset indexes for large files: path → Vec of byte offsets
-struct LineIndexCache(Mutex<HashMap<String, Vec<u64>>>);
+// Stores line-offset indexes for large files: path (or buffer key) → LineIndex, LRU-bounded
+struct LineIndexCache(Mutex<IndexStore>);
+
+const DEFAULT_INDEX_CACHE_BUDGET: usize = 256 * 1024 * 1024; // 256 MB
+
+/// Line indexes keyed by path. When the approximate memory use exceeds
+/// `budget_bytes`, least-recently-used entries are evicted.
+struct IndexStore {
+    entries: HashMap<String, LineIndex>,
+    budget_bytes: usize,
+    clock: u64,
+}
+
+impl IndexStore {
+    fn new(budget_bytes: usize) -> Self {
+        IndexStore { entries: HashMap::new(), budget_bytes, clock: 0 }
+    }
+
+    /// Look up an index and mark it as recently used.
+    fn get(&mut self, path: &str) -> Option<&LineIndex> {
+        self.clock += 1;
+        let index = self.entries.get_mut(path)?;
+        index.last_used = self.clock;
+        Some(index)
+    }
+
+    fn insert(&mut self, path: String, mut index: LineIndex) {
+        self.clock += 1;
+        index.last_used = self.clock;
+        self.entries.insert(path.clone(), index);
+        self.evict(Some(&path));
+    }
+
+    fn bytes_used(&self) -> usize {
+        self.entries.iter().map(|(path, index)| path.len() + index.approx_bytes()).sum()
+    }
+
+    /// Drop LRU entries until within budget. `keep` (the entry just inserted)
+    /// survives even if it alone exceeds the budget, so it can still be read.
+    fn evict(&mut self, keep: Option<&str>) {
+        let mut used = self.bytes_used();
+        while used > self.budget_bytes {
+            let victim = self.entries
+                .iter()
+                .filter(|(path, _)| Some(path.as_str()) != keep)
+                .min_by_key(|(_, index)| index.last_used)
+                .map(|(path, _)| path.clone());
+            let Some(victim) = victim else { break };
+            if let Some(index) = self.entries.remove(&victim) {
+                used -= victim.len() + index.approx_bytes();
+            }
+        }
+    }
+}
+
+/// Byte offsets of each line start, plus the file's dominant line ending and
+/// encoding and the file state the index was built from.
+struct LineIndex {
+    offsets: Vec<u64>,
+    crlf: bool,
+    /// Detected from the head of the file when it was indexed; always one
+    /// whose `\n` bytes are line breaks (see `encoding::is_line_indexable`).
+    encoding: &'static encoding_rs::Encoding,
+    file_size: u64,
+    mtime: u64,
+    fingerprint: String,
+    last_used: u64,
+    /// For `.gz` files: offsets address the decompressed stream of this length.
+    decompressed_size: Option<u64>,
+    /// For unsaved buffers indexed with `index_content`: the text itself,
+    /// which the offsets address instead of a file.
+    buffer: Option<String>,
+    /// For `.gz` files: where the last read stopped, so reading on from
+    /// there (scrolling down) doesn't decompress from the start again.
+    gzip_cursor: Mutex<Option<GzipCursor>>,
+}
+
+/// A gzip decoder positioned `position` bytes into the decompressed stream.
+struct GzipCursor {
+    decoder: MultiGzDecoder<BufReader<std::fs::File>>,
+    position: u64,
+}
+
+impl LineIndex {
+    fn approx_bytes(&self) -> usize {
+        std::mem::size_of::<Self>()
+            + self.offsets.capacity() * std::mem::size_of::<u64>()
+            + self.fingerprint.capacity()
+            + self.buffer.as_ref().map_or(0, String::capacity)
+    }
+
+    fn response(&self, reindexed: bool) -> IndexResponse {
+        IndexResponse {
+            total_lines: self.offsets.len(),
+            file_size: self.file_size,
+            mtime: self.mtime,
+            has_trailing_newline: ends_with_newline(&self.offsets, self.content_size()),
+            line_ending: line_ending_name(self.crlf),
+            encoding: self.encoding.name().to_string(),
+            reindexed,
+            decompressed_size: self.decompressed_size,
+        }
+    }
+
+    /// Length of the byte stream the offsets point into.
+    fn content_size(&self) -> u64 {
+        self.decompressed_size.unwrap_or(self.file_size)
+    }
+}
 
 // ── Types ─────────────────────────────────────────────────────────────────────
 
@@ -23,6 +163,40 @@ struct FileResponse {
     mtime: u64, // Unix timestamp (seconds)
     is_binary: bool,
     is_large_file: bool,
+    decompressed_size: Option<u64>, // Set for transparently decompressed `.gz` files
+    is_minified: bool, // Minified JS/CSS/JSON that `beautify_code` can reflow
+    encoding: Option<String>, // Text only: the encoding detected or forced
+    /// Large files only: `content` holds the first `preview_lines` whole lines,
+    /// which end at byte `preview_end` (of the decompressed stream for `.gz`).
+    /// `read_lines` from `preview_lines` on continues where it stops.
+    preview_end: Option<u64>,
+    preview_lines: Option<usize>,
+    /// Lines cut short by `max_line_bytes`; empty when nothing was.
+    truncated_lines: Vec<TruncatedLine>,
+}
+
+/// A line cut to `max_line_bytes` for display. `byte_length` is its full
+/// length in UTF-8, without the line ending; `read_lines` without a limit
+/// (or `read_file_content` without one) returns the whole line.
+#[derive(Serialize, Deserialize)]
+struct TruncatedLine {
+    line: usize, // 0-based, in the file
+    byte_length: usize,
+}
+
+/// One file of a `read_files` batch: its `FileResponse`, or why it failed.
+#[derive(Serialize)]
+struct BatchFile {
+    path: String,
+    file: Option<FileResponse>,
+    error: Option<PeekError>,
+}
+
+#[derive(Serialize)]
+struct BatchResponse {
+    files: Vec<BatchFile>,
+    /// `max_total_bytes` was reached before every path was read.
+    truncated: bool,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -30,6 +204,32 @@ struct IndexResponse {
     total_lines: usize,
     file_size: u64,
     mtime: u64,
+    has_trailing_newline: bool,
+    line_ending: String, // "lf" | "crlf"
+    encoding: String, // WHATWG name, e.g. "UTF-8", "windows-1252", "Shift_JIS"
+    reindexed: bool,
+    decompressed_size: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct CacheStats {
+    entries: usize,
+    bytes_used: usize,
+    budget_bytes: usize,
+}
+
+#[derive(Serialize)]
+struct BackendStats {
+    indexed_files: usize,
+    /// Approximate memory held by cached line indexes.
+    index_bytes: usize,
+    terminals: usize,
+    dir_watchers: usize,
+    file_watchers: usize,
+    /// Recursive watchers kept by quick-open's file lists, one per root.
+    quick_open_watchers: usize,
+    /// Resident memory of the backend process; `None` where it can't be read.
+    rss_bytes: Option<u64>,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -37,6 +237,139 @@ struct LinesResponse {
     content: String,
     start_line: usize,
     lines_read: usize,
+    /// Lines cut short by `max_line_bytes`; empty when nothing was.
+    truncated_lines: Vec<TruncatedLine>,
+}
+
+/// The last lines of a file. `start_line`/`total_lines` are `None` when the
+/// file wasn't indexed and the scan didn't reach its start.
+#[derive(Serialize, Deserialize)]
+struct TailResponse {
+    content: String,
+    start_line: Option<usize>,
+    lines_read: usize,
+    total_lines: Option<usize>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct NdjsonLine {
+    line_number: usize,
+    raw: String,
+    parsed: Option<serde_json::Value>,
+    parse_error: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct ContextLine {
+    line_number: usize,
+    text: String,
+}
+
+/// A line plus its surroundings; `before`/`after` are the context lines
+/// actually returned after clamping at the file boundaries.
+#[derive(Serialize, Deserialize)]
+struct LineContext {
+    line: usize,
+    lines: Vec<ContextLine>,
+    before: usize,
+    after: usize,
+    total_lines: usize,
+}
+
+/// Where `jump_to_byte` lands, with the lines around it.
+#[derive(Serialize, Deserialize)]
+struct ByteJump {
+    line: usize,
+    column: usize,
+    context: LineContext,
+}
+
+/// What `patch_file_lines` would leave behind, from a dry run: the new totals,
+/// and the edited lines with up to `PATCH_PREVIEW_CONTEXT` unchanged ones on
+/// either side, starting at `start_line`.
+#[derive(Serialize, Deserialize)]
+struct PatchPreview {
+    total_lines: usize,
+    file_size: u64,
+    start_line: usize,
+    content: String,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum PatchResponse {
+    Applied(IndexResponse),
+    Preview(PatchPreview),
+}
+
+/// Where a moved block of lines ended up. `moved` is false when the block was
+/// already at the top or bottom of the file and nothing was written.
+#[derive(Serialize, Deserialize)]
+struct MoveLinesResponse {
+    moved: bool,
+    start_line: usize,
+    end_line: usize,
+    total_lines: usize,
+}
+
+/// The first and last lines of a file, for a preview tooltip. `overlap`
+/// means the file is short enough that `head` and `tail` share lines; show
+/// one of them.
+#[derive(Serialize, Deserialize)]
+struct HeadTailResponse {
+    head: String,
+    head_lines: usize,
+    tail: String,
+    tail_lines: usize,
+    overlap: bool,
+    /// Binary files come back with no text.
+    is_binary: bool,
+}
+
+/// What `read_stream_snapshot` got from a pipe, device or generated file.
+#[derive(Serialize, Deserialize)]
+struct StreamSnapshot {
+    content: String,
+    bytes_read: usize,
+    /// The stream didn't end: `max_bytes` was reached, or the timeout hit
+    /// while it was still open.
+    more_available: bool,
+    timed_out: bool,
+}
+
+#[derive(Serialize, Deserialize)]
+struct LineEndingsResponse {
+    /// Lines whose ending was rewritten.
+    changed_lines: usize,
+    total_lines: usize,
+    line_ending: String, // "lf" | "crlf"
+}
+
+#[derive(Serialize, Deserialize)]
+struct ReplaceResponse {
+    replacements: usize,
+    total_lines: usize,
+}
+
+/// 0-based line and column; `column` counts Unicode scalar values (what JS
+/// iterates with `for..of` or `Array.from`, not UTF-16 code units).
+#[derive(Serialize, Deserialize)]
+struct Position {
+    line: usize,
+    column: usize,
+}
+
+/// Size of a selection. `chars` counts Unicode scalar values with each line
+/// break as one, as the editor's `\n`-only text has them; `bytes` is the
+/// range in the file, `start_byte..end_byte`, in its encoding and line
+/// endings.
+#[derive(Serialize, Deserialize)]
+struct SelectionStats {
+    lines: usize,
+    chars: usize,
+    bytes: u64,
+    start_byte: u64,
+    end_byte: u64,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -45,15 +378,878 @@ struct ChunkResponse {
     bytes_read: usize,
 }
 
+#[derive(Serialize, Deserialize)]
+struct BytesResponse {
+    data: String, // base64-encoded raw bytes
+    bytes_read: usize,
+}
+
+/// Whether a save can succeed; `reason` is a code (`READONLY`,
+/// `PERMISSION_DENIED`, `DIRECTORY_NOT_WRITABLE`, `IS_DIRECTORY`, ...) when not.
+#[derive(Serialize, Deserialize)]
+struct WritableResponse {
+    writable: bool,
+    reason: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct FileInfo {
+    path: String,
+    file_name: String,
+    size: u64,
+    mtime: u64,
+    readonly: bool,
+}
+
+#[derive(Serialize, Deserialize)]
+struct FileStats {
+    lines: usize,
+    words: usize,
+    chars: usize, // Unicode scalar values
+    bytes: u64,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SaveResponse {
+    mtime: u64,
+    hash: String,
+}
+
+const IMAGE_EXTENSIONS: &[&str] = &["png", "jpg", "jpeg", "gif", "bmp", "svg", "webp", "ico"];
+const MINIFIED_SAMPLE_BYTES: u64 = 64 * 1024;
+const TAIL_SCAN_LIMIT: u64 = 16 * 1024 * 1024; // 16 MB
 const LARGE_FILE_THRESHOLD: u64 = 2 * 1024 * 1024; // 2 MB
+const PREVIEW_BYTES: usize = 64 * 1024;
+const DEFAULT_BATCH_READ_BYTES: u64 = 16 * 1024 * 1024; // 16 MB
+const PATCH_PREVIEW_CONTEXT: usize = 3; // unchanged lines around a dry-run edit
+const DEFAULT_METADATA_TIMEOUT_MS: u64 = 3000;
+const DEFAULT_BACKUP_SUFFIX: &str = "~";
+const MMAP_INDEX_THRESHOLD: u64 = 64 * 1024 * 1024; // 64 MB
+const DEFAULT_STREAM_SNAPSHOT_BYTES: usize = 1024 * 1024; // 1 MB
+const DEFAULT_STREAM_TIMEOUT_MS: u64 = 2000;
+const SELECTION_CHUNK_BYTES: usize = 1024 * 1024; // 1 MB
+const DEFAULT_JUMP_CONTEXT: usize = 3;
+
+// ── Helpers ───────────────────────────────────────────────────────────────────
+
+/// Modification time as a Unix timestamp (seconds), 0 if unavailable.
+fn unix_mtime(metadata: &fs::Metadata) -> u64 {
+    metadata.modified()
+        .map(|t| t.duration_since(std::time::UNIX_EPOCH).unwrap_or_default().as_secs())
+        .unwrap_or(0)
+}
+
+/// Run blocking file I/O on its own thread and give up with `TIMEOUT` after
+/// `timeout_ms`. A hung call (e.g. a stale NFS mount) keeps its thread, but
+/// the caller is freed and the UI can report that the file system is stuck.
+fn with_timeout<T, F>(timeout_ms: u64, f: F) -> Result<T, PeekError>
+where
+    T: Send + 'static,
+    F: FnOnce() -> Result<T, PeekError> + Send + 'static,
+{
+    let (tx, rx) = std::sync::mpsc::channel();
+    std::thread::spawn(move || {
+        let _ = tx.send(f());
+    });
+    match rx.recv_timeout(std::time::Duration::from_millis(timeout_ms)) {
+        Ok(result) => result,
+        Err(std::sync::mpsc::RecvTimeoutError::Timeout) => Err(PeekError::timeout("File operation timed out")),
+        Err(std::sync::mpsc::RecvTimeoutError::Disconnected) => Err("File operation failed".into()),
+    }
+}
+
+fn file_info(path: &Path) -> Result<FileInfo, PeekError> {
+    let metadata = fs::metadata(path).at(path)?;
+    Ok(FileInfo {
+        path: path.to_string_lossy().to_string(),
+        file_name: path.file_name().and_then(|n| n.to_str()).unwrap_or("unknown").to_string(),
+        size: metadata.len(),
+        mtime: unix_mtime(&metadata),
+        readonly: metadata.permissions().readonly(),
+    })
+}
+
+/// Stat a path and make sure it is a regular file before anyone opens it.
+/// Opening a FIFO blocks forever and directories/devices give confusing errors,
+/// so these come back as `IS_DIRECTORY`, `IS_FIFO`, `IS_SOCKET` or `IS_DEVICE`.
+fn regular_file_metadata(path: &Path) -> Result<fs::Metadata, PeekError> {
+    let metadata = match fs::metadata(path) {
+        Ok(metadata) => metadata,
+        Err(e) => {
+            // Windows reparse points that can't be followed (app execution
+            // aliases, dangling junctions, ...) fail to stat through the link
+            #[cfg(windows)]
+            {
+                use std::os::windows::fs::MetadataExt;
+                const FILE_ATTRIBUTE_REPARSE_POINT: u32 = 0x400;
+                if let Ok(link) = fs::symlink_metadata(path) {
+                    if link.file_attributes() & FILE_ATTRIBUTE_REPARSE_POINT != 0 {
+                        return Err(PeekError::is_device("Not a regular file"));
+                    }
+                }
+            }
+            return Err(e.into());
+        }
+    };
+    let file_type = metadata.file_type();
+
+    if file_type.is_dir() {
+        return Err(PeekError::is_directory("Is a directory"));
+    }
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::FileTypeExt;
+        if file_type.is_fifo() {
+            return Err(PeekError::is_fifo("Is a named pipe"));
+        }
+        if file_type.is_socket() {
+            return Err(PeekError::is_socket("Is a socket"));
+        }
+        if file_type.is_block_device() || file_type.is_char_device() {
+            return Err(PeekError::is_device("Is a device"));
+        }
+    }
+
+    if !file_type.is_file() {
+        return Err(PeekError::is_device("Not a regular file"));
+    }
+
+    Ok(metadata)
+}
+
+/// Whether a regular file is generated on read (`/proc/cpuinfo`,
+/// `/sys/.../uevent`): it reports size 0 whatever it holds, so size-based
+/// reading shows nothing.
+#[cfg(target_os = "linux")]
+fn is_pseudo_file(path: &Path, metadata: &fs::Metadata) -> bool {
+    let canonical = fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+    metadata.len() == 0 && (canonical.starts_with("/proc") || canonical.starts_with("/sys"))
+}
+
+#[cfg(not(target_os = "linux"))]
+fn is_pseudo_file(_path: &Path, _metadata: &fs::Metadata) -> bool {
+    false
+}
+
+/// Build the line-start offsets for a file.
+///
+/// A file has `newline_count + 1` lines, the way an editor displays it:
+/// - an empty file is one empty line (`[0]`)
+/// - `"a"` is one line, `"a\n"` is two: the last one is empty and starts at EOF
+/// - `"\n"` is two empty lines (`[0, 1]`)
+///
+/// Each line's bytes run up to the next offset (or EOF) and include its `\n`
+/// (and `\r`, for CRLF files), so offsets always map to real file positions.
+///
+/// Returns the offsets and whether the file predominantly uses CRLF.
+fn build_line_offsets(path: &Path, file_size: u64) -> Result<(Vec<u64>, bool), PeekError> {
+    // Giant files: scan a memory map directly, falling back if mapping fails
+    if file_size >= MMAP_INDEX_THRESHOLD {
+        if let Ok(result) = build_line_offsets_mmap(path) {
+            return Ok(result);
+        }
+    }
+    build_line_offsets_buffered(path)
+}
+
+fn build_line_offsets_buffered(path: &Path) -> Result<(Vec<u64>, bool), PeekError> {
+    let file = std::fs::File::open(path).at(path)?;
+    let reader = BufReader::with_capacity(64 * 1024, file); // 64KB buffer for fast scanning
+    let (offsets, crlf, _) = scan_line_offsets(reader)?;
+    Ok((offsets, crlf))
+}
+
+/// Sequential scan of any byte stream (plain file or decompressed gzip).
+/// Returns the offsets, the CRLF flag and the total number of bytes seen.
+fn scan_line_offsets<R: BufRead>(mut reader: R) -> Result<(Vec<u64>, bool, u64), PeekError> {
+    // Build offsets: the byte position where each line starts
+    let mut offsets: Vec<u64> = Vec::new();
+    offsets.push(0); // Line 0 starts at byte 0
+
+    let mut byte_pos: u64 = 0;
+    let mut crlf_lines: usize = 0;
+    let mut line = Vec::new();
+    loop {
+        line.clear();
+        let n = reader.read_until(b'\n', &mut line)?;
+        if n == 0 {
+            break;
+        }
+        byte_pos += n as u64;
+        // Only a terminated line starts another one; a final unterminated
+        // segment is the last line
+        if line.ends_with(b"\n") {
+            offsets.push(byte_pos);
+            if line.ends_with(b"\r\n") {
+                crlf_lines += 1;
+            }
+        }
+    }
+
+    let crlf = is_mostly_crlf(crlf_lines, offsets.len() - 1);
+    Ok((offsets, crlf, byte_pos))
+}
+
+/// memchr over a read-only map: no per-line allocations on multi-GB files.
+fn build_line_offsets_mmap(path: &Path) -> Result<(Vec<u64>, bool), PeekError> {
+    let file = std::fs::File::open(path).at(path)?;
+    // SAFETY: the map is read-only and dropped before returning. If another
+    // process truncates the file mid-scan we may fault, the usual mmap caveat.
+    let map = unsafe { memmap2::Mmap::map(&file) }?;
+    let data: &[u8] = &map;
+
+    let mut offsets: Vec<u64> = Vec::new();
+    offsets.push(0);
+
+    let mut crlf_lines: usize = 0;
+    for pos in memchr::memchr_iter(b'\n', data) {
+        offsets.push(pos as u64 + 1);
+        if pos > 0 && data[pos - 1] == b'\r' {
+            crlf_lines += 1;
+        }
+    }
+
+    let crlf = is_mostly_crlf(crlf_lines, offsets.len() - 1);
+    Ok((offsets, crlf))
+}
+
+/// Mixed files take whichever ending most lines use.
+fn is_mostly_crlf(crlf_lines: usize, newlines: usize) -> bool {
+    crlf_lines * 2 > newlines
+}
+
+/// Read the last `count` lines of an unindexed file by scanning backwards
+/// from its end, never reading more than `TAIL_SCAN_LIMIT` bytes. Lines
+/// follow the index semantics: a trailing newline ends in an empty last line.
+/// Also returns the byte offset the lines start at.
+fn scan_tail_lines(path: &Path, count: usize) -> Result<(TailResponse, u64), PeekError> {
+    const CHUNK: u64 = 64 * 1024;
+    let mut file = std::fs::File::open(path).at(path)?;
+    let size = file.metadata()?.len();
+
+    let mut buf = vec![0u8; CHUNK as usize];
+    let mut pos = size;
+    let mut newlines = 0;
+    let mut earliest_newline = None;
+    let mut start = None;
+    'scan: while pos > 0 && size - pos < TAIL_SCAN_LIMIT {
+        let read = CHUNK.min(pos);
+        pos -= read;
+        file.seek(SeekFrom::Start(pos))?;
+        file.read_exact(&mut buf[..read as usize])?;
+        for i in (0..read as usize).rev() {
+            if buf[i] == b'\n' {
+                newlines += 1;
+                earliest_newline = Some(pos + i as u64);
+                if newlines == count {
+                    start = Some(pos + i as u64 + 1);
+                    break 'scan;
+                }
+            }
+        }
+    }
+
+    let (start, start_line, lines_read, total_lines) = match (start, earliest_newline) {
+        (Some(start), _) => (start, None, count, None),
+        // Reached the start of the file: the whole thing has fewer lines
+        _ if pos == 0 => (0, Some(0), newlines + 1, Some(newlines + 1)),
+        // Hit the scan limit: drop the partial line at the window's start
+        (None, Some(newline)) => (newline + 1, None, newlines, None),
+        (None, None) => return Err("Last line is longer than the tail scan limit".into()),
+    };
+
+    let mut content = Vec::new();
+    file.seek(SeekFrom::Start(start))?;
+    file.read_to_end(&mut content)?;
+
+    let tail = TailResponse {
+        content: strip_crlf(String::from_utf8_lossy(&content).to_string()),
+        start_line,
+        lines_read,
+        total_lines,
+    };
+    Ok((tail, start))
+}
+
+/// Update line offsets after lines `start_line..end_line` (ending at
+/// `end_byte`) were replaced by `new`, in O(edit size + lines after it)
+/// instead of rescanning the file. The old start of `end_line` always goes:
+/// either `new` ends with a newline and re-creates it, or `new`'s last line
+/// now runs into it.
+fn splice_line_offsets(offsets: &mut Vec<u64>, start_line: usize, end_line: usize, end_byte: u64, new: &[u8]) {
+    let start_byte = offsets[start_line];
+    let delta = new.len() as i64 - (end_byte - start_byte) as i64;
+    let tail = (end_line + 1).min(offsets.len());
+    for offset in &mut offsets[tail..] {
+        *offset = (*offset as i64 + delta) as u64;
+    }
+    let starts = std::iter::once(start_byte)
+        .chain(memchr::memchr_iter(b'\n', new).map(|i| start_byte + i as u64 + 1));
+    offsets.splice(start_line..tail, starts);
+}
+
+/// Cheap change detector: hash of the first and last 64KB of a file's first
+/// `file_size` bytes (so it also tells whether a grown file still starts with
+/// what was indexed).
+fn quick_fingerprint(path: &Path, file_size: u64) -> Result<String, PeekError> {
+    const SAMPLE: u64 = 64 * 1024;
+    let mut file = std::fs::File::open(path).at(path)?;
+    let mut hasher = Sha256::new();
+    let mut buf = Vec::new();
+
+    (&mut file).take(SAMPLE.min(file_size)).read_to_end(&mut buf)?;
+    hasher.update(&buf);
+
+    if file_size > SAMPLE {
+        buf.clear();
+        let tail_start = file_size.saturating_sub(SAMPLE).max(SAMPLE);
+        file.seek(SeekFrom::Start(tail_start))?;
+        (&mut file).take(file_size - tail_start).read_to_end(&mut buf)?;
+        hasher.update(&buf);
+    }
+
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+/// End of the content an index addresses. For plain files that's the size
+/// on disk now, so an appended-to file reads through to its new end.
+fn indexed_content_size(path: &str, index: &LineIndex) -> Result<u64, PeekError> {
+    if index.decompressed_size.is_some() || index.buffer.is_some() {
+        Ok(index.content_size())
+    } else {
+        Ok(fs::metadata(path).at(path)?.len())
+    }
+}
+
+/// Raw bytes `start_byte..end_byte` of what an index addresses: the file,
+/// its decompressed stream, or an unsaved buffer.
+fn read_indexed_bytes(path: &str, index: &LineIndex, start_byte: u64, end_byte: u64) -> Result<Vec<u8>, PeekError> {
+    let mut buffer = vec![0u8; (end_byte - start_byte) as usize];
+
+    if let Some(text) = &index.buffer {
+        buffer.copy_from_slice(&text.as_bytes()[start_byte as usize..end_byte as usize]);
+    } else if index.decompressed_size.is_some() {
+        // No random access into gzip: decompress and discard up to the range,
+        // from where the last read stopped if that's not past it
+        let mut cursor = index.gzip_cursor.lock()?;
+        let resumable = cursor.as_ref().is_some_and(|cursor| cursor.position <= start_byte);
+        let mut current = match cursor.take() {
+            Some(current) if resumable => current,
+            _ => GzipCursor { decoder: open_gzip(&paths::resolve_path(path))?, position: 0 },
+        };
+        std::io::copy(&mut (&mut current.decoder).take(start_byte - current.position), &mut std::io::sink())?;
+        current.decoder.read_exact(&mut buffer)?;
+        current.position = end_byte;
+        *cursor = Some(current);
+    } else {
+        let mut file = std::fs::File::open(paths::resolve_path(path))?;
+        file.seek(SeekFrom::Start(start_byte))?;
+        file.read_exact(&mut buffer)?;
+    }
+    Ok(buffer)
+}
+
+/// Read `line_count` lines starting at `start_line` using a file's line index.
+fn read_indexed_lines(
+    path: &str,
+    index: &LineIndex,
+    start_line: usize,
+    line_count: usize,
+) -> Result<LinesResponse, PeekError> {
+    let offsets = &index.offsets;
+    let total_lines = offsets.len();
+    let safe_start = start_line.min(total_lines.saturating_sub(1));
+    let safe_end = safe_start.saturating_add(line_count).min(total_lines);
+    let actual_count = safe_end - safe_start;
+
+    if actual_count == 0 {
+        return Ok(LinesResponse {
+            content: String::new(),
+            start_line: safe_start,
+            lines_read: 0,
+            truncated_lines: Vec::new(),
+        });
+    }
+
+    let start_byte = offsets[safe_start];
+    // End byte: if safe_end < total_lines, use that offset; otherwise read to EOF
+    let end_byte = if safe_end < total_lines { offsets[safe_end] } else { indexed_content_size(path, index)? };
+    let buffer = read_indexed_bytes(path, index, start_byte, end_byte)?;
+
+    let content = strip_crlf(encoding::decode(&buffer, index.encoding, start_byte == 0));
+
+    Ok(LinesResponse {
+        content,
+        start_line: safe_start,
+        lines_read: actual_count,
+        truncated_lines: Vec::new(),
+    })
+}
+
+/// What a command's `path` names in the index cache: an unsaved buffer's key
+/// (see `index_content`) as given, anything else resolved like a file path.
+fn index_key(path: String, state: &LineIndexCache) -> Result<String, PeekError> {
+    let cache = state.0.lock()?;
+    if cache.entries.get(&path).is_some_and(|index| index.buffer.is_some()) {
+        return Ok(path);
+    }
+    Ok(paths::resolve(&path))
+}
+
+/// Scan a file, store its index in the cache and describe it. With `gzip`,
+/// the file is decompressed sequentially and offsets address the decompressed
+/// stream (gzip can't seek, so this is the only way to index it).
+/// The encoding is guessed from the head of the (decompressed) content; files
+/// in encodings that can't be indexed by byte (UTF-16) fail with
+/// `UNSUPPORTED_ENCODING`, naming the encoding.
+fn index_and_cache(path: String, gzip: bool, cache: &LineIndexCache) -> Result<IndexResponse, PeekError> {
+    let resolved = paths::resolve_path(&path);
+    let file_path = resolved.as_path();
+    let metadata = regular_file_metadata(file_path).at(file_path)?;
+    let file_size = metadata.len();
+
+    let mut sample = Vec::new();
+    if gzip {
+        open_gzip(file_path)?.take(encoding::SAMPLE_BYTES as u64).read_to_end(&mut sample)
+    } else {
+        std::fs::File::open(file_path)
+            .at(file_path)?
+            .take(encoding::SAMPLE_BYTES as u64)
+            .read_to_end(&mut sample)
+    }?;
+    let detected = encoding::detect(&sample);
+    if !encoding::is_line_indexable(detected) {
+        return Err(PeekError::unsupported_encoding(detected.name()));
+    }
+
+    let (offsets, crlf, decompressed_size) = if gzip {
+        let (offsets, crlf, size) = scan_line_offsets(BufReader::new(open_gzip(file_path)?))?;
+        (offsets, crlf, Some(size))
+    } else {
+        let (offsets, crlf) = build_line_offsets(file_path, file_size)?;
+        (offsets, crlf, None)
+    };
+    let index = LineIndex {
+        offsets,
+        crlf,
+        encoding: detected,
+        file_size,
+        mtime: unix_mtime(&metadata),
+        fingerprint: quick_fingerprint(file_path, file_size)?,
+        last_used: 0,
+        decompressed_size,
+        buffer: None,
+        gzip_cursor: Mutex::new(None),
+    };
+    let response = index.response(true);
+
+    let mut cache = cache.0.lock()?;
+    cache.insert(path, index);
+
+    Ok(response)
+}
+
+fn is_gzip_path(path: &Path) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| e.eq_ignore_ascii_case("gz"))
+}
+
+/// Open a gzip file for decompression. Multi-member streams (e.g. logs that
+/// were appended to with `gzip >>`) are read through to the end.
+fn open_gzip(path: &Path) -> Result<MultiGzDecoder<BufReader<std::fs::File>>, PeekError> {
+    let file = std::fs::File::open(path).at(path)?;
+    Ok(MultiGzDecoder::new(BufReader::new(file)))
+}
+
+/// Decompress a `.gz` file for `read_file_content`. Only the first
+/// `LARGE_FILE_THRESHOLD` bytes are kept; larger files are just measured and
+/// left to `index_gzip_file` + `read_lines`.
+fn read_gzip_content(
+    path: &Path,
+    file_name: String,
+    extension: String,
+    size: u64,
+    mtime: u64,
+    force_encoding: Option<&'static encoding_rs::Encoding>,
+    force_text: bool,
+) -> Result<FileResponse, PeekError> {
+    let mut decoder = open_gzip(path)?;
+    let mut head = Vec::new();
+    (&mut decoder).take(LARGE_FILE_THRESHOLD + 1).read_to_end(&mut head)?;
+
+    let is_large_file = head.len() as u64 > LARGE_FILE_THRESHOLD;
+    let decompressed_size = if is_large_file {
+        head.len() as u64 + std::io::copy(&mut decoder, &mut std::io::sink())?
+    } else {
+        head.len() as u64
+    };
+
+    let is_binary = !force_text && looks_binary(&head, force_encoding);
+    let text_encoding = force_encoding.unwrap_or_else(|| encoding::detect(&head[..head.len().min(encoding::SAMPLE_BYTES)]));
+    let preview = (is_large_file && !is_binary).then(|| large_file_preview(&head, text_encoding)).flatten();
+    let content = if is_binary {
+        "Binary file detected".to_string()
+    } else if is_large_file {
+        preview.as_ref().map(|(text, _, _)| text.clone()).unwrap_or_default()
+    } else {
+        encoding::decode(&head, text_encoding, false)
+    };
+
+    Ok(FileResponse {
+        content,
+        file_name,
+        extension,
+        size,
+        mtime,
+        is_binary,
+        is_large_file: is_large_file && !is_binary,
+        decompressed_size: Some(decompressed_size),
+        is_minified: false,
+        encoding: (!is_binary).then(|| text_encoding.name().to_string()),
+        preview_end: preview.as_ref().map(|(_, end, _)| *end),
+        preview_lines: preview.map(|(_, _, lines)| lines),
+        truncated_lines: Vec::new(),
+    })
+}
+
+/// Binary sniffing: a NUL in the first 1KB, unless the text is in UTF-16
+/// (forced, or announced by a BOM), where NULs are ordinary.
+fn looks_binary(head: &[u8], force_encoding: Option<&'static encoding_rs::Encoding>) -> bool {
+    let utf16 = force_encoding
+        .or_else(|| encoding_rs::Encoding::for_bom(head).map(|(encoding, _)| encoding))
+        .is_some_and(|encoding| !encoding.is_ascii_compatible());
+    !utf16 && head[..head.len().min(1024)].contains(&0)
+}
+
+/// The whole lines at the top of a large file, decoded the way `read_lines`
+/// would return them, with the byte offset they end at and their count.
+/// `None` when the head holds no complete line (or for UTF-16, whose lines
+/// `read_lines` can't address).
+fn large_file_preview(head: &[u8], text_encoding: &'static encoding_rs::Encoding) -> Option<(String, u64, usize)> {
+    if !encoding::is_line_indexable(text_encoding) {
+        return None;
+    }
+    let head = &head[..head.len().min(PREVIEW_BYTES)];
+    let end = memchr::memrchr(b'\n', head)? + 1;
+    let lines = memchr::memchr_iter(b'\n', &head[..end]).count();
+    let content = strip_crlf(encoding::decode(&head[..end], text_encoding, true));
+    Some((content, end as u64, lines))
+}
+
+/// Whether a file of this type reads as minified code (see `beautify`).
+fn is_minified_code(extension: &str, sample: &[u8]) -> bool {
+    beautify::BEAUTIFY_EXTENSIONS.contains(&extension) && beautify::looks_minified(sample)
+}
+
+/// Strip the `\r` from every `\r\n` so the editor only ever sees `\n`.
+fn strip_crlf(content: String) -> String {
+    if content.contains("\r\n") {
+        content.replace("\r\n", "\n")
+    } else {
+        content
+    }
+}
+
+/// Convert editor text (`\n`) to the file's line ending before writing.
+fn to_file_line_endings(content: &str, crlf: bool) -> String {
+    if crlf {
+        content.replace("\r\n", "\n").replace('\n', "\r\n")
+    } else {
+        content.to_string()
+    }
+}
+
+fn line_ending_name(crlf: bool) -> String {
+    if crlf { "crlf" } else { "lf" }.to_string()
+}
+
+/// A non-empty file ends with `\n` exactly when its last line starts at EOF.
+fn ends_with_newline(offsets: &[u64], file_size: u64) -> bool {
+    file_size > 0 && offsets.last() == Some(&file_size)
+}
+
+/// Expand tabs to spaces, honoring tab stops every `tab_width` columns.
+fn expand_tabs(line: &str, tab_width: usize) -> String {
+    let mut out = String::with_capacity(line.len());
+    let mut column = 0;
+    for ch in line.chars() {
+        if ch == '\t' {
+            let spaces = tab_width - column % tab_width;
+            out.extend(std::iter::repeat_n(' ', spaces));
+            column += spaces;
+        } else {
+            out.push(ch);
+            column += 1;
+        }
+    }
+    out
+}
+
+/// Cut lines longer than `max_line_bytes` (UTF-8, at a character boundary) in
+/// place, keeping their line endings. `first_line` is the file line `text`
+/// starts at.
+fn truncate_long_lines(text: &mut String, first_line: usize, max_line_bytes: usize) -> Vec<TruncatedLine> {
+    let mut truncated = Vec::new();
+    let mut out = String::with_capacity(text.len());
+    for (i, raw) in text.split_inclusive('\n').enumerate() {
+        let line = raw.trim_end_matches(['\n', '\r']);
+        if line.len() <= max_line_bytes {
+            out.push_str(raw);
+            continue;
+        }
+        let mut cut = max_line_bytes;
+        while !line.is_char_boundary(cut) {
+            cut -= 1;
+        }
+        out.push_str(&line[..cut]);
+        out.push_str(&raw[line.len()..]);
+        truncated.push(TruncatedLine { line: first_line + i, byte_length: line.len() });
+    }
+    if !truncated.is_empty() {
+        *text = out;
+    }
+    truncated
+}
+
+/// Apply opt-in save transforms line by line, keeping each line's own ending.
+fn apply_save_transforms(
+    content: &str,
+    trim_trailing_ws: bool,
+    ensure_final_newline: bool,
+    tabs_to_spaces: Option<usize>,
+) -> String {
+    let mut out = String::with_capacity(content.len() + 2);
+    for line in content.split_inclusive('\n') {
+        let (body, ending) = match line.strip_suffix("\r\n") {
+            Some(body) => (body, "\r\n"),
+            None => match line.strip_suffix('\n') {
+                Some(body) => (body, "\n"),
+                None => (line, ""),
+            },
+        };
+        let body = match tabs_to_spaces {
+            Some(width) => expand_tabs(body, width),
+            None => body.to_string(),
+        };
+        let body = if trim_trailing_ws { body.trim_end_matches([' ', '\t']) } else { &body };
+        out.push_str(body);
+        out.push_str(ending);
+    }
+
+    if ensure_final_newline && !out.is_empty() && !out.ends_with('\n') {
+        out.push_str(if content.contains("\r\n") { "\r\n" } else { "\n" });
+    }
+    out
+}
+
+/// Number of bytes at the end of `bytes` that form an incomplete UTF-8
+/// sequence and should be carried over to the next chunk.
+fn incomplete_utf8_tail(bytes: &[u8]) -> usize {
+    for back in 1..=bytes.len().min(3) {
+        let byte = bytes[bytes.len() - back];
+        if byte & 0xC0 != 0x80 {
+            // Lead byte: how long should this sequence be?
+            let expected = match byte {
+                0xC0..=0xDF => 2,
+                0xE0..=0xEF => 3,
+                0xF0..=0xF7 => 4,
+                _ => 1,
+            };
+            return if expected > back { back } else { 0 };
+        }
+    }
+    0
+}
+
+/// SHA-256 of a file's contents as lowercase hex, streamed in 64KB blocks.
+fn hash_file(path: &Path) -> Result<String, PeekError> {
+    let mut file = std::fs::File::open(path).at(path)?;
+    let mut hasher = Sha256::new();
+    let mut buf = vec![0u8; 64 * 1024];
+    loop {
+        let n = file.read(&mut buf)?;
+        if n == 0 { break; }
+        hasher.update(&buf[..n]);
+    }
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+/// Copy a file, keeping its permissions and modification time.
+fn copy_with_mtime(src: &Path, dest: &Path, src_metadata: &fs::Metadata) -> std::io::Result<()> {
+    // fs::copy carries permissions over, but not timestamps
+    fs::copy(src, dest)?;
+    if let Ok(modified) = src_metadata.modified() {
+        // The copy may be read-only, so open with just enough access to set times
+        #[cfg(windows)]
+        let dest_file = {
+            use std::os::windows::fs::OpenOptionsExt;
+            const FILE_WRITE_ATTRIBUTES: u32 = 0x100;
+            std::fs::File::options().access_mode(FILE_WRITE_ATTRIBUTES).open(dest)
+        };
+        #[cfg(not(windows))]
+        let dest_file = std::fs::File::open(dest);
+
+        let dest_file = dest_file?;
+        dest_file.set_modified(modified)?;
+    }
+    Ok(())
+}
+
+/// Before a save with `backup`, copy the current file to `path` + `suffix`
+/// (`~` by default). A new file has nothing to back up. Errors are
+/// `BACKUP_FAILED` and the caller must not go on to save.
+fn backup_before_save(path: &str, backup: Option<bool>, suffix: Option<String>) -> Result<(), PeekError> {
+    if !backup.unwrap_or(false) {
+        return Ok(());
+    }
+    let file_path = Path::new(path);
+    let metadata = match fs::metadata(file_path) {
+        Ok(metadata) => metadata,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+        Err(e) => return Err(PeekError::backup_failed(e.to_string())),
+    };
+    let backup_path = format!("{}{}", path, suffix.as_deref().unwrap_or(DEFAULT_BACKUP_SUFFIX));
+    copy_with_mtime(file_path, Path::new(&backup_path), &metadata)
+        .map_err(|e| PeekError::backup_failed(e.to_string()).at(&backup_path))
+}
+
+/// Resident set size of this process.
+#[cfg(target_os = "linux")]
+fn process_rss() -> Option<u64> {
+    let status = fs::read_to_string("/proc/self/status").ok()?;
+    let line = status.lines().find(|line| line.starts_with("VmRSS:"))?;
+    let kib: u64 = line["VmRSS:".len()..].trim().trim_end_matches("kB").trim().parse().ok()?;
+    Some(kib * 1024)
+}
+
+#[cfg(not(target_os = "linux"))]
+fn process_rss() -> Option<u64> {
+    let pid = sysinfo::get_current_pid().ok()?;
+    let mut system = sysinfo::System::new();
+    system.refresh_processes(sysinfo::ProcessesToUpdate::Some(&[pid]), true);
+    system.process(pid).map(|process| process.memory())
+}
+
+/// Directory a save to `path` puts its temp file in.
+fn parent_dir(path: &Path) -> &Path {
+    match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    }
+}
+
+/// Fail with `NO_SPACE` before writing `needed` bytes next to `path` if its
+/// file system can't hold them, rather than leaving a truncated temp file.
+/// If free space can't be queried the save goes ahead and reports any error.
+fn ensure_disk_space(path: &Path, needed: u64) -> Result<(), PeekError> {
+    match fs2::available_space(parent_dir(path)) {
+        Ok(available) if available < needed => Err(PeekError::no_space(format!("{} bytes needed, {} free", needed, available))),
+        _ => Ok(()),
+    }
+}
+
+// ── Commands ──────────────────────────────────────────────────────────────────
+
+/// Read a file for display. The I/O runs on the blocking pool so a slow disk
+/// or network mount doesn't tie up a command worker; with `timeout_ms` it
+/// fails with `TIMEOUT` instead of waiting on an unresponsive file system.
+/// SVGs are sanitized before being inlined unless `sanitize_svg` is `false`.
+/// Text is decoded with its detected encoding, or with `force_encoding` (any
+/// WHATWG label, e.g. from `detect_encoding_candidates`) to reopen it as
+/// another; an unknown label fails with `UNKNOWN_ENCODING`.
+/// Large files come back with just their first lines (see `preview_end`) to
+/// show while `index_file` runs. With `max_line_bytes`, text lines longer
+/// than that (minified code, base64 blobs) are cut short and listed in
+/// `truncated_lines`; such content is for display and must not be saved back.
+/// With `expand_path`, `path` is first expanded like the `expand_path`
+/// command (`~`, environment variables), for paths typed by the user.
+/// `force_text` skips the binary check, for files the user knows are text
+/// (NUL bytes, UTF-16 without a BOM): undecodable bytes come back as U+FFFD.
+/// Pipes, devices and generated `/proc` / `/sys` files fail with `IS_FIFO`,
+/// `IS_DEVICE` or `IS_STREAM`; read those with `read_stream_snapshot`.
+#[tauri::command]
+async fn read_file_content(
+    path: String,
+    timeout_ms: Option<u64>,
+    sanitize_svg: Option<bool>,
+    force_encoding: Option<String>,
+    max_line_bytes: Option<usize>,
+    expand_path: Option<bool>,
+    force_text: Option<bool>,
+) -> Result<FileResponse, PeekError> {
+    let force_text = force_text.unwrap_or(false);
+    let path = if expand_path.unwrap_or(false) {
+        paths::expand(&path, None)?.to_string_lossy().to_string()
+    } else {
+        paths::resolve(&path)
+    };
+    let sanitize_svg = sanitize_svg.unwrap_or(true);
+    let force_encoding = force_encoding.as_deref().map(encoding::for_label).transpose()?;
+    let loaded = tauri::async_runtime::spawn_blocking(move || match timeout_ms {
+        Some(ms) => with_timeout(ms, move || load_file_content(path, sanitize_svg, force_encoding, force_text)),
+        None => load_file_content(path, sanitize_svg, force_encoding, force_text),
+    })
+    .await?;
+    let mut response = loaded?;
+    if let Some(max) = max_line_bytes.filter(|_| response.encoding.is_some()) {
+        response.truncated_lines = truncate_long_lines(&mut response.content, 0, max);
+    }
+    Ok(response)
+}
 
-// ── Commands ──────────────────────────────────────────────────────────────────
+/// Read several files in one call, each exactly as `read_file_content` would
+/// (binary, image and large-file handling included). A file that can't be read
+/// gets an `error` instead of failing the batch. Files are read in order while
+/// their content fits in `max_total_bytes` (16 MB by default); from the first
+/// one that doesn't, the rest are left out and `truncated` is set.
+#[tauri::command]
+async fn read_files(paths: Vec<String>, max_total_bytes: Option<u64>) -> Result<BatchResponse, PeekError> {
+    let budget = max_total_bytes.unwrap_or(DEFAULT_BATCH_READ_BYTES);
+    let batch = tauri::async_runtime::spawn_blocking(move || {
+        let mut files = Vec::new();
+        let mut total = 0;
+        for path in &paths {
+            match load_file_content(path.clone(), true, None, false) {
+                Ok(file) => {
+                    total += file.content.len() as u64;
+                    if total > budget {
+                        return BatchResponse { files, truncated: true };
+                    }
+                    files.push(BatchFile { path: path.clone(), file: Some(file), error: None });
+                }
+                Err(e) => files.push(BatchFile { path: path.clone(), file: None, error: Some(e) }),
+            }
+        }
+        BatchResponse { files, truncated: false }
+    })
+    .await?;
+    Ok(batch)
+}
 
+/// Stat a file. Probing is bounded by `timeout_ms` (3s by default) so a dead
+/// mount reports `TIMEOUT` rather than hanging.
 #[tauri::command]
-fn read_file_content(path: String) -> Result<FileResponse, String> {
-    let file_path = Path::new(&path);
+async fn get_file_metadata(path: String, timeout_ms: Option<u64>) -> Result<FileInfo, PeekError> {
+    let timeout_ms = timeout_ms.unwrap_or(DEFAULT_METADATA_TIMEOUT_MS);
+    tauri::async_runtime::spawn_blocking(move || with_timeout(timeout_ms, move || file_info(&paths::resolve_path(&path))))
+        .await?
+}
+
+fn load_file_content(
+    path: String,
+    sanitize_svg: bool,
+    force_encoding: Option<&'static encoding_rs::Encoding>,
+    force_text: bool,
+) -> Result<FileResponse, PeekError> {
+    let resolved = paths::resolve_path(&path);
+    let file_path = resolved.as_path();
     
-    let metadata = fs::metadata(file_path).map_err(|e| e.to_string())?;
+    let metadata = regular_file_metadata(file_path).at(file_path)?;
+    if is_pseudo_file(file_path, &metadata) {
+        return Err(PeekError::is_stream("Generated file with no size; use read_stream_snapshot"));
+    }
     let size = metadata.len();
     
     let mtime = metadata.modified()
@@ -72,13 +1268,18 @@ fn read_file_content(path: String) -> Result<FileResponse, String> {
         .unwrap_or("")
         .to_lowercase();
         
-    let is_image_ext = ["png", "jpg", "jpeg", "gif", "bmp", "svg", "webp", "ico"].contains(&extension.as_str());
+    let is_image_ext = IMAGE_EXTENSIONS.contains(&extension.as_str());
+
+    // Rotated logs etc.: decompress `.gz` transparently
+    if extension == "gz" {
+        return read_gzip_content(file_path, file_name, extension, size, mtime, force_encoding, force_text);
+    }
 
     // Check for binary via magic bytes
-    let mut file = std::fs::File::open(file_path).map_err(|e| e.to_string())?;
+    let mut file = std::fs::File::open(file_path).at(file_path)?;
     let mut buffer = [0; 1024];
-    let count = file.read(&mut buffer).map_err(|e| e.to_string())?;
-    let has_null_byte = buffer[..count].contains(&0);
+    let count = file.read(&mut buffer)?;
+    let has_null_byte = !force_text && looks_binary(&buffer[..count], force_encoding);
 
     if has_null_byte && !is_image_ext {
         return Ok(FileResponse {
@@ -89,14 +1290,29 @@ fn read_file_content(path: String) -> Result<FileResponse, String> {
             mtime,
             is_binary: true,
             is_large_file: false,
+            decompressed_size: None,
+            is_minified: false,
+            encoding: None,
+            preview_end: None,
+            preview_lines: None,
+            truncated_lines: Vec::new(),
         });
     }
 
     if is_image_ext {
-        let bytes = fs::read(file_path).map_err(|e| e.to_string())?;
-        use base64::{Engine as _, engine::general_purpose};
-        let b64 = general_purpose::STANDARD.encode(bytes);
-        let content = format!("data:image/{};base64,{}", if extension == "svg" { "svg+xml" } else { &extension }, b64);
+        // Raster images stream through the `peek-img` protocol instead of being
+        // inlined as base64; SVG is small text and stays a data URI, with
+        // scripts, handlers and external refs stripped first
+        let content = if extension == "svg" {
+            let mut bytes = fs::read(file_path).at(file_path)?;
+            if sanitize_svg {
+                bytes = svg::sanitize_svg(&String::from_utf8_lossy(&bytes)).into_bytes();
+            }
+            use base64::{Engine as _, engine::general_purpose};
+            format!("data:image/svg+xml;base64,{}", general_purpose::STANDARD.encode(bytes))
+        } else {
+            protocol::image_url(&resolved.to_string_lossy())
+        };
         
         return Ok(FileResponse {
             content,
@@ -106,248 +1322,1707 @@ fn read_file_content(path: String) -> Result<FileResponse, String> {
             mtime,
             is_binary: false,
             is_large_file: false,
+            decompressed_size: None,
+            is_minified: false,
+            encoding: None,
+            preview_end: None,
+            preview_lines: None,
+            truncated_lines: Vec::new(),
         }); 
     }
 
-    // Large file: return empty content, frontend will use index_file + read_lines
+    // Large file: return the top of it to show while the frontend runs
+    // index_file, then read_lines for the rest
     if size > LARGE_FILE_THRESHOLD {
+        let mut sample = Vec::new();
+        file.seek(SeekFrom::Start(0))?;
+        (&mut file).take(MINIFIED_SAMPLE_BYTES).read_to_end(&mut sample)?;
+        let is_minified = is_minified_code(&extension, &sample);
+        let text_encoding = force_encoding.unwrap_or_else(|| encoding::detect(&sample));
+        let preview = large_file_preview(&sample, text_encoding);
+
         return Ok(FileResponse {
-            content: String::new(),
+            content: preview.as_ref().map(|(text, _, _)| text.clone()).unwrap_or_default(),
             file_name,
             extension,
             size,
             mtime,
             is_binary: false,
             is_large_file: true,
+            decompressed_size: None,
+            is_minified,
+            encoding: Some(text_encoding.name().to_string()),
+            preview_end: preview.as_ref().map(|(_, end, _)| *end),
+            preview_lines: preview.map(|(_, _, lines)| lines),
+            truncated_lines: Vec::new(),
+        });
+    }
+
+    // Standard small file
+    file.seek(SeekFrom::Start(0))?;
+    let mut bytes = Vec::new();
+    file.read_to_end(&mut bytes)?;
+    let text_encoding = force_encoding.unwrap_or_else(|| encoding::detect(&bytes[..bytes.len().min(encoding::SAMPLE_BYTES)]));
+    // A BOM stays in the text so saving writes it back
+    let full_content = encoding::decode(&bytes, text_encoding, false);
+    let is_minified = is_minified_code(&extension, full_content.as_bytes());
+
+    Ok(FileResponse {
+        content: full_content,
+        file_name,
+        extension,
+        size,
+        mtime,
+        is_binary: false,
+        is_large_file: false,
+        decompressed_size: None,
+        is_minified,
+        encoding: Some(text_encoding.name().to_string()),
+        preview_end: None,
+        preview_lines: None,
+        truncated_lines: Vec::new(),
+    })
+}
+
+/// Scan a file and build an index of byte offsets for each line start.
+/// Returns total number of lines and file size (see `build_line_offsets` for
+/// how trailing newlines are counted). `.gz` files are indexed decompressed.
+#[tauri::command]
+async fn index_file(path: String, app: tauri::AppHandle) -> Result<IndexResponse, PeekError> {
+    let path = paths::resolve(&path);
+    tauri::async_runtime::spawn_blocking(move || {
+        let gzip = is_gzip_path(Path::new(&path));
+        index_and_cache(path, gzip, &app.state::<LineIndexCache>())
+    })
+    .await?
+}
+
+/// Index a gzip-compressed file by decompressing it sequentially, whatever
+/// its extension. `read_lines` then returns decompressed text.
+#[tauri::command]
+fn index_gzip_file(path: String, state: tauri::State<'_, LineIndexCache>) -> Result<IndexResponse, PeekError> {
+    let path = paths::resolve(&path);
+    index_and_cache(path, true, &state)
+}
+
+/// Re-index only if the file changed since it was last indexed (size, mtime,
+/// or a head/tail sample hash). `reindexed` in the response says which happened.
+#[tauri::command]
+fn reindex_if_needed(path: String, state: tauri::State<'_, LineIndexCache>) -> Result<IndexResponse, PeekError> {
+    let path = index_key(path, &state)?;
+    {
+        // Buffers have no file to go stale against; `index_content` replaces them
+        let mut cache = state.0.lock()?;
+        if let Some(index) = cache.get(&path).filter(|index| index.buffer.is_some()) {
+            return Ok(index.response(false));
+        }
+    }
+
+    let file_path = Path::new(&path);
+    let metadata = fs::metadata(file_path).at(file_path)?;
+    let file_size = metadata.len();
+    let mut gzip = is_gzip_path(file_path);
+
+    {
+        let mut cache = state.0.lock()?;
+        if let Some(index) = cache.get(&path) {
+            let unchanged = index.file_size == file_size
+                && index.mtime == unix_mtime(&metadata)
+                && index.fingerprint == quick_fingerprint(file_path, file_size)?;
+            if unchanged {
+                return Ok(index.response(false));
+            }
+            gzip = index.decompressed_size.is_some();
+        }
+    }
+
+    index_and_cache(path, gzip, &state)
+}
+
+/// Bring a cached index up to date after its file changed on disk. If the
+/// file only grew and still starts with what was indexed (the log case), the
+/// new lines are scanned and appended to the offsets, and the first line that
+/// changed (the old last line, which may have been extended) and the new
+/// total come back. Any other change re-indexes from scratch. Files that
+/// aren't indexed, buffers and `.gz` indexes are left alone.
+fn follow_index(path: &str, state: &LineIndexCache) -> Result<Option<(usize, usize)>, PeekError> {
+    let file_path = Path::new(path);
+    let metadata = fs::metadata(file_path).at(file_path)?;
+    let new_size = metadata.len();
+    let (old_size, fingerprint, crlf) = {
+        let mut cache = state.0.lock()?;
+        let Some(index) = cache.entries.get_mut(path) else { return Ok(None) };
+        if index.buffer.is_some() || index.decompressed_size.is_some() {
+            return Ok(None);
+        }
+        if index.file_size == new_size && index.mtime == unix_mtime(&metadata) {
+            return Ok(None);
+        }
+        (index.file_size, index.fingerprint.clone(), index.crlf)
+    }; // Drop the lock before reading the file
+
+    if new_size > old_size && quick_fingerprint(file_path, old_size)? == fingerprint {
+        let mut file = std::fs::File::open(file_path).at(file_path)?;
+        file.seek(SeekFrom::Start(old_size))?;
+        let reader = BufReader::with_capacity(64 * 1024, file.take(new_size - old_size));
+        let (appended, appended_crlf, _) = scan_line_offsets(reader)?;
+        // If the new lines mostly agree with the file's ending, so does the
+        // whole file; otherwise it needs a full count
+        let ending_kept = appended.len() == 1 || appended_crlf == crlf;
+        if ending_kept {
+            let new_fingerprint = quick_fingerprint(file_path, new_size)?;
+            let mut cache = state.0.lock()?;
+            let Some(index) = cache.entries.get_mut(path) else { return Ok(None) };
+            // Re-indexed or followed by someone else meanwhile
+            if index.file_size != old_size || index.fingerprint != fingerprint {
+                return Ok(None);
+            }
+            let first_changed = index.offsets.len() - 1;
+            index.offsets.extend(appended.into_iter().skip(1).map(|offset| old_size + offset));
+            index.file_size = new_size;
+            index.mtime = unix_mtime(&metadata);
+            index.fingerprint = new_fingerprint;
+            let total_lines = index.offsets.len();
+            cache.evict(Some(path));
+            return Ok(Some((first_changed, total_lines)));
+        }
+    }
+    index_and_cache(path.to_string(), false, state)?;
+    Ok(None)
+}
+
+/// Index an unsaved buffer under `key` (any id, e.g. `untitled:1`), so
+/// `read_lines` and friends work on it without saving. The text is kept in
+/// the cache, counted against its budget; indexing the same key replaces it.
+#[tauri::command]
+fn index_content(key: String, content: String, state: tauri::State<'_, LineIndexCache>) -> Result<IndexResponse, PeekError> {
+    let (offsets, crlf, size) = scan_line_offsets(content.as_bytes())?;
+    let index = LineIndex {
+        offsets,
+        crlf,
+        encoding: encoding_rs::UTF_8,
+        file_size: size,
+        mtime: 0,
+        fingerprint: String::new(),
+        last_used: 0,
+        decompressed_size: None,
+        buffer: Some(content),
+        gzip_cursor: Mutex::new(None),
+    };
+    let response = index.response(true);
+
+    let mut cache = state.0.lock()?;
+    cache.insert(key, index);
+
+    Ok(response)
+}
+
+/// Read a range of lines from an indexed file. CRLF endings come back as `\n`.
+/// `overscan` adds up to that many lines on each side of the range, so a
+/// viewport and its scroll margins come back in one call; `start_line` in the
+/// response says where the content actually starts. With `max_line_bytes`,
+/// longer lines are cut short and listed in `truncated_lines`; read them
+/// again without the limit to show them in full. With `expand_tabs`, tabs
+/// become spaces up to the next stop of that width; only `content` changes,
+/// line numbers and `byte_length`s still describe the file.
+#[tauri::command]
+fn read_lines(
+    path: String,
+    start_line: usize,
+    line_count: usize,
+    overscan: Option<usize>,
+    max_line_bytes: Option<usize>,
+    expand_tabs: Option<u8>,
+    state: tauri::State<'_, LineIndexCache>,
+) -> Result<LinesResponse, PeekError> {
+    let path = index_key(path, &state)?;
+    let overscan = overscan.unwrap_or(0);
+    let first = start_line.saturating_sub(overscan);
+    let count = line_count.saturating_add(start_line - first).saturating_add(overscan);
+    let mut cache = state.0.lock()?;
+    let index = cache.get(&path).ok_or_else(PeekError::not_indexed_yet)?;
+    let mut lines = read_indexed_lines(&path, index, first, count)?;
+    if let Some(max) = max_line_bytes {
+        lines.truncated_lines = truncate_long_lines(&mut lines.content, lines.start_line, max);
+    }
+    if let Some(width) = expand_tabs.filter(|&width| width > 0) {
+        if lines.content.contains('\t') {
+            let expanded: Vec<String> = lines.content.split('\n').map(|line| self::expand_tabs(line, width as usize)).collect();
+            lines.content = expanded.join("\n");
+        }
+    }
+    Ok(lines)
+}
+
+/// Read lines as NDJSON: each line is parsed on its own, so a malformed entry
+/// is returned raw with its error instead of failing the whole range.
+#[tauri::command]
+fn read_ndjson_lines(
+    path: String,
+    start_line: usize,
+    line_count: usize,
+    state: tauri::State<'_, LineIndexCache>,
+) -> Result<Vec<NdjsonLine>, PeekError> {
+    let path = index_key(path, &state)?;
+    let mut cache = state.0.lock()?;
+    let index = cache.get(&path).ok_or_else(PeekError::not_indexed_yet)?;
+    let lines = read_indexed_lines(&path, index, start_line, line_count)?;
+
+    Ok(lines.content
+        .split_inclusive('\n')
+        .enumerate()
+        .map(|(i, raw)| {
+            let raw = raw.strip_suffix('\n').unwrap_or(raw).to_string();
+            let (parsed, parse_error) = if raw.trim().is_empty() {
+                (None, None)
+            } else {
+                match serde_json::from_str::<serde_json::Value>(&raw) {
+                    Ok(value) => (Some(value), None),
+                    Err(e) => (None, Some(e.to_string())),
+                }
+            };
+            NdjsonLine { line_number: lines.start_line + i, raw, parsed, parse_error }
+        })
+        .collect())
+}
+
+/// Read the last `count` lines, for log views that open at the end. Uses the
+/// cached index when there is one; otherwise the file is scanned backwards
+/// (bounded), which skips a full index but can't number the lines.
+#[tauri::command]
+fn read_lines_from_end(
+    path: String,
+    count: usize,
+    state: tauri::State<'_, LineIndexCache>,
+) -> Result<TailResponse, PeekError> {
+    let path = index_key(path, &state)?;
+    {
+        let mut cache = state.0.lock()?;
+        if let Some(index) = cache.get(&path) {
+            let total_lines = index.offsets.len();
+            let start_line = total_lines.saturating_sub(count);
+            let lines = read_indexed_lines(&path, index, start_line, count)?;
+            return Ok(TailResponse {
+                content: lines.content,
+                start_line: Some(lines.start_line),
+                lines_read: lines.lines_read,
+                total_lines: Some(total_lines),
+            });
+        }
+    }
+
+    let file_path = Path::new(&path);
+    regular_file_metadata(file_path).at(file_path)?;
+    if is_gzip_path(file_path) {
+        return Err(PeekError::not_indexed_yet());
+    }
+    if count == 0 {
+        return Ok(TailResponse { content: String::new(), start_line: None, lines_read: 0, total_lines: None });
+    }
+    let (tail, _) = scan_tail_lines(file_path, count)?;
+    Ok(tail)
+}
+
+/// The first `head_lines` and last `tail_lines` lines of a file without
+/// indexing it, for previews in a file picker: the head is read forwards and
+/// the tail backwards from the end, each at most `TAIL_SCAN_LIMIT` bytes, so
+/// a huge file costs no more than a small one. Text is decoded as UTF-8
+/// (lossily) with CRLF endings returned as `\n`.
+#[tauri::command]
+async fn peek_head_tail(path: String, head_lines: usize, tail_lines: usize) -> Result<HeadTailResponse, PeekError> {
+    let path = paths::resolve(&path);
+    tauri::async_runtime::spawn_blocking(move || -> Result<HeadTailResponse, PeekError> {
+        let file_path = paths::resolve_path(&path);
+        regular_file_metadata(&file_path).at(&file_path)?;
+        if is_gzip_path(&file_path) {
+            return Err("Compressed files can't be previewed without decompressing them".into());
+        }
+        let file = std::fs::File::open(&file_path).at(&file_path)?;
+        let mut reader = BufReader::new(file.take(TAIL_SCAN_LIMIT));
+        let mut head = Vec::new();
+        let mut read_lines = 0;
+        while read_lines < head_lines {
+            if reader.read_until(b'\n', &mut head)? == 0 {
+                break;
+            }
+            read_lines += 1;
+        }
+        if looks_binary(&head, None) {
+            return Ok(HeadTailResponse {
+                head: String::new(),
+                head_lines: 0,
+                tail: String::new(),
+                tail_lines: 0,
+                overlap: false,
+                is_binary: true,
+            });
+        }
+
+        let (tail, tail_start) = if tail_lines > 0 {
+            let (tail, start) = scan_tail_lines(&file_path, tail_lines)?;
+            (tail, Some(start))
+        } else {
+            (TailResponse { content: String::new(), start_line: None, lines_read: 0, total_lines: None }, None)
+        };
+        Ok(HeadTailResponse {
+            head: strip_crlf(String::from_utf8_lossy(&head).to_string()),
+            head_lines: read_lines,
+            overlap: tail_start.is_some_and(|start| start < head.len() as u64),
+            tail: tail.content,
+            tail_lines: tail.lines_read,
+            is_binary: false,
+        })
+    })
+    .await?
+}
+
+/// Read what a pipe, device or generated file (`/dev/stdin`,
+/// `/proc/self/fd/0`, `/proc/cpuinfo`) has to offer, for the paths
+/// `read_file_content` turns away with `IS_FIFO`, `IS_DEVICE` or `IS_STREAM`.
+/// Reads up to `max_bytes` (default 1 MB) for at most `timeout_ms` (default
+/// 2s) and returns whatever arrived, decoded as UTF-8 (lossily). A read
+/// still blocked at the timeout is abandoned, not cancelled.
+#[tauri::command]
+async fn read_stream_snapshot(
+    path: String,
+    max_bytes: Option<usize>,
+    timeout_ms: Option<u64>,
+) -> Result<StreamSnapshot, PeekError> {
+    let path = paths::resolve(&path);
+    let max_bytes = max_bytes.unwrap_or(DEFAULT_STREAM_SNAPSHOT_BYTES);
+    let deadline = std::time::Instant::now() + std::time::Duration::from_millis(timeout_ms.unwrap_or(DEFAULT_STREAM_TIMEOUT_MS));
+    let file_path = paths::resolve_path(&path);
+    if file_path.is_dir() {
+        return Err(PeekError::is_directory("Is a directory"));
+    }
+
+    tauri::async_runtime::spawn_blocking(move || -> Result<StreamSnapshot, PeekError> {
+        // Opening a FIFO blocks until there is a writer, so even the open
+        // happens on the reader thread
+        let (tx, rx) = std::sync::mpsc::channel::<Result<Vec<u8>, PeekError>>();
+        std::thread::spawn(move || {
+            let mut file = match std::fs::File::open(&file_path) {
+                Ok(file) => file,
+                Err(e) => {
+                    let _ = tx.send(Err(e.into()));
+                    return;
+                }
+            };
+            let mut chunk = vec![0u8; 64 * 1024];
+            loop {
+                match file.read(&mut chunk) {
+                    Ok(n) => {
+                        // The receiver is gone once it has enough or gave up
+                        if tx.send(Ok(chunk[..n].to_vec())).is_err() || n == 0 {
+                            return;
+                        }
+                    }
+                    Err(e) if e.kind() == std::io::ErrorKind::Interrupted => {}
+                    Err(e) => {
+                        let _ = tx.send(Err(e.into()));
+                        return;
+                    }
+                }
+            }
         });
+
+        let mut bytes = Vec::new();
+        let (more_available, timed_out) = loop {
+            let wait = deadline.saturating_duration_since(std::time::Instant::now());
+            match rx.recv_timeout(wait) {
+                Ok(Ok(chunk)) if chunk.is_empty() => break (false, false),
+                Ok(Ok(chunk)) => {
+                    bytes.extend_from_slice(&chunk);
+                    if bytes.len() >= max_bytes {
+                        break (true, false);
+                    }
+                }
+                Ok(Err(e)) => return Err(e),
+                Err(std::sync::mpsc::RecvTimeoutError::Timeout) => break (true, true),
+                Err(std::sync::mpsc::RecvTimeoutError::Disconnected) => break (false, false),
+            }
+        };
+        bytes.truncate(max_bytes);
+        let complete = bytes.len() - incomplete_utf8_tail(&bytes);
+        Ok(StreamSnapshot {
+            content: String::from_utf8_lossy(&bytes[..complete]).to_string(),
+            bytes_read: bytes.len(),
+            more_available,
+            timed_out,
+        })
+    })
+    .await?
+}
+
+/// Read `line` with up to `before` lines above and `after` below, for
+/// go-to-line and error-location jumps. `line` is clamped to the file.
+#[tauri::command]
+fn read_line_context(
+    path: String,
+    line: usize,
+    before: usize,
+    after: usize,
+    state: tauri::State<'_, LineIndexCache>,
+) -> Result<LineContext, PeekError> {
+    let path = index_key(path, &state)?;
+    let mut cache = state.0.lock()?;
+    let index = cache.get(&path).ok_or_else(PeekError::not_indexed_yet)?;
+    line_context(&path, index, line, before, after)
+}
+
+fn line_context(path: &str, index: &LineIndex, line: usize, before: usize, after: usize) -> Result<LineContext, PeekError> {
+    let total_lines = index.offsets.len();
+    let line = line.min(total_lines.saturating_sub(1));
+    let first = line.saturating_sub(before);
+    let last = line.saturating_add(after).min(total_lines.saturating_sub(1));
+    let range = read_indexed_lines(path, index, first, last - first + 1)?;
+
+    let lines = range.content
+        .split_inclusive('\n')
+        .map(|text| text.strip_suffix('\n').unwrap_or(text))
+        .chain(std::iter::once("")) // a trailing empty last line has no text to split
+        .take(range.lines_read)
+        .enumerate()
+        .map(|(i, text)| ContextLine { line_number: range.start_line + i, text: text.to_string() })
+        .collect();
+
+    Ok(LineContext {
+        line,
+        lines,
+        before: line - range.start_line,
+        after: (range.start_line + range.lines_read).saturating_sub(line + 1),
+        total_lines,
+    })
+}
+
+/// A line-range edit resolved against the index: bytes `start_byte..end_byte`
+/// of the file (lines `start_line..end_line` of `total_lines`) become
+/// `new_bytes`, already in the file's line ending and encoding.
+struct LineEdit {
+    start_byte: u64,
+    end_byte: u64,
+    new_bytes: Vec<u8>,
+    start_line: usize,
+    end_line: usize,
+    total_lines: usize,
+}
+
+/// Work out what replacing `original_line_count` lines from `start_line` with
+/// `new_content` means in bytes, without touching the file.
+fn resolve_line_edit(
+    path: &str,
+    start_line: usize,
+    original_line_count: usize,
+    new_content: &str,
+    state: &LineIndexCache,
+) -> Result<LineEdit, PeekError> {
+    // Look up byte range from line index
+    let (start_byte, mut end_byte, crlf, file_encoding, s, e, total) = {
+        let mut cache = state.0.lock()?;
+        let index = cache.get(path).ok_or_else(PeekError::not_indexed_yet)?;
+        if index.decompressed_size.is_some() {
+            return Err("Compressed files can't be patched in place; save the whole file instead".into());
+        }
+        if index.buffer.is_some() {
+            return Err("Unsaved buffers can't be patched; index the new content instead".into());
+        }
+        // Offsets into a file that has since changed would splice the wrong bytes
+        let metadata = fs::metadata(path).at(path)?;
+        if metadata.len() != index.file_size || unix_mtime(&metadata) != index.mtime {
+            return Err(PeekError::stale_index("File changed on disk since it was indexed. Re-index it first."));
+        }
+        let offsets = &index.offsets;
+        let total = offsets.len();
+        let s = start_line.min(total.saturating_sub(1));
+        let e = (s + original_line_count).min(total);
+        let sb = offsets[s];
+        let eb = if e < total { offsets[e] } else { metadata.len() };
+        (sb, eb, index.crlf, index.encoding, s, e, total)
+    }; // Drop the lock before doing file I/O
+
+    let mut new_bytes = encoding::encode(&to_file_line_endings(new_content, crlf), file_encoding)?;
+
+    // `read_lines` leaves the BOM out of line 0, so an edit there keeps it
+    if start_byte == 0 {
+        let mut head = Vec::new();
+        std::fs::File::open(path)
+            .at(path)?
+            .take(3)
+            .read_to_end(&mut head)?;
+        if let Some((bom_encoding, bom_len)) = encoding_rs::Encoding::for_bom(&head) {
+            if bom_encoding == file_encoding {
+                new_bytes.splice(0..0, head[..bom_len].iter().copied());
+                end_byte = end_byte.max(bom_len as u64);
+            }
+        }
+    }
+
+    Ok(LineEdit { start_byte, end_byte, new_bytes, start_line: s, end_line: e, total_lines: total })
+}
+
+/// The result of an edit as `patch_file_lines` would write it, read around
+/// the edit instead of written.
+fn preview_line_edit(path: &str, edit: &LineEdit, state: &LineIndexCache) -> Result<PatchPreview, PeekError> {
+    let mut cache = state.0.lock()?;
+    let index = cache.get(path).ok_or_else(PeekError::not_indexed_yet)?;
+    let first = edit.start_line.saturating_sub(PATCH_PREVIEW_CONTEXT);
+    let last = edit.end_line + PATCH_PREVIEW_CONTEXT;
+    let context_end = if last < index.offsets.len() { index.offsets[last] } else { index.file_size };
+
+    let mut bytes = read_indexed_bytes(path, index, index.offsets[first], edit.start_byte)?;
+    bytes.extend_from_slice(&edit.new_bytes);
+    bytes.extend(read_indexed_bytes(path, index, edit.end_byte, context_end.max(edit.end_byte))?);
+
+    // Same arithmetic as `splice_line_offsets`
+    let replaced_lines = (edit.end_line + 1).min(edit.total_lines) - edit.start_line;
+    let new_lines = 1 + memchr::memchr_iter(b'\n', &edit.new_bytes).count();
+    Ok(PatchPreview {
+        total_lines: edit.total_lines - replaced_lines + new_lines,
+        file_size: index.file_size - (edit.end_byte - edit.start_byte) + edit.new_bytes.len() as u64,
+        start_line: first,
+        content: strip_crlf(encoding::decode(&bytes, index.encoding, index.offsets[first] == 0)),
+    })
+}
+
+/// Replace `original_line_count` indexed lines from `start_line` with
+/// `new_content` by streaming the file through a temp copy, then update the
+/// cached index (see `patch_file_lines`).
+fn patch_lines(
+    path: String,
+    start_line: usize,
+    original_line_count: usize,
+    new_content: &str,
+    state: &LineIndexCache,
+    locks: &lock::FileLocks,
+) -> Result<IndexResponse, PeekError> {
+    let LineEdit { start_byte, end_byte, new_bytes: new_content, start_line: s, end_line: e, total_lines: total } =
+        resolve_line_edit(&path, start_line, original_line_count, new_content, state)?;
+    let file_path = Path::new(&path);
+    let temp_path = file_path.with_extension("tmp");
+    let mut source = std::fs::File::open(file_path).at(file_path)?;
+
+    // The temp file is a full copy with the edit applied
+    let file_size = fs::metadata(file_path).at(file_path)?.len();
+    ensure_disk_space(file_path, file_size.saturating_sub(end_byte - start_byte) + new_content.len() as u64)?;
+
+    let mut dest = std::fs::File::create(&temp_path).at(&temp_path)?;
+
+    // 1. Copy bytes before the edit region
+    if start_byte > 0 {
+        let mut buf = [0u8; 8192];
+        let mut remaining = start_byte;
+        while remaining > 0 {
+            let to_read = std::cmp::min(remaining, 8192) as usize;
+            let n = source.read(&mut buf[..to_read])?;
+            if n == 0 { break; }
+            dest.write_all(&buf[..n])?;
+            remaining -= n as u64;
+        }
+    }
+
+    // 2. Write new content
+    dest.write_all(&new_content)?;
+
+    // 3. Skip old content, copy rest
+    source.seek(SeekFrom::Start(end_byte))?;
+    std::io::copy(&mut source, &mut dest)?;
+
+    // 4. Atomic replace
+    drop(source);
+    locks.replace_file(&temp_path, &path)?;
+
+    // 5. Update the index in place, falling back to a full re-index if it
+    //    no longer describes the file we just patched
+    let metadata = fs::metadata(file_path).at(file_path)?;
+    {
+        let mut cache = state.0.lock()?;
+        if let Some(index) = cache.entries.get_mut(&path) {
+            let expected_size = (index.file_size + new_content.len() as u64).checked_sub(end_byte - start_byte);
+            if index.offsets.len() == total && expected_size == Some(metadata.len()) {
+                splice_line_offsets(&mut index.offsets, s, e, end_byte, &new_content);
+                index.file_size = metadata.len();
+                index.mtime = unix_mtime(&metadata);
+                index.fingerprint = quick_fingerprint(file_path, index.file_size)?;
+                let response = index.response(true);
+                cache.evict(Some(&path));
+                return Ok(response);
+            }
+        }
+    }
+    index_and_cache(path, false, state)
+}
+
+/// Bytes of line `line` up to `end` (the line's end if `None`), without the
+/// BOM at the start of the file, and where they start.
+fn indexed_line_bytes(path: &str, index: &LineIndex, line: usize, end: Option<u64>) -> Result<(Vec<u8>, u64), PeekError> {
+    let line_start = index.offsets[line];
+    let line_end = match end {
+        Some(end) => end,
+        None if line + 1 < index.offsets.len() => index.offsets[line + 1],
+        None => indexed_content_size(path, index)?,
+    };
+    let bytes = read_indexed_bytes(path, index, line_start, line_end)?;
+    let bom_len = match encoding_rs::Encoding::for_bom(&bytes) {
+        Some((bom_encoding, bom_len)) if line_start == 0 && bom_encoding == index.encoding => bom_len,
+        _ => 0,
+    };
+    Ok((bytes[bom_len..].to_vec(), line_start + bom_len as u64))
+}
+
+/// Byte offset of `line` and `column` (Unicode scalar values), clamped as
+/// `position_to_byte` describes.
+fn position_byte(path: &str, index: &LineIndex, line: usize, column: usize) -> Result<u64, PeekError> {
+    let line = line.min(index.offsets.len().saturating_sub(1));
+    let (bytes, text_start) = indexed_line_bytes(path, index, line, None)?;
+
+    let text = encoding::decode(&bytes, index.encoding, false);
+    let text = text.strip_suffix('\n').unwrap_or(&text);
+    let text = text.strip_suffix('\r').unwrap_or(text);
+    let prefix: String = text.chars().take(column).collect();
+    Ok(text_start + encoding::encode(&prefix, index.encoding)?.len() as u64)
+}
+
+/// Line and column of a byte offset in an indexed file (for `.gz`, in the
+/// decompressed stream). An offset inside a multi-byte character is that
+/// character's column; one past the end is clamped to it.
+#[tauri::command]
+fn byte_to_position(path: String, byte_offset: u64, state: tauri::State<'_, LineIndexCache>) -> Result<Position, PeekError> {
+    let path = index_key(path, &state)?;
+    let mut cache = state.0.lock()?;
+    let index = cache.get(&path).ok_or_else(PeekError::not_indexed_yet)?;
+    byte_position(&path, index, byte_offset)
+}
+
+fn byte_position(path: &str, index: &LineIndex, byte_offset: u64) -> Result<Position, PeekError> {
+    let byte_offset = byte_offset.min(indexed_content_size(path, index)?);
+    let line = index.offsets.partition_point(|&start| start <= byte_offset).saturating_sub(1);
+    let (bytes, _) = indexed_line_bytes(path, index, line, Some(byte_offset))?;
+
+    // Not the last chunk, so a character cut off by the offset isn't counted
+    let mut decoder = index.encoding.new_decoder_without_bom_handling();
+    let mut prefix = String::with_capacity(decoder.max_utf8_buffer_length(bytes.len()).unwrap_or(bytes.len()));
+    let _ = decoder.decode_to_string(&bytes, &mut prefix, false);
+    Ok(Position { line, column: prefix.chars().count() })
+}
+
+/// Open a file at a byte offset, as tools that report errors by offset need:
+/// the line and column holding it (see `byte_to_position`) and up to
+/// `context` lines (default 3) around that line, indexing the file first if
+/// it isn't. For `.gz` files the offset is into the decompressed text.
+#[tauri::command]
+async fn jump_to_byte(
+    path: String,
+    byte_offset: u64,
+    context: Option<usize>,
+    app: tauri::AppHandle,
+) -> Result<ByteJump, PeekError> {
+    let path = index_key(path, &app.state::<LineIndexCache>())?;
+    let context = context.unwrap_or(DEFAULT_JUMP_CONTEXT);
+    tauri::async_runtime::spawn_blocking(move || -> Result<ByteJump, PeekError> {
+        let state = app.state::<LineIndexCache>();
+        if state.0.lock()?.get(&path).is_none() {
+            let gzip = is_gzip_path(Path::new(&path));
+            index_and_cache(path.clone(), gzip, &state)?;
+        }
+        let mut cache = state.0.lock()?;
+        let index = cache.get(&path).ok_or_else(PeekError::not_indexed_yet)?;
+        let position = byte_position(&path, index, byte_offset)?;
+        let context = line_context(&path, index, position.line, context, context)?;
+        Ok(ByteJump { line: position.line, column: position.column, context })
+    })
+    .await?
+}
+
+/// Byte offset of a line and column in an indexed file, the inverse of
+/// `byte_to_position`. `line` is clamped to the file and `column` (Unicode
+/// scalar values) to the end of the line, before its line break.
+#[tauri::command]
+fn position_to_byte(
+    path: String,
+    line: usize,
+    column: usize,
+    state: tauri::State<'_, LineIndexCache>,
+) -> Result<u64, PeekError> {
+    let path = index_key(path, &state)?;
+    let mut cache = state.0.lock()?;
+    let index = cache.get(&path).ok_or_else(PeekError::not_indexed_yet)?;
+    position_byte(&path, index, line, column)
+}
+
+/// Line, character and byte counts of a selection in an indexed file, for
+/// the status bar, without the frontend holding the selected text. Columns
+/// are Unicode scalar values and are clamped like `position_to_byte`; the
+/// ends may come in either order. Only the edge lines are decoded to place
+/// the columns; the range between is streamed to count characters.
+#[tauri::command]
+fn selection_stats(
+    path: String,
+    start_line: usize,
+    start_col: usize,
+    end_line: usize,
+    end_col: usize,
+    state: tauri::State<'_, LineIndexCache>,
+) -> Result<SelectionStats, PeekError> {
+    let path = index_key(path, &state)?;
+    let (mut start, mut end) = ((start_line, start_col), (end_line, end_col));
+    if end < start {
+        std::mem::swap(&mut start, &mut end);
+    }
+    let (start_byte, end_byte, last_line, text_encoding, gzip, buffered) = {
+        let mut cache = state.0.lock()?;
+        let index = cache.get(&path).ok_or_else(PeekError::not_indexed_yet)?;
+        let start_byte = position_byte(&path, index, start.0, start.1)?;
+        let end_byte = position_byte(&path, index, end.0, end.1)?.max(start_byte);
+        // An unsaved buffer has no file to stream from
+        let buffered = index.buffer.as_ref().map(|text| text.as_bytes()[start_byte as usize..end_byte as usize].to_vec());
+        let last_line = index.offsets.len().saturating_sub(1);
+        (start_byte, end_byte, last_line, index.encoding, index.decompressed_size.is_some(), buffered)
+    }; // Drop the lock before streaming the range
+    if start_byte == end_byte {
+        return Ok(SelectionStats { lines: 0, chars: 0, bytes: 0, start_byte, end_byte });
+    }
+
+    let range = end_byte - start_byte;
+    let mut reader: Box<dyn Read> = match buffered {
+        Some(bytes) => Box::new(std::io::Cursor::new(bytes)),
+        None if gzip => {
+            let mut decoder = open_gzip(&paths::resolve_path(&path))?;
+            std::io::copy(&mut (&mut decoder).take(start_byte), &mut std::io::sink())?;
+            Box::new(decoder.take(range))
+        }
+        None => {
+            let mut file = std::fs::File::open(paths::resolve_path(&path))?;
+            file.seek(SeekFrom::Start(start_byte))?;
+            Box::new(file.take(range))
+        }
+    };
+
+    let mut decoder = text_encoding.new_decoder_without_bom_handling();
+    let mut chunk = vec![0u8; SELECTION_CHUNK_BYTES];
+    let mut text = String::new();
+    let mut chars = 0;
+    let mut after_cr = false;
+    loop {
+        let n = reader.read(&mut chunk)?;
+        text.clear();
+        text.reserve(decoder.max_utf8_buffer_length(n).unwrap_or(n));
+        let _ = decoder.decode_to_string(&chunk[..n], &mut text, n == 0);
+        for ch in text.chars() {
+            // CRLF is one line break
+            if !(after_cr && ch == '\n') {
+                chars += 1;
+            }
+            after_cr = ch == '\r';
+        }
+        if n == 0 {
+            break;
+        }
+    }
+
+    Ok(SelectionStats {
+        lines: end.0.min(last_line) - start.0.min(last_line) + 1,
+        chars,
+        bytes: end_byte - start_byte,
+        start_byte,
+        end_byte,
+    })
+}
+
+/// Patch a file at a specific line range. Replaces `original_line_count` lines 
+/// starting at `start_line` with `new_content`. Returns new total line count.
+/// `new_content` uses `\n`; it is written back with the file's own line ending
+/// and encoding. Fails with `STALE_INDEX` if the file changed since indexing.
+/// With `dry_run`, nothing is written and a `PatchPreview` of the result comes
+/// back instead.
+#[tauri::command]
+fn patch_file_lines(
+    path: String,
+    start_line: usize,
+    original_line_count: usize,
+    new_content: String,
+    dry_run: Option<bool>,
+    state: tauri::State<'_, LineIndexCache>,
+    locks: tauri::State<'_, lock::FileLocks>,
+) -> Result<PatchResponse, PeekError> {
+    let path = paths::resolve(&path);
+    if dry_run.unwrap_or(false) {
+        let edit = resolve_line_edit(&path, start_line, original_line_count, &new_content, &state)?;
+        return Ok(PatchResponse::Preview(preview_line_edit(&path, &edit, &state)?));
+    }
+    Ok(PatchResponse::Applied(patch_lines(path, start_line, original_line_count, &new_content, &state, &locks)?))
+}
+
+/// Lines `first..first + count` of an indexed file as `read_lines` returns
+/// them, with the index's current state for edits that turn out to be no-ops.
+fn read_edit_range(
+    path: &str,
+    first: usize,
+    count: usize,
+    state: &LineIndexCache,
+) -> Result<(LinesResponse, IndexResponse), PeekError> {
+    let mut cache = state.0.lock()?;
+    let index = cache.get(path).ok_or_else(PeekError::not_indexed_yet)?;
+    Ok((read_indexed_lines(path, index, first, count)?, index.response(false)))
+}
+
+/// The lines of a `read_lines` range and whether it ended with a line break
+/// (every range but one reaching the end of a file without a final newline).
+fn split_range_lines(content: &str) -> (Vec<&str>, bool) {
+    match content.strip_suffix('\n') {
+        Some(body) => (body.split('\n').collect(), true),
+        None => (content.split('\n').collect(), false),
+    }
+}
+
+/// Inverse of `split_range_lines`.
+fn join_range_lines<S: AsRef<str>>(lines: &[S], trailing_newline: bool) -> String {
+    let mut text = lines.iter().map(AsRef::as_ref).collect::<Vec<_>>().join("\n");
+    if trailing_newline {
+        text.push('\n');
+    }
+    text
+}
+
+/// Comment out every non-blank line of `text` (as `read_lines` returns it)
+/// with `token` at their shallowest indentation, or, if they all already
+/// start with `token`, remove it (and one space after it) from each.
+fn toggle_comments(text: &str, token: &str) -> String {
+    let is_blank = |line: &str| line.trim().is_empty();
+    // Only ASCII indentation, so every line's indent is a valid byte column
+    let indent_len = |line: &str| line.len() - line.trim_start_matches([' ', '\t']).len();
+    let lines: Vec<&str> = text.split('\n').collect();
+    let code: Vec<&str> = lines.iter().copied().filter(|line| !is_blank(line)).collect();
+    let uncomment = !code.is_empty() && code.iter().all(|line| line[indent_len(line)..].starts_with(token));
+    let column = code.iter().map(|line| indent_len(line)).min().unwrap_or(0);
+
+    lines
+        .iter()
+        .map(|&line| {
+            if is_blank(line) {
+                line.to_string()
+            } else if uncomment {
+                let indent = indent_len(line);
+                let rest = &line[indent + token.len()..];
+                format!("{}{}", &line[..indent], rest.strip_prefix(' ').unwrap_or(rest))
+            } else {
+                format!("{}{} {}", &line[..column], token, &line[column..])
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Toggle line comments on lines `start_line..=end_line` of an indexed file:
+/// if every non-blank line already starts with `comment_token` (`//`, `#`,
+/// `--`, ...) it's removed, otherwise all of them get one, lined up at the
+/// shallowest indentation. Blank lines are left as they are. Written back
+/// like `patch_file_lines`.
+#[tauri::command]
+fn toggle_line_comment(
+    path: String,
+    start_line: usize,
+    end_line: usize,
+    comment_token: String,
+    state: tauri::State<'_, LineIndexCache>,
+    locks: tauri::State<'_, lock::FileLocks>,
+) -> Result<IndexResponse, PeekError> {
+    let path = paths::resolve(&path);
+    let token = comment_token.trim();
+    if token.is_empty() {
+        return Err("Comment token is empty".into());
     }
+    let first = start_line.min(end_line);
+    let (current, unchanged) = read_edit_range(&path, first, start_line.abs_diff(end_line) + 1, &state)?;
 
-    // Standard small file
-    file.seek(SeekFrom::Start(0)).map_err(|e| e.to_string())?;
-    let mut full_content = String::new();
-    file.read_to_string(&mut full_content).map_err(|_| "Failed to read text content".to_string())?;
-
-    Ok(FileResponse {
-        content: full_content,
-        file_name,
-        extension,
-        size,
-        mtime,
-        is_binary: false,
-        is_large_file: false,
-    })
+    let toggled = toggle_comments(&current.content, token);
+    if toggled == current.content {
+        return Ok(unchanged); // only blank lines
+    }
+    patch_lines(path, current.start_line, current.lines_read, &toggled, &state, &locks)
 }
 
-/// Scan a file and build an index of byte offsets for each line start.
-/// Returns total number of lines and file size.
+/// Move lines `start_line..=end_line` of an indexed file one line `"up"` or
+/// `"down"`, swapping them with the line they pass over, and return where the
+/// block is now so the selection can follow. At the top or bottom of the file
+/// (the empty line after a final newline doesn't count) nothing is written
+/// and `moved` is false. Written back like `patch_file_lines`.
 #[tauri::command]
-fn index_file(path: String, state: tauri::State<'_, LineIndexCache>) -> Result<IndexResponse, String> {
-    let file = std::fs::File::open(&path).map_err(|e| e.to_string())?;
-    let metadata = fs::metadata(&path).map_err(|e| e.to_string())?;
-    let file_size = metadata.len();
-    let reader = BufReader::with_capacity(64 * 1024, file); // 64KB buffer for fast scanning
+fn move_lines(
+    path: String,
+    start_line: usize,
+    end_line: usize,
+    direction: String,
+    state: tauri::State<'_, LineIndexCache>,
+    locks: tauri::State<'_, lock::FileLocks>,
+) -> Result<MoveLinesResponse, PeekError> {
+    let path = paths::resolve(&path);
+    let up = match direction.as_str() {
+        "up" => true,
+        "down" => false,
+        other => return Err(format!("Unknown direction: {} (expected \"up\" or \"down\")", other).into()),
+    };
+    let (total_lines, movable_lines) = {
+        let mut cache = state.0.lock()?;
+        let index = cache.get(&path).ok_or_else(PeekError::not_indexed_yet)?;
+        let total = index.offsets.len();
+        (total, total - usize::from(ends_with_newline(&index.offsets, index.content_size())))
+    };
+    let last = start_line.max(end_line).min(movable_lines.saturating_sub(1));
+    let first = start_line.min(end_line).min(last);
+    let unmoved = MoveLinesResponse { moved: false, start_line: first, end_line: last, total_lines };
+    if (up && first == 0) || (!up && last + 1 >= movable_lines) {
+        return Ok(unmoved);
+    }
 
-    // Build offsets: the byte position where each line starts
-    let mut offsets: Vec<u64> = Vec::new();
-    offsets.push(0); // Line 0 starts at byte 0
+    // The block plus the line it swaps with
+    let range_start = if up { first - 1 } else { first };
+    let (current, _) = read_edit_range(&path, range_start, last - first + 2, &state)?;
+    let (mut lines, trailing_newline) = split_range_lines(&current.content);
+    if lines.len() != last - first + 2 {
+        return Ok(unmoved);
+    }
+    if up {
+        lines.rotate_left(1);
+    } else {
+        lines.rotate_right(1);
+    }
+    let moved = join_range_lines(&lines, trailing_newline);
+    let response = patch_lines(path, range_start, current.lines_read, &moved, &state, &locks)?;
+    let (start_line, end_line) = if up { (first - 1, last - 1) } else { (first + 1, last + 1) };
+    Ok(MoveLinesResponse { moved: true, start_line, end_line, total_lines: response.total_lines })
+}
 
-    let mut byte_pos: u64 = 0;
-    for line_result in reader.split(b'\n') {
-        let line_bytes = line_result.map_err(|e| e.to_string())?;
-        byte_pos += line_bytes.len() as u64 + 1; // +1 for the \n delimiter
-        if byte_pos <= file_size {
-            offsets.push(byte_pos);
+/// The number a line starts with (after leading whitespace), for numeric
+/// sorts: an optional sign, digits and a fractional part, like `sort -n`.
+fn leading_number(line: &str) -> Option<f64> {
+    let text = line.trim_start();
+    let mut end = 0;
+    let mut seen_digit = false;
+    let mut seen_point = false;
+    for (i, c) in text.char_indices() {
+        match c {
+            '-' | '+' if i == 0 => {}
+            '0'..='9' => seen_digit = true,
+            '.' if !seen_point => seen_point = true,
+            _ => break,
         }
+        end = i + c.len_utf8();
     }
+    if !seen_digit {
+        return None;
+    }
+    text[..end].trim_end_matches('.').parse().ok()
+}
 
-    let total_lines = offsets.len();
-
-    // Cache the index
-    let mut cache = state.0.lock().map_err(|e| e.to_string())?;
-    cache.insert(path, offsets);
+/// Sort lines `start_line..=end_line` of an indexed file and write them back
+/// like `patch_file_lines`. `numeric` orders by each line's leading number
+/// (lines without one first, equal numbers by text), otherwise text order,
+/// ignoring case unless `case_sensitive`, as in `search_file`; ties keep their
+/// original order. `unique` keeps only the first of lines with the same text,
+/// compared the same way, even when sorting by number. Whether the range ends
+/// with a line break is kept. Returns the updated index.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+fn sort_lines(
+    path: String,
+    start_line: usize,
+    end_line: usize,
+    numeric: Option<bool>,
+    reverse: Option<bool>,
+    unique: Option<bool>,
+    case_sensitive: Option<bool>,
+    state: tauri::State<'_, LineIndexCache>,
+    locks: tauri::State<'_, lock::FileLocks>,
+) -> Result<IndexResponse, PeekError> {
+    let path = paths::resolve(&path);
+    let numeric = numeric.unwrap_or(false);
+    let case_sensitive = case_sensitive.unwrap_or(false);
+    let first = start_line.min(end_line);
+    let (current, unchanged) = read_edit_range(&path, first, start_line.abs_diff(end_line) + 1, &state)?;
+    let (lines, trailing_newline) = split_range_lines(&current.content);
 
-    let mtime = metadata.modified()
-        .map(|t| t.duration_since(std::time::UNIX_EPOCH).unwrap_or_default().as_secs())
-        .unwrap_or(0);
+    // Keys are computed once per line rather than on every comparison
+    let mut keyed: Vec<(Option<f64>, String, &str)> = lines
+        .into_iter()
+        .map(|line| {
+            let number = if numeric { leading_number(line) } else { None };
+            let text = if case_sensitive { line.to_string() } else { line.to_lowercase() };
+            (number, text, line)
+        })
+        .collect();
+    keyed.sort_by(|(a_number, a_text, _), (b_number, b_text, _)| {
+        let by_number = match (a_number, b_number) {
+            (Some(x), Some(y)) => x.total_cmp(y),
+            (x, y) => x.is_some().cmp(&y.is_some()),
+        };
+        let order = by_number.then_with(|| a_text.cmp(b_text));
+        if reverse.unwrap_or(false) { order.reverse() } else { order }
+    });
+    if unique.unwrap_or(false) {
+        let mut seen = HashSet::new();
+        keyed.retain(|(_, text, _)| seen.insert(text.clone()));
+    }
+    let lines: Vec<&str> = keyed.into_iter().map(|(_, _, line)| line).collect();
 
-    Ok(IndexResponse { total_lines, file_size, mtime })
+    let sorted = join_range_lines(&lines, trailing_newline);
+    if sorted == current.content {
+        return Ok(unchanged);
+    }
+    patch_lines(path, current.start_line, current.lines_read, &sorted, &state, &locks)
 }
 
-/// Read a range of lines from an indexed file.
+/// Join lines `start_line..=end_line` of an indexed file into one, with
+/// `separator` (a space by default) between them and the leading whitespace
+/// of every line after the first removed. Written back like
+/// `patch_file_lines`; returns the updated index.
 #[tauri::command]
-fn read_lines(
+fn join_lines(
     path: String,
     start_line: usize,
-    line_count: usize,
+    end_line: usize,
+    separator: Option<String>,
     state: tauri::State<'_, LineIndexCache>,
-) -> Result<LinesResponse, String> {
-    let cache = state.0.lock().map_err(|e| e.to_string())?;
-    let offsets = cache.get(&path).ok_or("File not indexed. Call index_file first.")?;
-    
-    let total_lines = offsets.len();
-    let safe_start = start_line.min(total_lines.saturating_sub(1));
-    let safe_end = (safe_start + line_count).min(total_lines);
-    let actual_count = safe_end - safe_start;
-
-    if actual_count == 0 {
-        return Ok(LinesResponse {
-            content: String::new(),
-            start_line: safe_start,
-            lines_read: 0,
-        });
+    locks: tauri::State<'_, lock::FileLocks>,
+) -> Result<IndexResponse, PeekError> {
+    let path = paths::resolve(&path);
+    let separator = separator.unwrap_or_else(|| " ".to_string());
+    let first = start_line.min(end_line);
+    let (current, unchanged) = read_edit_range(&path, first, start_line.abs_diff(end_line) + 1, &state)?;
+    let (lines, trailing_newline) = split_range_lines(&current.content);
+    if lines.len() < 2 {
+        return Ok(unchanged);
     }
 
-    let start_byte = offsets[safe_start];
-    // End byte: if safe_end < total_lines, use that offset; otherwise read to EOF
-    let end_byte = if safe_end < total_lines {
-        offsets[safe_end]
-    } else {
-        // Read to end of file
-        let metadata = fs::metadata(&path).map_err(|e| e.to_string())?;
-        metadata.len()
-    };
+    let mut joined = lines[0].to_string();
+    for line in &lines[1..] {
+        joined.push_str(&separator);
+        joined.push_str(line.trim_start());
+    }
+    let joined = join_range_lines(&[joined], trailing_newline);
+    patch_lines(path, current.start_line, current.lines_read, &joined, &state, &locks)
+}
 
-    let read_length = (end_byte - start_byte) as usize;
+/// Break line `line` of an indexed file at each of `at_columns` (Unicode
+/// scalar values from the start of the line). Columns at or past either end
+/// of the line are ignored. Written back like `patch_file_lines`; returns the
+/// updated index.
+#[tauri::command]
+fn split_line(
+    path: String,
+    line: usize,
+    at_columns: Vec<usize>,
+    state: tauri::State<'_, LineIndexCache>,
+    locks: tauri::State<'_, lock::FileLocks>,
+) -> Result<IndexResponse, PeekError> {
+    let path = paths::resolve(&path);
+    let (current, unchanged) = read_edit_range(&path, line, 1, &state)?;
+    let (lines, trailing_newline) = split_range_lines(&current.content);
+    let text = lines.first().copied().unwrap_or_default();
 
-    let mut file = std::fs::File::open(&path).map_err(|e| e.to_string())?;
-    file.seek(SeekFrom::Start(start_byte)).map_err(|e| e.to_string())?;
+    let mut columns = at_columns;
+    columns.sort_unstable();
+    columns.dedup();
+    let char_count = text.chars().count();
+    let mut cuts: Vec<usize> = text
+        .char_indices()
+        .enumerate()
+        .filter(|(column, _)| *column > 0 && *column < char_count && columns.binary_search(column).is_ok())
+        .map(|(_, (byte, _))| byte)
+        .collect();
+    if cuts.is_empty() {
+        return Ok(unchanged);
+    }
+    cuts.push(text.len());
 
-    let mut buffer = vec![0u8; read_length];
-    file.read_exact(&mut buffer).map_err(|e| e.to_string())?;
+    let mut pieces = Vec::with_capacity(cuts.len());
+    let mut from = 0;
+    for cut in cuts {
+        pieces.push(&text[from..cut]);
+        from = cut;
+    }
+    let split = join_range_lines(&pieces, trailing_newline);
+    patch_lines(path, current.start_line, current.lines_read, &split, &state, &locks)
+}
 
-    let content = String::from_utf8_lossy(&buffer).to_string();
+/// Replace every match of `query` (a literal, or a regex with `is_regex`;
+/// case-insensitive unless `case_sensitive`) within lines
+/// `start_line..=end_line` of an indexed file, for "replace in selection".
+/// The range is matched as one text with `\n` line breaks, so a regex can span
+/// lines (`^`/`$` match at each line) and `replacement` (with `$1`-style
+/// groups for regexes) may add or remove lines; text outside the range is
+/// never touched. Written back like `patch_file_lines`.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+fn replace_in_range(
+    path: String,
+    start_line: usize,
+    end_line: usize,
+    query: String,
+    replacement: String,
+    is_regex: Option<bool>,
+    case_sensitive: Option<bool>,
+    state: tauri::State<'_, LineIndexCache>,
+    locks: tauri::State<'_, lock::FileLocks>,
+) -> Result<ReplaceResponse, PeekError> {
+    let path = paths::resolve(&path);
+    let is_regex = is_regex.unwrap_or(false);
+    let re = search::build_regex(query, Some(is_regex), case_sensitive, true)?;
 
-    Ok(LinesResponse {
-        content,
-        start_line: safe_start,
-        lines_read: actual_count,
-    })
+    let first = start_line.min(end_line);
+    let (current, unchanged) = read_edit_range(&path, first, start_line.abs_diff(end_line) + 1, &state)?;
+    // The range's final line break belongs to the boundary, not the selection
+    let (body, trailing_newline) = match current.content.strip_suffix('\n') {
+        Some(body) => (body, true),
+        None => (current.content.as_str(), false),
+    };
+    let replacements = re.find_iter(body).count();
+    if replacements == 0 {
+        return Ok(ReplaceResponse { replacements: 0, total_lines: unchanged.total_lines });
+    }
+    let mut replaced = if is_regex {
+        re.replace_all(body, replacement.as_str()).into_owned()
+    } else {
+        re.replace_all(body, regex::NoExpand(&replacement)).into_owned()
+    };
+    if trailing_newline {
+        replaced.push('\n');
+    }
+    let response = patch_lines(path, current.start_line, current.lines_read, &replaced, &state, &locks)?;
+    Ok(ReplaceResponse { replacements, total_lines: response.total_lines })
 }
 
-/// Patch a file at a specific line range. Replaces `original_line_count` lines 
-/// starting at `start_line` with `new_content`. Returns new total line count.
+/// Rewrite every line ending in a file as `target` (`"lf"` or `"crlf"`), to
+/// fix files that mix them. The file is streamed into a temp file that
+/// atomically replaces it, and its cached line index (if any) is rebuilt.
+/// Lone `\r`s are not line endings here and are left alone. A file already
+/// consistent is not touched. Encodings whose line breaks aren't single
+/// `\n` bytes (UTF-16) fail with `UNSUPPORTED_ENCODING`, naming the encoding.
 #[tauri::command]
-fn patch_file_lines(
+fn normalize_line_endings(
     path: String,
-    start_line: usize,
-    original_line_count: usize,
-    new_content: String,
+    target: String,
     state: tauri::State<'_, LineIndexCache>,
-) -> Result<IndexResponse, String> {
-    // Look up byte range from line index
-    let (start_byte, end_byte) = {
-        let cache = state.0.lock().map_err(|e| e.to_string())?;
-        let offsets = cache.get(&path).ok_or("File not indexed")?;
-        let total = offsets.len();
-        let s = start_line.min(total.saturating_sub(1));
-        let e = (s + original_line_count).min(total);
-        let sb = offsets[s];
-        let eb = if e < total {
-            offsets[e]
-        } else {
-            fs::metadata(&path).map_err(|e| e.to_string())?.len()
-        };
-        (sb, eb)
-    }; // Drop the lock before doing file I/O
+    locks: tauri::State<'_, lock::FileLocks>,
+) -> Result<LineEndingsResponse, PeekError> {
+    let path = paths::resolve(&path);
+    let crlf = match target.to_lowercase().as_str() {
+        "lf" => false,
+        "crlf" => true,
+        _ => return Err(format!("Unknown line ending: {}", target).into()),
+    };
+    let resolved = paths::resolve_path(&path);
+    let file_path = resolved.as_path();
+    let metadata = regular_file_metadata(file_path).at(file_path)?;
+    if is_gzip_path(file_path) {
+        return Err("Compressed files can't be rewritten in place".into());
+    }
+    let mut sample = Vec::new();
+    std::fs::File::open(file_path)
+        .at(file_path)?
+        .take(encoding::SAMPLE_BYTES as u64)
+        .read_to_end(&mut sample)?;
+    let file_encoding = encoding::detect(&sample);
+    if !encoding::is_line_indexable(file_encoding) {
+        return Err(PeekError::unsupported_encoding(file_encoding.name()));
+    }
+    // Each LF may gain a CR
+    ensure_disk_space(file_path, metadata.len().saturating_mul(if crlf { 2 } else { 1 }))?;
 
-    let file_path = Path::new(&path);
     let temp_path = file_path.with_extension("tmp");
+    let mut reader = BufReader::new(std::fs::File::open(file_path).at(file_path)?);
+    let mut dest = std::io::BufWriter::new(std::fs::File::create(&temp_path).at(&temp_path)?);
+    let mut response = LineEndingsResponse { changed_lines: 0, total_lines: 1, line_ending: line_ending_name(crlf) };
+    let mut raw = Vec::new();
 
-    let mut source = std::fs::File::open(file_path).map_err(|e| e.to_string())?;
-    let mut dest = std::fs::File::create(&temp_path).map_err(|e| e.to_string())?;
-
-    // 1. Copy bytes before the edit region
-    if start_byte > 0 {
-        let mut buf = [0u8; 8192];
-        let mut remaining = start_byte;
-        while remaining > 0 {
-            let to_read = std::cmp::min(remaining, 8192) as usize;
-            let n = source.read(&mut buf[..to_read]).map_err(|e| e.to_string())?;
-            if n == 0 { break; }
-            dest.write_all(&buf[..n]).map_err(|e| e.to_string())?;
-            remaining -= n as u64;
+    let mut rewrite = || -> Result<(), PeekError> {
+        loop {
+            raw.clear();
+            if reader.read_until(b'\n', &mut raw)? == 0 {
+                return Ok(());
+            }
+            let Some(body) = raw.strip_suffix(b"\n") else {
+                // The last line, with no ending to change
+                return Ok(dest.write_all(&raw)?);
+            };
+            response.total_lines += 1;
+            let (body, had_cr) = match body.strip_suffix(b"\r") {
+                Some(body) => (body, true),
+                None => (body, false),
+            };
+            if had_cr != crlf {
+                response.changed_lines += 1;
+            }
+            dest.write_all(body)?;
+            dest.write_all(if crlf { b"\r\n" } else { b"\n" })?;
         }
+    };
+    let written = rewrite().and_then(|_| Ok(dest.into_inner().map_err(|e| e.into_error())?.sync_all()?));
+    if let Err(e) = written {
+        let _ = std::fs::remove_file(&temp_path);
+        return Err(e);
     }
+    if response.changed_lines == 0 {
+        let _ = std::fs::remove_file(&temp_path);
+        return Ok(response);
+    }
+    locks.replace_file(&temp_path, &path)?;
 
-    // 2. Write new content
-    dest.write_all(new_content.as_bytes()).map_err(|e| e.to_string())?;
+    let indexed = state.0.lock()?.entries.contains_key(&path);
+    if indexed {
+        index_and_cache(path, false, &state)?;
+    }
+    Ok(response)
+}
 
-    // 3. Skip old content, copy rest
-    source.seek(SeekFrom::Start(end_byte)).map_err(|e| e.to_string())?;
-    std::io::copy(&mut source, &mut dest).map_err(|e| e.to_string())?;
+/// `wc` for the status bar: lines, words, chars and bytes, streamed so it
+/// works on files too large to load. The line total follows the line index
+/// semantics (`newlines + 1`) and comes from the cache when it's fresh.
+#[tauri::command]
+fn file_stats(path: String, state: tauri::State<'_, LineIndexCache>) -> Result<FileStats, PeekError> {
+    let path = paths::resolve(&path);
+    let file_path = Path::new(&path);
+    let metadata = regular_file_metadata(file_path).at(file_path)?;
+    let mut file = std::fs::File::open(file_path).at(file_path)?;
 
-    // 4. Atomic replace
-    fs::rename(&temp_path, file_path).map_err(|e| e.to_string())?;
+    let mut buf = vec![0u8; 64 * 1024];
+    let mut pending: Vec<u8> = Vec::new();
+    let mut stats = FileStats { lines: 1, words: 0, chars: 0, bytes: 0 };
+    let mut in_word = false;
 
-    // 5. Re-index the file and return
-    drop(source);
-    // Re-index by calling the indexing logic directly
-    let file = std::fs::File::open(file_path).map_err(|e| e.to_string())?;
-    let file_size = fs::metadata(file_path).map_err(|e| e.to_string())?.len();
-    let reader = BufReader::with_capacity(64 * 1024, file);
+    loop {
+        let n = file.read(&mut buf)?;
+        let at_eof = n == 0;
+        stats.bytes += n as u64;
+        stats.lines += memchr::memchr_iter(b'\n', &buf[..n]).count();
+        pending.extend_from_slice(&buf[..n]);
 
-    let mut offsets: Vec<u64> = Vec::new();
-    offsets.push(0);
-    let mut byte_pos: u64 = 0;
-    for line_result in reader.split(b'\n') {
-        let line_bytes = line_result.map_err(|e| e.to_string())?;
-        byte_pos += line_bytes.len() as u64 + 1;
-        if byte_pos <= file_size {
-            offsets.push(byte_pos);
+        // Keep a split multi-byte character for the next chunk
+        let keep = if at_eof { 0 } else { incomplete_utf8_tail(&pending) };
+        let text = String::from_utf8_lossy(&pending[..pending.len() - keep]);
+        for ch in text.chars() {
+            stats.chars += 1;
+            if ch.is_whitespace() {
+                in_word = false;
+            } else if !in_word {
+                in_word = true;
+                stats.words += 1;
+            }
+        }
+        pending.drain(..pending.len() - keep);
+
+        if at_eof {
+            break;
         }
     }
 
-    let total_lines = offsets.len();
-    
-    // Re-stat for new mtime (Must do before moving path into cache)
-    let metadata = fs::metadata(file_path).map_err(|e| e.to_string())?;
-    let file_size = metadata.len();
-    let mtime = metadata.modified()
-        .map(|t| t.duration_since(std::time::UNIX_EPOCH).unwrap_or_default().as_secs())
-        .unwrap_or(0);
+    let mut cache = state.0.lock()?;
+    if let Some(index) = cache.get(&path) {
+        let fresh = index.decompressed_size.is_none()
+            && index.file_size == metadata.len()
+            && index.mtime == unix_mtime(&metadata);
+        if fresh {
+            stats.lines = index.offsets.len();
+        }
+    }
+
+    Ok(stats)
+}
+
+/// Free per-file backend state when the editor closes a tab: the cached line
+/// index, the file lock and any `watch_file` watch.
+#[tauri::command]
+fn close_file(
+    path: String,
+    state: tauri::State<'_, LineIndexCache>,
+    locks: tauri::State<'_, lock::FileLocks>,
+    file_watchers: tauri::State<'_, watch::FileWatchers>,
+) -> Result<(), PeekError> {
+    let path = index_key(path, &state)?;
+    file_watchers.remove(&path)?;
+    let mut cache = state.0.lock()?;
+    cache.entries.remove(&path);
+    locks.release(&path)
+}
+
+/// Drop every cached line index. Subsequent `read_lines` calls need a re-index.
+#[tauri::command]
+fn clear_index_cache(state: tauri::State<'_, LineIndexCache>) -> Result<(), PeekError> {
+    let mut cache = state.0.lock()?;
+    cache.entries.clear();
+    Ok(())
+}
+
+/// Set the approximate memory budget for cached line indexes, evicting now if needed.
+#[tauri::command]
+fn set_index_cache_budget(budget_bytes: usize, state: tauri::State<'_, LineIndexCache>) -> Result<(), PeekError> {
+    let mut cache = state.0.lock()?;
+    cache.budget_bytes = budget_bytes;
+    cache.evict(None);
+    Ok(())
+}
 
-    let mut cache = state.0.lock().map_err(|e| e.to_string())?;
-    cache.insert(path, offsets);
+#[tauri::command]
+fn cache_stats(state: tauri::State<'_, LineIndexCache>) -> Result<CacheStats, PeekError> {
+    let cache = state.0.lock()?;
+    Ok(CacheStats {
+        entries: cache.entries.len(),
+        bytes_used: cache.bytes_used(),
+        budget_bytes: cache.budget_bytes,
+    })
+}
 
-    Ok(IndexResponse { total_lines, file_size, mtime })
+/// Backend resource use for a diagnostics panel: cached line indexes and
+/// their memory, live terminals, active watches (quick-open's included) and
+/// the process's resident memory.
+#[tauri::command]
+fn get_backend_stats(
+    cache: tauri::State<'_, LineIndexCache>,
+    terminals: tauri::State<'_, terminal::PtyState>,
+    dir_watchers: tauri::State<'_, watch::DirWatchers>,
+    file_watchers: tauri::State<'_, watch::FileWatchers>,
+    file_lists: tauri::State<'_, quickopen::FileLists>,
+) -> Result<BackendStats, PeekError> {
+    let (indexed_files, index_bytes) = {
+        let cache = cache.0.lock()?;
+        (cache.entries.len(), cache.bytes_used())
+    };
+    Ok(BackendStats {
+        indexed_files,
+        index_bytes,
+        terminals: terminals.count(),
+        dir_watchers: dir_watchers.count(),
+        file_watchers: file_watchers.count(),
+        quick_open_watchers: file_lists.count(),
+        rss_bytes: process_rss(),
+    })
 }
 
 // Keep old read_file_chunk for backwards compat (used by initial load)
 #[tauri::command]
-fn read_file_chunk(path: String, offset: u64, length: usize) -> Result<ChunkResponse, String> {
-    let mut file = std::fs::File::open(&path).map_err(|e| e.to_string())?;
-    file.seek(SeekFrom::Start(offset)).map_err(|e| e.to_string())?;
+fn read_file_chunk(path: String, offset: u64, length: usize) -> Result<ChunkResponse, PeekError> {
+    let path = paths::resolve(&path);
+    let mut file = std::fs::File::open(&path).at(&path)?;
+    file.seek(SeekFrom::Start(offset))?;
     let mut buffer = vec![0; length];
-    let read_bytes = file.read(&mut buffer).map_err(|e| e.to_string())?;
+    let read_bytes = file.read(&mut buffer)?;
     Ok(ChunkResponse {
         content: String::from_utf8_lossy(&buffer[..read_bytes]).to_string(),
         bytes_read: read_bytes,
     })
 }
 
+/// Read raw bytes from a file range without any text decoding.
+/// Byte-accurate sibling of `read_file_chunk` for hex views and header sniffing.
 #[tauri::command]
-fn safe_save_file(path: String, content: String) -> Result<(), String> {
-    let file_path = Path::new(&path);
+fn read_file_range_bytes(path: String, offset: u64, length: usize) -> Result<BytesResponse, PeekError> {
+    let path = paths::resolve(&path);
+    let mut file = std::fs::File::open(&path).at(&path)?;
+    file.seek(SeekFrom::Start(offset))?;
+
+    let mut buffer = Vec::new();
+    file.take(length as u64).read_to_end(&mut buffer)?;
+
+    use base64::{Engine as _, engine::general_purpose};
+    Ok(BytesResponse {
+        data: general_purpose::STANDARD.encode(&buffer),
+        bytes_read: buffer.len(),
+    })
+}
+
+/// Atomically save a file. Saving a `.gz` path re-compresses the content.
+/// With `require_lock`, the save fails with `NOT_LOCKED` unless this process
+/// holds the file's lock (see `lock_file`). With `backup`, the previous
+/// version is first kept as `path~` (or `path` + `backup_suffix`). With
+/// `durable` (default on) the directory is synced after the rename, so the
+/// new file survives a power loss right after the save; turning it off
+/// saves a disk flush. `encoding` is the WHATWG label the file was read with
+/// (`read_file_content`'s `encoding`), default UTF-8; content it can't
+/// represent fails the save rather than being written lossily.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+fn safe_save_file(
+    path: String,
+    content: String,
+    require_lock: Option<bool>,
+    backup: Option<bool>,
+    backup_suffix: Option<String>,
+    durable: Option<bool>,
+    encoding: Option<String>,
+    locks: tauri::State<'_, lock::FileLocks>,
+) -> Result<(), PeekError> {
+    let path = paths::resolve(&path);
+    let file_encoding = save_encoding(encoding.as_deref())?;
+    if require_lock.unwrap_or(false) && !locks.is_held(&path)? {
+        return Err(PeekError::not_locked("Acquire the file lock before saving"));
+    }
+    backup_before_save(&path, backup, backup_suffix)?;
+    save_atomically(&path, &content, file_encoding, &locks)?;
+    if durable.unwrap_or(true) {
+        sync_parent_dir(&paths::resolve_path(&path))?;
+    }
+    Ok(())
+}
+
+/// The encoding a save command's `encoding` label names, UTF-8 if none.
+fn save_encoding(label: Option<&str>) -> Result<&'static encoding_rs::Encoding, PeekError> {
+    label.map_or(Ok(encoding_rs::UTF_8), encoding::for_label)
+}
+
+fn save_atomically(
+    path: &str,
+    content: &str,
+    file_encoding: &'static encoding_rs::Encoding,
+    locks: &lock::FileLocks,
+) -> Result<(), PeekError> {
+    let resolved = paths::resolve_path(path);
+    let file_path = resolved.as_path();
     let temp_path = file_path.with_extension("tmp");
+    let encoded;
+    let content = if file_encoding == encoding_rs::UTF_8 {
+        content.as_bytes()
+    } else {
+        // Reading strips a UTF-16 BOM, and without one the file wouldn't be
+        // recognized as UTF-16 when it's opened again
+        let needs_bom = (file_encoding == encoding_rs::UTF_16LE || file_encoding == encoding_rs::UTF_16BE)
+            && !content.starts_with('\u{feff}');
+        let with_bom;
+        let content = if needs_bom {
+            with_bom = format!("\u{feff}{}", content);
+            &with_bom
+        } else {
+            content
+        };
+        encoded = encoding::encode(content, file_encoding)?;
+        &encoded
+    };
+
+    // The compressed size isn't known up front, so `.gz` saves aren't pre-checked
+    if !is_gzip_path(file_path) {
+        ensure_disk_space(file_path, content.len() as u64)?;
+    }
 
     // 1. Write to temp file
-    let mut file = std::fs::File::create(&temp_path).map_err(|e| e.to_string())?;
-    file.write_all(content.as_bytes()).map_err(|e| e.to_string())?;
+    let file = std::fs::File::create(&temp_path).at(&temp_path)?;
+    let file = if is_gzip_path(file_path) {
+        let mut encoder = GzEncoder::new(file, flate2::Compression::default());
+        encoder.write_all(content)?;
+        encoder.finish()?
+    } else {
+        let mut file = file;
+        file.write_all(content)?;
+        file
+    };
     
     // 2. Sync to disk (ensure data is flushed)
-    file.sync_all().map_err(|e| e.to_string())?;
+    file.sync_all()?;
     
     // 3. Atomic replace
-    fs::rename(&temp_path, file_path).map_err(|e| e.to_string())?;
+    locks.replace_file(&temp_path, path)
+}
+
+/// Flush a file's directory entry to disk. A rename is only durable once the
+/// directory holding it is synced; `sync_all` on the file covers its data,
+/// not its name. Windows can't open directories this way and makes renames
+/// durable itself, so there it does nothing.
+#[cfg(unix)]
+fn sync_parent_dir(path: &Path) -> Result<(), PeekError> {
+    Ok(fs::File::open(parent_dir(path))?.sync_all()?)
+}
 
+#[cfg(not(unix))]
+fn sync_parent_dir(_path: &Path) -> Result<(), PeekError> {
     Ok(())
 }
 
+/// Check up front whether `safe_save_file` could write `path`: the file must
+/// be writable (readonly attribute / mode and ownership, via an actual open)
+/// and its directory must accept the temp file that gets renamed into place.
+/// Nothing is modified.
+#[tauri::command]
+fn is_path_writable(path: String) -> Result<WritableResponse, PeekError> {
+    let path = paths::resolve(&path);
+    let file_path = Path::new(&path);
+    let not_writable = |reason: &str| Ok(WritableResponse { writable: false, reason: Some(reason.to_string()) });
+
+    match fs::metadata(file_path) {
+        Ok(_) => {
+            let metadata = match regular_file_metadata(file_path) {
+                Ok(metadata) => metadata,
+                Err(PeekError::IsDirectory { .. }) => return not_writable("IS_DIRECTORY"),
+                Err(PeekError::IsFifo { .. }) => return not_writable("IS_FIFO"),
+                Err(PeekError::IsSocket { .. }) => return not_writable("IS_SOCKET"),
+                Err(PeekError::IsDevice { .. }) => return not_writable("IS_DEVICE"),
+                Err(e) => return Err(e),
+            };
+            if metadata.permissions().readonly() {
+                return not_writable("READONLY");
+            }
+            // Opening without truncation leaves contents and mtime untouched
+            if let Err(e) = fs::OpenOptions::new().write(true).open(file_path) {
+                return match e.kind() {
+                    std::io::ErrorKind::PermissionDenied => not_writable("PERMISSION_DENIED"),
+                    _ => Err(e.into()),
+                };
+            }
+        }
+        // A new file only needs a writable directory
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+        Err(e) => return Err(e.into()),
+    }
+
+    let probe = parent_dir(file_path).join(format!(".peek-write-test-{}", std::process::id()));
+    match fs::OpenOptions::new().write(true).create_new(true).open(&probe) {
+        Ok(_) => {
+            let _ = fs::remove_file(&probe);
+            Ok(WritableResponse { writable: true, reason: None })
+        }
+        Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => Ok(WritableResponse { writable: true, reason: None }),
+        Err(e) if e.kind() == std::io::ErrorKind::PermissionDenied => not_writable("DIRECTORY_NOT_WRITABLE"),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => not_writable("DIRECTORY_NOT_FOUND"),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Free bytes on the file system `path` would be saved to (it needn't exist
+/// yet, only its directory), for warning before a large save.
+#[tauri::command]
+fn get_available_disk_space(path: String) -> Result<u64, PeekError> {
+    let path = paths::resolve(&path);
+    Ok(fs2::available_space(parent_dir(Path::new(&path)))?)
+}
+
+/// Hash a file's current contents, so the editor can detect external edits on save.
+#[tauri::command]
+fn get_file_hash(path: String) -> Result<String, PeekError> {
+    let path = paths::resolve(&path);
+    hash_file(Path::new(&path))
+}
+
+/// Save only if the file on disk still matches what the editor loaded.
+/// On mismatch nothing is written and the error is `CONFLICT` with the current
+/// file's `<mtime>:<hash>` as its message (`0:` if it no longer exists).
+/// `encoding` works as in `safe_save_file`.
+#[tauri::command]
+fn save_file_if_unchanged(
+    path: String,
+    content: String,
+    expected_mtime: Option<u64>,
+    expected_hash: Option<String>,
+    encoding: Option<String>,
+    locks: tauri::State<'_, lock::FileLocks>,
+) -> Result<SaveResponse, PeekError> {
+    let path = paths::resolve(&path);
+    let file_encoding = save_encoding(encoding.as_deref())?;
+    let file_path = Path::new(&path);
+
+    let (current_mtime, current_hash) = match fs::metadata(file_path) {
+        Ok(metadata) => {
+            // Only hash when the caller gave us something to compare against
+            let hash = match expected_hash {
+                Some(_) => hash_file(file_path).at(file_path)?,
+                None => String::new(),
+            };
+            (unix_mtime(&metadata), hash)
+        }
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => (0, String::new()),
+        Err(e) => return Err(e.into()),
+    };
+
+    let mtime_changed = expected_mtime.is_some_and(|m| m != current_mtime);
+    let hash_changed = expected_hash.as_ref().is_some_and(|h| *h != current_hash);
+    if mtime_changed || hash_changed {
+        return Err(PeekError::conflict(format!("{}:{}", current_mtime, current_hash)));
+    }
+
+    save_atomically(&path, &content, file_encoding, &locks)?;
+    // Hash what actually landed on disk (compressed, for `.gz`)
+    let hash = hash_file(file_path).at(file_path)?;
+    let metadata = fs::metadata(file_path).at(file_path)?;
+
+    Ok(SaveResponse { mtime: unix_mtime(&metadata), hash })
+}
+
+/// Save with optional whitespace transforms (all off by default). Returns the
+/// content actually written so the editor buffer can be updated to match.
+/// `backup`, `backup_suffix` and `encoding` work as in `safe_save_file`.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+fn save_file_with_transforms(
+    path: String,
+    content: String,
+    trim_trailing_ws: Option<bool>,
+    ensure_final_newline: Option<bool>,
+    convert_tabs_to_spaces: Option<bool>,
+    tab_width: Option<usize>,
+    backup: Option<bool>,
+    backup_suffix: Option<String>,
+    encoding: Option<String>,
+    locks: tauri::State<'_, lock::FileLocks>,
+) -> Result<String, PeekError> {
+    let path = paths::resolve(&path);
+    let file_encoding = save_encoding(encoding.as_deref())?;
+    let tabs_to_spaces = convert_tabs_to_spaces
+        .unwrap_or(false)
+        .then(|| tab_width.unwrap_or(4).max(1));
+    let transformed = apply_save_transforms(
+        &content,
+        trim_trailing_ws.unwrap_or(false),
+        ensure_final_newline.unwrap_or(false),
+        tabs_to_spaces,
+    );
+    backup_before_save(&path, backup, backup_suffix)?;
+    save_atomically(&path, &transformed, file_encoding, &locks)?;
+    Ok(transformed)
+}
+
+/// Show `path` selected in the system file manager (Finder, Explorer, or
+/// whatever implements the FreeDesktop FileManager1 interface on Linux).
+#[tauri::command]
+fn reveal_in_file_manager(path: String) -> Result<(), PeekError> {
+    let path = paths::resolve(&path);
+    fs::metadata(&path).at(&path)?;
+    tauri_plugin_opener::reveal_item_in_dir(&path)
+        .map_err(|e| format!("No file manager could reveal {}: {}", path, e).into())
+}
+
+/// Hand `path` to the application the OS associates with it.
+#[tauri::command]
+fn open_with_default_app(path: String) -> Result<(), PeekError> {
+    let path = paths::resolve(&path);
+    fs::metadata(&path).at(&path)?;
+    tauri_plugin_opener::open_path(&path, None::<&str>)
+        .map_err(|e| format!("No application is registered to open {}: {}", path, e).into())
+}
+
+/// Copy a file, keeping its permissions and modification time. Refuses to
+/// replace an existing `dest` unless `overwrite` is set.
+#[tauri::command]
+fn duplicate_file(src: String, dest: String, overwrite: Option<bool>) -> Result<FileInfo, PeekError> {
+    let (src, dest) = (paths::resolve(&src), paths::resolve(&dest));
+    let src_path = Path::new(&src);
+    let dest_path = Path::new(&dest);
+
+    let src_metadata = regular_file_metadata(src_path).at(src_path)?;
+    if dest_path.exists() && !overwrite.unwrap_or(false) {
+        return Err(PeekError::already_exists("Destination already exists").at(&dest));
+    }
+
+    copy_with_mtime(src_path, dest_path, &src_metadata).at(&dest)?;
+    file_info(dest_path)
+}
+
 #[tauri::command]
-fn write_file_content(path: String, content: String) -> Result<(), String> {
+fn write_file_content(
+    path: String,
+    content: String,
+    encoding: Option<String>,
+    locks: tauri::State<'_, lock::FileLocks>,
+) -> Result<(), PeekError> {
+    let path = paths::resolve(&path);
     // Forward to safe implementation for now, or keep as unsafe alias?
     // Let's upgrade it to safe implementation to protect existing calls.
-    safe_save_file(path, content)
+    let file_encoding = save_encoding(encoding.as_deref())?;
+    save_atomically(&path, &content, file_encoding, &locks)
 }
 
 // ── App Entry ─────────────────────────────────────────────────────────────────
@@ -357,27 +3032,246 @@ pub fn run() {
     tauri::Builder::default()
         .plugin(tauri_plugin_opener::init())
         .plugin(tauri_plugin_dialog::init())
-        .manage(LineIndexCache(Mutex::new(HashMap::new())))
+        .manage(LineIndexCache(Mutex::new(IndexStore::new(DEFAULT_INDEX_CACHE_BUDGET))))
         .manage(terminal::PtyState::new())
+        .manage(cli::LaunchTargets::from_args())
+        .manage(recent::RecentFiles::new())
+        .manage(lock::FileLocks::new())
+        .manage(trash::TrashLog::new())
+        .manage(clipboard::ClipboardState::new())
+        .manage(watch::DirWatchers::new())
+        .manage(watch::FileWatchers::new())
+        .manage(command::CommandStreams::new())
+        .manage(dirhash::HashJobs::new())
+        .manage(quickopen::FileLists::new())
         .setup(|app| {
             let window = app.get_webview_window("main").unwrap();
             let icon = tauri::image::Image::from_bytes(include_bytes!("../icons/icon.png"))?;
             window.set_icon(icon)?;
             Ok(())
         })
+        .register_asynchronous_uri_scheme_protocol(protocol::IMAGE_SCHEME, |_ctx, request, responder| {
+            std::thread::spawn(move || responder.respond(protocol::handle_image_request(&request)));
+        })
+        .on_page_load(|webview, payload| {
+            // Send `peek file.rs:120:8` locations once the frontend can listen
+            if payload.event() == PageLoadEvent::Finished {
+                let targets = webview.state::<cli::LaunchTargets>();
+                let pending = targets.0.lock().map(|mut t| std::mem::take(&mut *t)).unwrap_or_default();
+                for target in pending {
+                    let _ = webview.emit("open-at-location", target);
+                }
+            }
+        })
         .invoke_handler(tauri::generate_handler![
             read_file_content,
+            get_file_metadata,
+            read_files,
+            probe::probe_file,
+            probe::classify_file,
+            archive::list_archive,
+            archive::read_archive_entry,
+            watch::watch_directory,
+            watch::unwatch_directory,
+            watch::watch_file,
+            watch::unwatch_file,
+            encoding::detect_encoding_candidates,
+            encoding::find_encoding_errors,
+            shells::list_available_shells,
             read_file_chunk,
+            read_file_range_bytes,
             write_file_content,
             safe_save_file,
+            save_file_if_unchanged,
+            save_file_with_transforms,
+            duplicate_file,
+            reveal_in_file_manager,
+            open_with_default_app,
+            get_file_hash,
+            is_path_writable,
+            get_available_disk_space,
             index_file,
+            index_gzip_file,
+            reindex_if_needed,
+            index_content,
             read_lines,
+            read_ndjson_lines,
+            read_line_context,
+            byte_to_position,
+            jump_to_byte,
+            position_to_byte,
+            selection_stats,
+            read_lines_from_end,
+            peek_head_tail,
+            read_stream_snapshot,
             patch_file_lines,
+            toggle_line_comment,
+            move_lines,
+            sort_lines,
+            join_lines,
+            split_line,
+            replace_in_range,
+            normalize_line_endings,
+            file_stats,
+            close_file,
+            clear_index_cache,
+            set_index_cache_budget,
+            get_backend_stats,
+            cache_stats,
+            cli::parse_open_target,
+            clipboard::copy_to_clipboard,
+            clipboard::copy_lines_to_clipboard,
+            clipboard::copy_image_to_clipboard,
+            clipboard::read_clipboard,
+            diff::diff_files,
+            diff::diff_file_with_content,
+            dirhash::hash_directory,
+            dirhash::cancel_hash_directory,
+            quickopen::fuzzy_find_files,
+            fold::compute_fold_ranges,
+            format::format_structured,
+            git::git_blame,
+            git::git_diff_hunks,
+            git::git_stage_hunk,
+            git::git_revert_hunk,
+            hazards::scan_unicode_hazards,
+            search::search_file,
+            search::count_matches_in_file,
+            symbols::extract_symbols,
+            paths::expand_path,
+            paths::get_env_var,
+            paths::find_project_root,
+            paths::set_cwd,
+            paths::get_cwd,
+            beautify::beautify_code,
+            lock::lock_file,
+            lock::unlock_file,
+            patch::parse_unified_diff,
+            patch::apply_patch_to_file,
+            recent::get_recent_files,
+            recent::add_recent_file,
+            rename::rename_identifier,
+            schema::validate_json_schema,
             terminal::spawn_terminal,
+            terminal::spawn_command_terminal,
+            terminal::get_login_shell_env,
             terminal::write_terminal,
+            terminal::write_terminal_bytes,
             terminal::resize_terminal,
             terminal::kill_terminal,
+            terminal::signal_terminal,
+            terminal::terminal_process_tree,
+            terminal::pause_terminal_output,
+            terminal::resume_terminal_output,
+            terminal::replay_terminal,
+            terminal::log_terminal,
+            terminal::stop_logging_terminal,
+            terminal::get_terminal_scrollback,
+            terminal::get_terminal_buffer_as_text,
+            terminal::search_terminal_scrollback,
+            terminal::rename_terminal,
+            terminal::list_terminals,
+            command::stream_command,
+            command::stop_command,
+            trash::delete_to_trash,
+            trash::restore_from_trash,
+            trash::list_trashed,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Write `bytes` to a fresh temp file and index it.
+    fn index_temp_file(name: &str, bytes: &[u8]) -> (LineIndexCache, String, IndexResponse) {
+        let path = std::env::temp_dir().join(format!("peek-test-{}-{}", std::process::id(), name));
+        fs::write(&path, bytes).unwrap();
+        let path = path.to_string_lossy().to_string();
+        let cache = LineIndexCache(Mutex::new(IndexStore::new(DEFAULT_INDEX_CACHE_BUDGET)));
+        let response = index_and_cache(path.clone(), false, &cache).unwrap();
+        (cache, path, response)
+    }
+
+    fn last_line(cache: &LineIndexCache, path: &str, total_lines: usize) -> LinesResponse {
+        let mut store = cache.0.lock().unwrap();
+        let index = store.get(path).unwrap();
+        read_indexed_lines(path, index, total_lines - 1, 1).unwrap()
+    }
+
+    #[test]
+    fn empty_file_is_one_empty_line() {
+        let (cache, path, response) = index_temp_file("empty", b"");
+        assert_eq!(response.total_lines, 1);
+        assert!(!response.has_trailing_newline);
+        let line = last_line(&cache, &path, response.total_lines);
+        assert_eq!((line.content.as_str(), line.lines_read), ("", 1));
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn single_line_without_newline() {
+        let (cache, path, response) = index_temp_file("no-newline", b"a");
+        assert_eq!(response.total_lines, 1);
+        assert!(!response.has_trailing_newline);
+        let line = last_line(&cache, &path, response.total_lines);
+        assert_eq!((line.content.as_str(), line.lines_read), ("a", 1));
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn lone_newline_ends_in_an_empty_line() {
+        let (cache, path, response) = index_temp_file("newline", b"\n");
+        assert_eq!(response.total_lines, 2);
+        assert!(response.has_trailing_newline);
+        let line = last_line(&cache, &path, response.total_lines);
+        assert_eq!((line.content.as_str(), line.start_line, line.lines_read), ("", 1, 1));
+        fs::remove_file(&path).unwrap();
+    }
+
+    /// Replace `line_count` lines from `start_line` of `original` with `new`
+    /// as `patch_lines` does, and check the spliced offsets against a rescan.
+    fn assert_splice_matches_rescan(original: &[u8], start_line: usize, line_count: usize, new: &[u8]) {
+        let (mut offsets, _, size) = scan_line_offsets(original).unwrap();
+        let end_line = (start_line + line_count).min(offsets.len());
+        let end_byte = if end_line < offsets.len() { offsets[end_line] } else { size };
+        let mut edited = original[..offsets[start_line] as usize].to_vec();
+        edited.extend_from_slice(new);
+        edited.extend_from_slice(&original[end_byte as usize..]);
+
+        splice_line_offsets(&mut offsets, start_line, end_line, end_byte, new);
+        assert_eq!(offsets, scan_line_offsets(&edited[..]).unwrap().0, "edited: {:?}", String::from_utf8_lossy(&edited));
+    }
+
+    #[test]
+    fn splice_inserting_newlines() {
+        assert_splice_matches_rescan(b"a\nb\nc\n", 1, 1, b"b1\nb2\nb3\n");
+        assert_splice_matches_rescan(b"a\nb\nc\n", 0, 0, b"new\n");
+        assert_splice_matches_rescan(b"a\nb\nc", 1, 1, b"x\ny");
+    }
+
+    #[test]
+    fn splice_removing_newlines() {
+        assert_splice_matches_rescan(b"a\nb\nc\nd\n", 1, 2, b"bc\n");
+        assert_splice_matches_rescan(b"a\nb\nc\nd\n", 1, 2, b"");
+        assert_splice_matches_rescan(b"a\nb\nc\nd\n", 0, 3, b"abc");
+    }
+
+    #[test]
+    fn splice_editing_the_last_line() {
+        assert_splice_matches_rescan(b"a\nb", 1, 1, b"bee");
+        assert_splice_matches_rescan(b"a\nb", 1, 1, b"bee\n");
+        assert_splice_matches_rescan(b"a\nb\n", 1, 1, b"bee\n");
+        assert_splice_matches_rescan(b"a\nb\n", 2, 1, b"c");
+        assert_splice_matches_rescan(b"", 0, 1, b"first\nsecond");
+    }
+
+    #[test]
+    fn splice_crlf() {
+        assert_splice_matches_rescan(b"a\r\nb\r\nc\r\n", 1, 1, b"x\r\ny\r\n");
+        assert_splice_matches_rescan(b"a\r\nb\r\nc\r\n", 0, 2, b"ab\r\n");
+        assert_splice_matches_rescan(b"a\r\nb\r\nc", 2, 1, b"c\r\nd");
+    }
+}
@@ -0,0 +1,93 @@
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::path::Path;
+use std::sync::Mutex;
+
+use fs2::FileExt;
+
+use crate::error::{AtPath, PeekError};
+
+/// Managed state: advisory locks held by this process, keyed by path. Dropping
+/// the handle releases the lock.
+///
+/// The locks are cooperative on Unix (`flock`): they only stop other tools
+/// that also lock. On Windows they are mandatory byte-range locks.
+pub struct FileLocks(Mutex<HashMap<String, File>>);
+
+impl FileLocks {
+    pub fn new() -> Self {
+        FileLocks(Mutex::new(HashMap::new()))
+    }
+
+    pub fn is_held(&self, path: &str) -> Result<bool, PeekError> {
+        let locks = self.0.lock()?;
+        Ok(locks.contains_key(path))
+    }
+
+    /// Drop the lock on `path`, if held.
+    pub fn release(&self, path: &str) -> Result<(), PeekError> {
+        let mut locks = self.0.lock()?;
+        if let Some(file) = locks.remove(path) {
+            let _ = file.unlock();
+        }
+        Ok(())
+    }
+
+    /// Move `temp_path` over `path`. A lock is tied to the file, not the name,
+    /// so a held lock is released around the rename and re-taken on the
+    /// replacement; otherwise it would keep guarding the old, unlinked file.
+    pub fn replace_file(&self, temp_path: &Path, path: &str) -> Result<(), PeekError> {
+        let mut locks = self.0.lock()?;
+        let held = locks.remove(path).map(|file| file.unlock()).is_some();
+
+        fs::rename(temp_path, path)?;
+
+        if held {
+            if let Some(file) = try_lock(path)? {
+                locks.insert(path.to_string(), file);
+            }
+        }
+        Ok(())
+    }
+}
+
+// ── Helpers ───────────────────────────────────────────────────────────────────
+
+/// Open `path` and take an exclusive lock without blocking. `None` means
+/// someone else holds it.
+fn try_lock(path: &str) -> Result<Option<File>, PeekError> {
+    let file = File::open(path).at(path)?;
+    match file.try_lock_exclusive() {
+        Ok(()) => Ok(Some(file)),
+        Err(e) if e.kind() == fs2::lock_contended_error().kind() => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+// ── Commands ──────────────────────────────────────────────────────────────────
+
+/// Take an exclusive advisory lock on `path`. Returns `false` if another
+/// process (or another Peek window) already holds it.
+#[tauri::command]
+pub fn lock_file(path: String, state: tauri::State<'_, FileLocks>) -> Result<bool, PeekError> {
+    let path = crate::paths::resolve(&path);
+    let mut locks = state.0.lock()?;
+    if locks.contains_key(&path) {
+        return Ok(true);
+    }
+    match try_lock(&path)? {
+        Some(file) => {
+            locks.insert(path, file);
+            Ok(true)
+        }
+        None => Ok(false),
+    }
+}
+
+/// Release a lock taken with `lock_file`. Unlocking a path that isn't locked
+/// is a no-op.
+#[tauri::command]
+pub fn unlock_file(path: String, state: tauri::State<'_, FileLocks>) -> Result<(), PeekError> {
+    let path = crate::paths::resolve(&path);
+    state.release(&path)
+}
@@ -0,0 +1,455 @@
+use std::fs;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{AtPath, PeekError};
+use crate::lock::FileLocks;
+use crate::LineIndexCache;
+
+/// How many context lines may be ignored at each end of a hunk that doesn't
+/// match as-is (GNU patch's default fuzz factor).
+const MAX_FUZZ: usize = 2;
+
+// ── Types ─────────────────────────────────────────────────────────────────────
+
+/// One `@@ -old_start,old_len +new_start,new_len @@` block. `lines` keep
+/// their prefix: `' '` context, `'-'` removed, `'+'` added, `'\'` marker.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Hunk {
+    old_start: usize,
+    old_len: usize,
+    new_start: usize,
+    new_len: usize,
+    section: String,
+    lines: Vec<String>,
+}
+
+/// The hunks for one file. A path is `None` for `/dev/null` (file created or
+/// deleted); git's `a/` and `b/` prefixes are stripped.
+#[derive(Serialize, Clone)]
+pub struct FilePatch {
+    old_path: Option<String>,
+    new_path: Option<String>,
+    pub hunks: Vec<Hunk>,
+}
+
+#[derive(Serialize)]
+pub struct HunkResult {
+    index: usize,
+    applied: bool,
+    /// 0-based line of the patched file where the hunk landed.
+    line: Option<usize>,
+    /// Lines between where the hunk said it applies and where it did.
+    offset: isize,
+    /// Context lines ignored at each end to make it match.
+    fuzz: usize,
+    /// For failed hunks, the hunk itself so the UI can show the context.
+    lines: Vec<String>,
+}
+
+/// `written` is only true if every hunk applied: a patch is never half-applied.
+#[derive(Serialize)]
+pub struct ApplyResult {
+    pub written: bool,
+    hunks: Vec<HunkResult>,
+}
+
+// ── Parsing ───────────────────────────────────────────────────────────────────
+
+/// Path from a `---`/`+++` header, without the timestamp some tools append.
+fn header_path(rest: &str, git_prefix: &str) -> Option<String> {
+    let path = rest.split('\t').next().unwrap_or(rest).trim_end();
+    if path == "/dev/null" {
+        return None;
+    }
+    Some(path.strip_prefix(git_prefix).unwrap_or(path).to_string())
+}
+
+/// Parse `start[,len]` from a hunk header; the length defaults to 1.
+fn parse_range(range: &str) -> Option<(usize, usize)> {
+    match range.split_once(',') {
+        Some((start, len)) => Some((start.parse().ok()?, len.parse().ok()?)),
+        None => Some((range.parse().ok()?, 1)),
+    }
+}
+
+fn parse_hunk_header(line: &str) -> Option<(usize, usize, usize, usize, String)> {
+    let rest = line.strip_prefix("@@ -")?;
+    let (ranges, section) = rest.split_once(" @@")?;
+    let (old, new) = ranges.split_once(" +")?;
+    let (old_start, old_len) = parse_range(old)?;
+    let (new_start, new_len) = parse_range(new)?;
+    Some((old_start, old_len, new_start, new_len, section.trim().to_string()))
+}
+
+pub fn parse_patch(content: &str) -> Result<Vec<FilePatch>, PeekError> {
+    let lines: Vec<&str> = content.lines().collect();
+    let mut files: Vec<FilePatch> = Vec::new();
+    let mut i = 0;
+
+    while i < lines.len() {
+        let line = lines[i];
+        if let (Some(old), Some(new)) = (
+            line.strip_prefix("--- "),
+            lines.get(i + 1).and_then(|next| next.strip_prefix("+++ ")),
+        ) {
+            let git = old.starts_with("a/") && new.starts_with("b/");
+            files.push(FilePatch {
+                old_path: header_path(old, if git { "a/" } else { "" }),
+                new_path: header_path(new, if git { "b/" } else { "" }),
+                hunks: Vec::new(),
+            });
+            i += 2;
+            continue;
+        }
+
+        if line.starts_with("@@") {
+            let (old_start, old_len, new_start, new_len, section) = parse_hunk_header(line)
+                .ok_or_else(|| format!("Invalid hunk header at line {}", i + 1))?;
+            let file = files
+                .last_mut()
+                .ok_or_else(|| format!("Hunk without a file header at line {}", i + 1))?;
+
+            let mut body = Vec::new();
+            let (mut old_seen, mut new_seen) = (0, 0);
+            i += 1;
+            while old_seen < old_len || new_seen < new_len || lines.get(i).is_some_and(|l| l.starts_with('\\')) {
+                let Some(&body_line) = lines.get(i) else {
+                    return Err(format!("Hunk at line {} is truncated", i).into());
+                };
+                match body_line.chars().next() {
+                    // Some tools strip the space from empty context lines
+                    Some(' ') | None => {
+                        old_seen += 1;
+                        new_seen += 1;
+                    }
+                    Some('-') => old_seen += 1,
+                    Some('+') => new_seen += 1,
+                    Some('\\') => {}
+                    _ => return Err(format!("Unexpected line in hunk at line {}", i + 1).into()),
+                }
+                body.push(if body_line.is_empty() { " ".to_string() } else { body_line.to_string() });
+                i += 1;
+            }
+            file.hunks.push(Hunk { old_start, old_len, new_start, new_len, section, lines: body });
+            continue;
+        }
+
+        // `diff --git`, `index ...`, mode lines and commentary
+        i += 1;
+    }
+
+    Ok(files)
+}
+
+// ── Applying ──────────────────────────────────────────────────────────────────
+
+/// A hunk split into what it expects to find and what replaces it.
+struct HunkSides {
+    old: Vec<String>,
+    new: Vec<String>,
+    /// Context lines at the start and end (the only ones fuzz may drop).
+    leading_context: usize,
+    trailing_context: usize,
+    old_no_newline: bool,
+    new_no_newline: bool,
+}
+
+fn hunk_sides(hunk: &Hunk) -> HunkSides {
+    let mut sides = HunkSides {
+        old: Vec::new(),
+        new: Vec::new(),
+        leading_context: 0,
+        trailing_context: 0,
+        old_no_newline: false,
+        new_no_newline: false,
+    };
+    let mut last = ' ';
+    let mut changed = false;
+    for line in &hunk.lines {
+        // An empty line is context whose trailing space was stripped
+        let mut chars = line.chars();
+        let prefix = chars.next().unwrap_or(' ');
+        let text = chars.as_str();
+        match prefix {
+            ' ' => {
+                sides.old.push(text.to_string());
+                sides.new.push(text.to_string());
+                if changed {
+                    sides.trailing_context += 1;
+                } else {
+                    sides.leading_context += 1;
+                }
+            }
+            '-' => {
+                sides.old.push(text.to_string());
+                changed = true;
+                sides.trailing_context = 0;
+            }
+            '+' => {
+                sides.new.push(text.to_string());
+                changed = true;
+                sides.trailing_context = 0;
+            }
+            // "\ No newline at end of file" applies to the line before it
+            _ => match last {
+                '-' => sides.old_no_newline = true,
+                '+' => sides.new_no_newline = true,
+                _ => {
+                    sides.old_no_newline = true;
+                    sides.new_no_newline = true;
+                }
+            },
+        }
+        last = prefix;
+    }
+    if !changed {
+        sides.trailing_context = 0;
+    }
+    sides
+}
+
+fn without_cr(line: &str) -> &str {
+    line.strip_suffix('\r').unwrap_or(line)
+}
+
+/// Find `needle` in `lines[from..]`, trying `expected` first and then
+/// positions at increasing distance from it. A trailing `\r` on either side
+/// is ignored.
+fn find_block(lines: &[String], needle: &[String], from: usize, expected: usize) -> Option<usize> {
+    let last_start = lines.len().checked_sub(needle.len())?;
+    if from > last_start {
+        return None;
+    }
+    let expected = expected.clamp(from, last_start);
+    let matches = |at: usize| {
+        lines[at..at + needle.len()]
+            .iter()
+            .zip(needle)
+            .all(|(line, want)| without_cr(line) == without_cr(want))
+    };
+    for distance in 0..=last_start - from {
+        if expected + distance <= last_start && matches(expected + distance) {
+            return Some(expected + distance);
+        }
+        if distance > 0 && expected >= from + distance && matches(expected - distance) {
+            return Some(expected - distance);
+        }
+    }
+    None
+}
+
+impl Hunk {
+    /// Check a hunk that came from the frontend rather than `parse_patch`:
+    /// every line must start with `' '`, `'+'`, `'-'` or `'\'` (an empty
+    /// line counts as context). Fails with `PARSE_ERROR` at column 1 of the
+    /// first bad line, counted from 1 within the hunk.
+    pub fn validate(&self) -> Result<(), PeekError> {
+        match self.lines.iter().position(|line| !matches!(line.chars().next(), None | Some(' ' | '+' | '-' | '\\'))) {
+            Some(i) => Err(PeekError::parse_error(i + 1, 1, "Hunk lines must start with ' ', '+', '-' or '\\'")),
+            None => Ok(()),
+        }
+    }
+
+    /// The hunk that undoes this one.
+    pub fn reversed(&self) -> Hunk {
+        let lines = self
+            .lines
+            .iter()
+            .map(|line| match (line.chars().next(), line.get(1..)) {
+                (Some('+'), Some(text)) => format!("-{}", text),
+                (Some('-'), Some(text)) => format!("+{}", text),
+                _ => line.clone(),
+            })
+            .collect();
+        Hunk {
+            old_start: self.new_start,
+            old_len: self.new_len,
+            new_start: self.old_start,
+            new_len: self.old_len,
+            section: self.section.clone(),
+            lines,
+        }
+    }
+
+    /// The hunk as unified diff text, header included.
+    pub fn to_patch_text(&self) -> String {
+        let mut text = format!("@@ -{},{} +{},{} @@", self.old_start, self.old_len, self.new_start, self.new_len);
+        if !self.section.is_empty() {
+            text.push(' ');
+            text.push_str(&self.section);
+        }
+        text.push('\n');
+        for line in &self.lines {
+            text.push_str(line);
+            text.push('\n');
+        }
+        text
+    }
+}
+
+/// Apply every hunk to `lines` in order. Returns per-hunk results; `lines`
+/// and `trailing_newline` are only meaningful if all hunks applied. Lines keep
+/// their own `\r`; the ones hunks write end in `\r` only when `crlf`.
+fn apply_hunks(lines: &mut Vec<String>, trailing_newline: &mut bool, crlf: bool, hunks: &[Hunk]) -> Vec<HunkResult> {
+    let mut results = Vec::new();
+    let mut delta: isize = 0; // lines added minus removed so far
+    let mut min_start = 0; // don't re-match text produced by earlier hunks
+
+    for (index, hunk) in hunks.iter().enumerate() {
+        let sides = hunk_sides(hunk);
+        // A pure insertion's start is the line it goes after
+        let declared = if hunk.old_len == 0 { hunk.old_start } else { hunk.old_start.saturating_sub(1) };
+        let expected = (declared as isize + delta).max(0) as usize;
+
+        let mut placed = None;
+        for fuzz in 0..=MAX_FUZZ {
+            let head = fuzz.min(sides.leading_context);
+            let tail = fuzz.min(sides.trailing_context);
+            if fuzz > 0 && head == 0 && tail == 0 {
+                break; // nothing left to ignore
+            }
+            let old = &sides.old[head..sides.old.len() - tail];
+            if let Some(at) = find_block(lines, old, min_start, expected + head) {
+                placed = Some((at, head, tail, fuzz));
+                break;
+            }
+        }
+
+        let Some((at, head, tail, fuzz)) = placed else {
+            results.push(HunkResult {
+                index,
+                applied: false,
+                line: None,
+                offset: 0,
+                fuzz: 0,
+                lines: hunk.lines.clone(),
+            });
+            continue;
+        };
+
+        let old_len = sides.old.len() - head - tail;
+        let touches_end = at + old_len == lines.len();
+        let mut new: Vec<String> = sides.new[head..sides.new.len() - tail]
+            .iter()
+            .map(|line| format!("{}{}", without_cr(line), if crlf { "\r" } else { "" }))
+            .collect();
+        if touches_end && sides.new_no_newline {
+            if let Some(last) = new.last_mut() {
+                last.truncate(without_cr(last).len());
+            }
+        }
+        let new_len = new.len();
+        lines.splice(at..at + old_len, new);
+        if touches_end {
+            if sides.new_no_newline {
+                *trailing_newline = false;
+            } else if sides.old_no_newline {
+                *trailing_newline = true;
+            }
+        }
+
+        results.push(HunkResult {
+            index,
+            applied: true,
+            line: Some(at - head),
+            offset: (at - head) as isize - expected as isize,
+            fuzz,
+            lines: Vec::new(),
+        });
+        delta += new_len as isize - old_len as isize;
+        min_start = at + new_len;
+    }
+    results
+}
+
+// ── Commands ──────────────────────────────────────────────────────────────────
+
+/// Parse a unified diff (`diff -u`, `git diff`, `.patch` files) into files
+/// and hunks, for previewing before applying.
+#[tauri::command]
+pub fn parse_unified_diff(content: String) -> Result<Vec<FilePatch>, PeekError> {
+    parse_patch(&content)
+}
+
+/// Apply a unified diff to `path`. Hunks that moved are found by searching
+/// outward from their stated position, and up to `MAX_FUZZ` context lines
+/// may be ignored. All-or-nothing: if any hunk fails the file is untouched and
+/// the failures are reported; otherwise it's replaced atomically and its
+/// cached line index (if any) rebuilt. A multi-file patch must name `path`.
+#[tauri::command]
+pub fn apply_patch_to_file(
+    path: String,
+    patch: String,
+    cache: tauri::State<'_, LineIndexCache>,
+    locks: tauri::State<'_, FileLocks>,
+) -> Result<ApplyResult, PeekError> {
+    let path = crate::paths::resolve(&path);
+    let files = parse_patch(&patch)?;
+    let file_patch = match files.as_slice() {
+        [single] => single,
+        _ => files
+            .iter()
+            .find(|file| {
+                [&file.new_path, &file.old_path]
+                    .into_iter()
+                    .flatten()
+                    .any(|patch_path| Path::new(&path).ends_with(patch_path))
+            })
+            .ok_or_else(|| format!("Patch doesn't touch {}", path))?,
+    };
+
+    apply_hunks_to_file(path, &file_patch.hunks, &cache, &locks)
+}
+
+/// Apply hunks to a file as `apply_patch_to_file` does: all or nothing, then
+/// an atomic replace and a rebuild of its cached line index. The file is
+/// decoded and saved in its detected encoding; bytes that aren't valid in it
+/// fail rather than being replaced.
+pub fn apply_hunks_to_file(
+    path: String,
+    file_hunks: &[Hunk],
+    cache: &LineIndexCache,
+    locks: &FileLocks,
+) -> Result<ApplyResult, PeekError> {
+    for hunk in file_hunks {
+        hunk.validate()?;
+    }
+    let file_path = Path::new(&path);
+    crate::regular_file_metadata(file_path).at(file_path)?;
+    let bytes = fs::read(file_path).at(file_path)?;
+    let file_encoding = crate::encoding::detect(&bytes[..bytes.len().min(crate::encoding::SAMPLE_BYTES)]);
+    // A BOM decodes to U+FEFF; keep it out of the first line's text
+    let decoded = file_encoding
+        .decode_without_bom_handling_and_without_replacement(&bytes)
+        .ok_or_else(|| format!("{} isn't valid {}", path, file_encoding.name()))?;
+    let bom = decoded.starts_with('\u{feff}');
+    let content = decoded.strip_prefix('\u{feff}').unwrap_or(&decoded);
+    // An empty file has no last line to be missing its newline
+    let mut trailing_newline = content.is_empty() || content.ends_with('\n');
+    let body = content.strip_suffix('\n').unwrap_or(content);
+    let mut lines: Vec<String> =
+        if content.is_empty() { Vec::new() } else { body.split('\n').map(str::to_string).collect() };
+    // Untouched lines keep their endings; new ones are CRLF only if every line is
+    let crlf = !lines.is_empty()
+        && lines.iter().enumerate().all(|(i, line)| line.ends_with('\r') || (i + 1 == lines.len() && !trailing_newline));
+
+    let hunks = apply_hunks(&mut lines, &mut trailing_newline, crlf, file_hunks);
+    if hunks.iter().any(|hunk| !hunk.applied) {
+        return Ok(ApplyResult { written: false, hunks });
+    }
+
+    let mut patched = if bom { String::from('\u{feff}') } else { String::new() };
+    patched.push_str(&lines.join("\n"));
+    if trailing_newline && !lines.is_empty() {
+        patched.push('\n');
+    }
+    crate::save_atomically(&path, &patched, file_encoding, locks)?;
+
+    let indexed = cache.0.lock()?.entries.contains_key(&path);
+    if indexed {
+        crate::index_and_cache(path, false, cache)?;
+    }
+    Ok(ApplyResult { written: true, hunks })
+}
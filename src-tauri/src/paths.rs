@@ -0,0 +1,336 @@
+use std::path::{Component, Path, PathBuf};
+use std::sync::Mutex;
+
+use serde::Serialize;
+
+use crate::error::PeekError;
+
+/// What marks a project root when the caller doesn't say, in order of
+/// preference within one directory.
+const DEFAULT_ROOT_MARKERS: &[&str] = &[".git", ".hg", ".svn", "Cargo.toml", "package.json"];
+
+/// Directories are limited to 248 characters, files to 260.
+const MAX_DIRECTORY_PATH: usize = 248;
+
+/// The directory relative paths resolve against, once `set_cwd` has set one.
+/// Kept here rather than as the process's working directory, which spawned
+/// terminals and commands inherit and other threads read at any moment.
+static WORKING_DIR: Mutex<Option<PathBuf>> = Mutex::new(None);
+
+// ── Types ─────────────────────────────────────────────────────────────────────
+
+#[derive(Serialize)]
+pub struct ProjectRoot {
+    root: String,
+    /// The marker found there, e.g. ".git".
+    marker: String,
+}
+
+// ── Helpers ───────────────────────────────────────────────────────────────────
+
+/// The directory relative paths resolve against: the one `set_cwd` set, or
+/// the one the app started in.
+fn working_dir() -> Result<PathBuf, PeekError> {
+    match WORKING_DIR.lock()?.clone() {
+        Some(dir) => Ok(dir),
+        None => Ok(std::env::current_dir()?),
+    }
+}
+
+/// `path` joined to the working directory if it's relative.
+fn absolute(path: &Path) -> PathBuf {
+    if path.is_absolute() {
+        return path.to_path_buf();
+    }
+    match working_dir() {
+        Ok(dir) => dir.join(path),
+        Err(_) => path.to_path_buf(),
+    }
+}
+
+/// A path from the frontend, in the form file APIs accept: relative paths are
+/// made absolute against the working directory (see `set_cwd`). On Windows,
+/// paths too long for `MAX_PATH` (deep `node_modules` trees) also get the
+/// verbatim `\\?\` prefix, `\\?\UNC\` for network shares, with `/` turned
+/// into `\` (verbatim paths skip that normalization).
+#[cfg(windows)]
+pub fn resolve_path(path: &str) -> PathBuf {
+    if path.starts_with(r"\\?\") || path.starts_with(r"\\.\") {
+        return PathBuf::from(path);
+    }
+    let normalized = path.replace('/', "\\");
+    let joined = absolute(Path::new(&normalized));
+    let absolute = std::path::absolute(&joined).unwrap_or(joined);
+    match long_path_form(&absolute.to_string_lossy()) {
+        Some(verbatim) => PathBuf::from(verbatim),
+        None => absolute,
+    }
+}
+
+/// The verbatim form of an absolute Windows path too long for `MAX_PATH`, or
+/// `None` if it's short enough or already verbatim.
+#[cfg_attr(not(windows), allow(dead_code))]
+fn long_path_form(absolute: &str) -> Option<String> {
+    if absolute.len() < MAX_DIRECTORY_PATH || absolute.starts_with(r"\\?\") || absolute.starts_with(r"\\.\") {
+        return None;
+    }
+    Some(match absolute.strip_prefix(r"\\") {
+        Some(share) => format!(r"\\?\UNC\{}", share),
+        None => format!(r"\\?\{}", absolute),
+    })
+}
+
+#[cfg(not(windows))]
+pub fn resolve_path(path: &str) -> PathBuf {
+    absolute(Path::new(path))
+}
+
+/// `resolve_path` as a string, for commands to rebind their `path` argument
+/// to: file access and the per-file state keyed by path (line indexes,
+/// locks, watchers) then agree on what a path names.
+pub fn resolve(path: &str) -> String {
+    resolve_path(path).to_string_lossy().into_owned()
+}
+
+fn home_dir() -> Option<PathBuf> {
+    #[cfg(windows)]
+    let home = std::env::var_os("USERPROFILE").or_else(|| std::env::var_os("HOME"));
+    #[cfg(not(windows))]
+    let home = std::env::var_os("HOME");
+    home.filter(|home| !home.is_empty()).map(PathBuf::from)
+}
+
+/// Replace environment variables in `text`: `$NAME` and `${NAME}` everywhere,
+/// plus `%NAME%` on Windows. Unset variables are left as written.
+fn expand_env_vars(text: &str) -> String {
+    let lookup = |name: &str| std::env::var(name).ok();
+    let is_name_char = |c: char| c.is_ascii_alphanumeric() || c == '_';
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(at) = rest.find(['$', '%']) {
+        out.push_str(&rest[..at]);
+        let tail = &rest[at..];
+        let (name, written) = if let Some(braced) = tail.strip_prefix("${") {
+            match braced.find('}') {
+                Some(end) => (&braced[..end], end + 3),
+                None => ("", 1),
+            }
+        } else if let Some(bare) = tail.strip_prefix('$') {
+            let end = bare.find(|c: char| !is_name_char(c)).unwrap_or(bare.len());
+            (&bare[..end], end + 1)
+        } else if cfg!(windows) {
+            let body = &tail[1..];
+            match body.find('%') {
+                Some(end) if end > 0 && !body[..end].contains(['\\', '/']) => (&body[..end], end + 2),
+                _ => ("", 1),
+            }
+        } else {
+            ("", 1)
+        };
+        match (!name.is_empty()).then(|| lookup(name)).flatten() {
+            Some(value) => out.push_str(&value),
+            None => out.push_str(&tail[..written]),
+        }
+        rest = &tail[written..];
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Drop `.` and resolve `..` without touching the file system, for paths
+/// that don't exist yet.
+fn normalize_lexically(path: &Path) -> PathBuf {
+    let mut out = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                if !out.pop() {
+                    out.push(component);
+                }
+            }
+            other => out.push(other),
+        }
+    }
+    out
+}
+
+/// `~`, environment variables and a relative `base_dir` (default: the
+/// working directory, see `set_cwd`) applied to a path as a user typed it, then
+/// canonicalized. A path that doesn't exist (yet) is made absolute and
+/// normalized instead.
+pub fn expand(raw: &str, base_dir: Option<&str>) -> Result<PathBuf, PeekError> {
+    let trimmed = raw.trim();
+    if trimmed.is_empty() {
+        return Err("Path is empty".into());
+    }
+    let expanded = expand_env_vars(trimmed);
+    let path = match expanded.strip_prefix('~') {
+        Some(rest) if rest.is_empty() || rest.starts_with('/') || (cfg!(windows) && rest.starts_with('\\')) => {
+            let home = home_dir().ok_or("Home directory not found")?;
+            home.join(rest.trim_start_matches(['/', '\\']))
+        }
+        _ => PathBuf::from(&expanded),
+    };
+    let absolute = if path.is_absolute() {
+        path
+    } else {
+        let base = match base_dir {
+            Some(base) => expand(base, None)?,
+            None => working_dir()?,
+        };
+        base.join(path)
+    };
+
+    match std::fs::canonicalize(&absolute) {
+        Ok(canonical) => Ok(strip_verbatim_prefix(canonical)),
+        Err(_) => Ok(normalize_lexically(&absolute)),
+    }
+}
+
+/// Windows `canonicalize` returns `\\?\C:\...`; show the ordinary form when
+/// it means the same thing.
+fn strip_verbatim_prefix(path: PathBuf) -> PathBuf {
+    #[cfg(windows)]
+    {
+        let text = path.to_string_lossy();
+        if let Some(rest) = text.strip_prefix(r"\\?\") {
+            if rest.as_bytes().get(1) == Some(&b':') {
+                return PathBuf::from(rest);
+            }
+        }
+    }
+    path
+}
+
+/// The nearest directory at or above `start` containing one of `markers`.
+fn find_root(start: &Path, markers: &[String]) -> Option<ProjectRoot> {
+    start.ancestors().find_map(|dir| {
+        markers.iter().find(|marker| dir.join(marker).exists()).map(|marker| ProjectRoot {
+            root: dir.to_string_lossy().to_string(),
+            marker: marker.clone(),
+        })
+    })
+}
+
+// ── Commands ──────────────────────────────────────────────────────────────────
+
+/// Turn a path as a user typed it (`~/notes.txt`, `$HOME/x`,
+/// `%USERPROFILE%\x`, `../y`) into an absolute, canonical one. Relative paths
+/// resolve against `base_dir`, itself expanded, or the app's working
+/// directory. Paths that don't exist are still expanded, just not resolved
+/// through symlinks.
+#[tauri::command]
+pub fn expand_path(path: String, base_dir: Option<String>) -> Result<String, PeekError> {
+    Ok(expand(&path, base_dir.as_deref())?.to_string_lossy().to_string())
+}
+
+/// An environment variable of the app's process, or `None` if it's unset or
+/// not valid Unicode.
+#[tauri::command]
+pub fn get_env_var(name: String) -> Result<Option<String>, PeekError> {
+    Ok(std::env::var(name).ok())
+}
+
+/// The project a file belongs to: the nearest directory at or above it that
+/// contains one of `markers` (default `.git`, `.hg`, `.svn`, `Cargo.toml`,
+/// `package.json`), with the marker found. Earlier markers win within one
+/// directory; a nearer directory wins over an earlier marker. `None` if no
+/// directory up to the file system root has one.
+#[tauri::command]
+pub fn find_project_root(path: String, markers: Option<Vec<String>>) -> Result<Option<ProjectRoot>, PeekError> {
+    let markers = markers.unwrap_or_else(|| DEFAULT_ROOT_MARKERS.iter().map(|marker| marker.to_string()).collect());
+    let path = resolve_path(&path);
+    let start = std::fs::canonicalize(&path).unwrap_or(path);
+    let start = if start.is_dir() { start.as_path() } else { crate::parent_dir(&start) };
+    Ok(find_root(start, &markers))
+}
+
+/// Set the directory relative paths resolve against, e.g. the open project,
+/// so the frontend can pass `notes.md` instead of an absolute path. Every
+/// file command honors it: absolute paths are always used as given, relative
+/// ones are joined to this, and per-file state (line indexes, locks,
+/// watchers) is keyed by the result, so changing it doesn't make `notes.md`
+/// name another file's state. The process's own working directory, which
+/// terminals and commands start in by default, is left alone. `path` is
+/// expanded like `expand_path`. Returns the new directory.
+#[tauri::command]
+pub fn set_cwd(path: String) -> Result<String, PeekError> {
+    let dir = expand(&path, None)?;
+    if !dir.is_dir() {
+        return Err(format!("Not a directory: {}", dir.display()).into());
+    }
+    *WORKING_DIR.lock()? = Some(dir.clone());
+    Ok(dir.to_string_lossy().to_string())
+}
+
+/// The directory relative paths currently resolve against (see `set_cwd`).
+#[tauri::command]
+pub fn get_cwd() -> Result<String, PeekError> {
+    Ok(working_dir()?.to_string_lossy().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn deep(prefix: &str) -> String {
+        format!("{}{}file.txt", prefix, "node_modules\\pkg\\".repeat(20))
+    }
+
+    #[test]
+    fn short_paths_stay_as_they_are() {
+        assert_eq!(long_path_form(r"C:\Users\me\notes.txt"), None);
+        assert_eq!(long_path_form(r"\\server\share\notes.txt"), None);
+    }
+
+    #[test]
+    fn long_drive_paths_get_the_verbatim_prefix() {
+        let path = deep(r"C:\project\");
+        assert_eq!(long_path_form(&path), Some(format!(r"\\?\{}", path)));
+    }
+
+    #[test]
+    fn long_share_paths_get_the_unc_prefix() {
+        let path = deep(r"\\server\share\");
+        assert_eq!(long_path_form(&path), Some(format!(r"\\?\UNC\{}", &path[2..])));
+    }
+
+    #[test]
+    fn verbatim_paths_are_left_alone() {
+        assert_eq!(long_path_form(&deep(r"\\?\C:\project\")), None);
+        assert_eq!(long_path_form(&deep(r"\\?\UNC\server\share\")), None);
+    }
+
+    /// Puts back the working directory a test replaced, even if it fails.
+    struct RestoreWorkingDir(Option<PathBuf>);
+
+    impl Drop for RestoreWorkingDir {
+        fn drop(&mut self) {
+            if let Ok(mut dir) = WORKING_DIR.lock() {
+                *dir = self.0.take();
+            }
+        }
+    }
+
+    #[test]
+    fn relative_paths_resolve_against_the_working_dir() {
+        let _restore = RestoreWorkingDir(WORKING_DIR.lock().unwrap().clone());
+        let process_dir = std::env::current_dir().unwrap();
+        let dir = PathBuf::from(set_cwd(std::env::temp_dir().to_string_lossy().to_string()).unwrap());
+        assert_eq!(resolve_path("notes.md"), dir.join("notes.md"));
+        assert_eq!(get_cwd().unwrap(), dir.to_string_lossy());
+        assert_eq!(std::env::current_dir().unwrap(), process_dir);
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn resolve_path_prefixes_long_paths() {
+        let path = deep(r"C:\project\").replace('\\', "/");
+        let resolved = resolve_path(&path).to_string_lossy().into_owned();
+        assert!(resolved.starts_with(r"\\?\C:\project\"), "{}", resolved);
+        assert!(!resolved.contains('/'));
+        assert_eq!(resolve_path(r"C:\short.txt"), PathBuf::from(r"C:\short.txt"));
+    }
+}
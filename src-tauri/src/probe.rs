@@ -0,0 +1,240 @@
+use std::io::Read;
+use std::path::Path;
+
+use serde::Serialize;
+
+use crate::error::{AtPath, PeekError};
+
+/// Language ids (as the highlighter names them) by extension.
+const LANGUAGES: &[(&str, &str)] = &[
+    ("js", "javascript"), ("mjs", "javascript"), ("cjs", "javascript"), ("jsx", "jsx"),
+    ("ts", "typescript"), ("tsx", "tsx"),
+    ("py", "python"), ("rb", "ruby"), ("rs", "rust"), ("go", "go"),
+    ("c", "c"), ("cpp", "cpp"), ("h", "cpp"), ("hpp", "cpp"),
+    ("java", "java"), ("kt", "kotlin"), ("swift", "swift"), ("php", "php"),
+    ("html", "html"), ("css", "css"), ("scss", "scss"), ("less", "less"),
+    ("sql", "sql"), ("sh", "bash"), ("bash", "bash"), ("zsh", "bash"),
+    ("yaml", "yaml"), ("yml", "yaml"), ("json", "json"), ("toml", "toml"),
+    ("xml", "xml"), ("md", "markdown"), ("markdown", "markdown"),
+    ("dockerfile", "dockerfile"),
+];
+
+/// Files recognized by name alone.
+const LANGUAGE_FILE_NAMES: &[(&str, &str)] = &[
+    ("dockerfile", "dockerfile"), ("makefile", "makefile"), ("gnumakefile", "makefile"),
+];
+
+/// Interpreters in a `#!` line.
+const SHEBANG_LANGUAGES: &[(&str, &str)] = &[
+    ("python", "python"), ("node", "javascript"), ("ruby", "ruby"), ("perl", "perl"),
+    ("bash", "bash"), ("zsh", "bash"), ("sh", "bash"),
+];
+
+/// Extensions by file-tree category, beyond the source languages above and
+/// `IMAGE_EXTENSIONS`. Checked before `LANGUAGES`, so `md` is markup and
+/// `yaml` config even though they're highlighted too.
+const MARKUP_EXTENSIONS: &[&str] = &["html", "htm", "xhtml", "xml", "md", "markdown", "rst", "adoc", "tex"];
+const CONFIG_EXTENSIONS: &[&str] = &["yaml", "yml", "toml", "ini", "cfg", "conf", "properties", "env", "lock"];
+const DATA_EXTENSIONS: &[&str] = &["json", "jsonl", "ndjson", "csv", "tsv", "log", "parquet", "sqlite", "db"];
+const ARCHIVE_EXTENSIONS: &[&str] = &["zip", "tar", "gz", "tgz", "bz2", "xz", "zst", "7z", "rar", "jar"];
+const DOCUMENT_EXTENSIONS: &[&str] = &["txt", "pdf", "doc", "docx", "odt", "rtf", "xls", "xlsx", "ppt", "pptx", "epub"];
+
+/// Config files known by name, whatever their extension says.
+const CONFIG_FILE_NAMES: &[&str] = &[
+    ".gitignore", ".gitattributes", ".gitmodules", ".editorconfig", ".env", ".npmrc", ".prettierrc",
+    ".eslintrc", "package.json", "tsconfig.json", "jsconfig.json", "cargo.toml", "pyproject.toml",
+];
+
+/// What kind of file something is, for the file tree's icons.
+#[derive(Serialize, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum FileCategory {
+    SourceCode,
+    Markup,
+    Config,
+    Data,
+    Image,
+    Archive,
+    Binary,
+    Document,
+}
+
+#[derive(Serialize)]
+pub struct FileClassification {
+    category: FileCategory,
+    /// Highlighter language id, as `probe_file` reports it; "text" if none.
+    language: String,
+}
+
+#[derive(Serialize)]
+pub struct ProbeResponse {
+    kind: String, // "text" | "binary" | "image"
+    file_name: String,
+    extension: String,
+    size: u64,
+    mtime: u64, // Unix timestamp (seconds)
+    /// For text only: WHATWG encoding name and "lf" | "crlf", from the head.
+    encoding: Option<String>,
+    line_ending: Option<String>,
+    language: String,
+    /// Open with `index_file` + `read_lines` rather than `read_file_content`.
+    is_large_file: bool,
+    /// For `.gz` files: the other fields describe the decompressed content.
+    compressed: bool,
+}
+
+// ── Helpers ───────────────────────────────────────────────────────────────────
+
+/// Language from the file name, its extension, or failing those a shebang.
+fn detect_language(file_name: &str, extension: &str, head: &[u8]) -> String {
+    let lookup = |table: &[(&str, &str)], key: &str| {
+        table.iter().find(|(k, _)| *k == key).map(|(_, language)| language.to_string())
+    };
+    if let Some(language) = lookup(LANGUAGE_FILE_NAMES, &file_name.to_lowercase()) {
+        return language;
+    }
+    if let Some(language) = lookup(LANGUAGES, extension) {
+        return language;
+    }
+    if let Some(shebang) = head.strip_prefix(b"#!") {
+        let line = String::from_utf8_lossy(shebang.split(|&b| b == b'\n').next().unwrap_or_default());
+        // `#!/usr/bin/env python3` names the interpreter last, `#!/bin/sh -e` first
+        let interpreter = line
+            .split_whitespace()
+            .map(|word| word.rsplit('/').next().unwrap_or(word))
+            .find(|word| *word != "env" && !word.starts_with('-'))
+            .unwrap_or_default()
+            .trim_end_matches(|c: char| c.is_ascii_digit() || c == '.');
+        if let Some(language) = lookup(SHEBANG_LANGUAGES, interpreter) {
+            return language;
+        }
+    }
+    "text".to_string()
+}
+
+/// Dominant line ending of a sample, by the same rule as the line index.
+fn sample_line_ending(sample: &[u8]) -> String {
+    let newlines = memchr::memchr_iter(b'\n', sample).count();
+    let crlf_lines = memchr::memmem::find_iter(sample, b"\r\n").count();
+    crate::line_ending_name(crate::is_mostly_crlf(crlf_lines, newlines))
+}
+
+/// Category from the file name and extension, falling back to the head of the
+/// content: NUL bytes mean binary, a shebang source code, anything else
+/// plain text (a document).
+fn categorize(file_name: &str, extension: &str, head: &[u8]) -> FileCategory {
+    let name = file_name.to_lowercase();
+    if CONFIG_FILE_NAMES.contains(&name.as_str()) || name.starts_with(".env.") {
+        return FileCategory::Config;
+    }
+    if LANGUAGE_FILE_NAMES.iter().any(|(known, _)| *known == name) {
+        return FileCategory::SourceCode;
+    }
+    if crate::IMAGE_EXTENSIONS.contains(&extension) {
+        FileCategory::Image
+    } else if ARCHIVE_EXTENSIONS.contains(&extension) {
+        FileCategory::Archive
+    } else if MARKUP_EXTENSIONS.contains(&extension) {
+        FileCategory::Markup
+    } else if CONFIG_EXTENSIONS.contains(&extension) {
+        FileCategory::Config
+    } else if DATA_EXTENSIONS.contains(&extension) {
+        FileCategory::Data
+    } else if DOCUMENT_EXTENSIONS.contains(&extension) {
+        FileCategory::Document
+    } else if LANGUAGES.iter().any(|(known, _)| *known == extension) {
+        FileCategory::SourceCode
+    } else if crate::looks_binary(head, None) {
+        FileCategory::Binary
+    } else if detect_language(file_name, extension, head) != "text" {
+        FileCategory::SourceCode
+    } else {
+        FileCategory::Document
+    }
+}
+
+fn probe(path: String) -> Result<ProbeResponse, PeekError> {
+    let file_path = Path::new(&path);
+    let metadata = crate::regular_file_metadata(file_path).at(file_path)?;
+    let file_name = file_path.file_name().and_then(|n| n.to_str()).unwrap_or("unknown").to_string();
+    let extension = file_path.extension().and_then(|e| e.to_str()).unwrap_or("").to_lowercase();
+    let compressed = extension == "gz";
+
+    // Decide on the same bytes `read_file_content` would look at
+    let mut head = Vec::new();
+    let size_hint = if compressed {
+        // Only read far enough to tell whether it's over the large-file threshold
+        let mut decoder = crate::open_gzip(file_path)?;
+        (&mut decoder).take(crate::LARGE_FILE_THRESHOLD + 1).read_to_end(&mut head)?;
+        head.len() as u64
+    } else {
+        let file = std::fs::File::open(file_path).at(file_path)?;
+        file.take(crate::encoding::SAMPLE_BYTES as u64).read_to_end(&mut head)?;
+        metadata.len()
+    };
+    // Language and encoding of `app.log.gz` are those of `app.log`
+    let inner_name = if compressed { file_name.strip_suffix(".gz").unwrap_or(&file_name) } else { &file_name };
+    let inner_extension = Path::new(inner_name).extension().and_then(|e| e.to_str()).unwrap_or("").to_lowercase();
+
+    let is_image = !compressed && crate::IMAGE_EXTENSIONS.contains(&extension.as_str());
+    let is_binary = !is_image && crate::looks_binary(&head, None);
+    let kind = if is_image { "image" } else if is_binary { "binary" } else { "text" };
+    let text = kind == "text";
+    let sample = &head[..head.len().min(crate::encoding::SAMPLE_BYTES)];
+
+    Ok(ProbeResponse {
+        kind: kind.to_string(),
+        language: if text { detect_language(inner_name, &inner_extension, sample) } else { "text".to_string() },
+        encoding: text.then(|| crate::encoding::detect(sample).name().to_string()),
+        line_ending: text.then(|| sample_line_ending(sample)),
+        is_large_file: text && size_hint > crate::LARGE_FILE_THRESHOLD,
+        file_name,
+        extension,
+        size: metadata.len(),
+        mtime: crate::unix_mtime(&metadata),
+        compressed,
+    })
+}
+
+// ── Commands ──────────────────────────────────────────────────────────────────
+
+/// Everything the UI needs to decide how to open a file, without its content:
+/// kind, size, encoding, line ending, language and whether to take the
+/// large-file path. Reads at most the head of the file (for `.gz`, up to the
+/// large-file threshold of decompressed data). Bounded by `timeout_ms` like
+/// `read_file_content`.
+#[tauri::command]
+pub async fn probe_file(path: String, timeout_ms: Option<u64>) -> Result<ProbeResponse, PeekError> {
+    let path = crate::paths::resolve(&path);
+    tauri::async_runtime::spawn_blocking(move || match timeout_ms {
+        Some(ms) => crate::with_timeout(ms, move || probe(path)),
+        None => probe(path),
+    })
+    .await?
+}
+
+/// Classify a file for the file tree: a category (source code, markup,
+/// config, data, image, archive, binary or document) from its name and
+/// extension, sniffing the head of the file only when those don't say, plus
+/// its highlighter language. Gives the UI the same view of a file as the rest
+/// of the backend.
+#[tauri::command]
+pub fn classify_file(path: String) -> Result<FileClassification, PeekError> {
+    let path = crate::paths::resolve(&path);
+    let file_path = Path::new(&path);
+    crate::regular_file_metadata(file_path).at(file_path)?;
+    let file_name = file_path.file_name().and_then(|n| n.to_str()).unwrap_or("unknown");
+    let extension = file_path.extension().and_then(|e| e.to_str()).unwrap_or("").to_lowercase();
+
+    let mut head = Vec::new();
+    std::fs::File::open(file_path)
+        .at(file_path)?
+        .take(crate::encoding::SAMPLE_BYTES as u64)
+        .read_to_end(&mut head)?;
+    let category = categorize(file_name, &extension, &head);
+    let language = match category {
+        FileCategory::Image | FileCategory::Archive | FileCategory::Binary => "text".to_string(),
+        _ => detect_language(file_name, &extension, &head),
+    };
+    Ok(FileClassification { category, language })
+}
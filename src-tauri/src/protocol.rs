@@ -0,0 +1,156 @@
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::Path;
+
+use tauri::http::{header, Request, Response, StatusCode};
+
+use crate::error::PeekError;
+
+/// Custom scheme that streams image files to the webview.
+pub const IMAGE_SCHEME: &str = "peek-img";
+
+/// Most bytes sent in one response. Bigger images go out as a `206` for the
+/// first chunk and the webview asks for the rest with `Range` requests.
+const MAX_RESPONSE_BYTES: u64 = 8 * 1024 * 1024;
+
+// ── URLs ──────────────────────────────────────────────────────────────────────
+
+/// URL the webview can load to stream `path` through the image protocol
+/// (same shape as the JS `convertFileSrc`).
+pub fn image_url(path: &str) -> String {
+    let encoded = percent_encode(path);
+    if cfg!(any(windows, target_os = "android")) {
+        format!("http://{}.localhost/{}", IMAGE_SCHEME, encoded)
+    } else {
+        format!("{}://localhost/{}", IMAGE_SCHEME, encoded)
+    }
+}
+
+/// Encode like `encodeURIComponent`: everything but unreserved characters.
+fn percent_encode(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for byte in s.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9'
+            | b'-' | b'_' | b'.' | b'!' | b'~' | b'*' | b'\'' | b'(' | b')' => out.push(byte as char),
+            _ => out.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    out
+}
+
+/// Undo `%XX` escapes, keeping malformed ones as they are.
+pub(crate) fn percent_decode(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' && i + 2 < bytes.len() {
+            let hex = std::str::from_utf8(&bytes[i + 1..i + 3]).unwrap_or("");
+            if let Ok(byte) = u8::from_str_radix(hex, 16) {
+                decoded.push(byte);
+                i += 3;
+                continue;
+            }
+        }
+        decoded.push(bytes[i]);
+        i += 1;
+    }
+    String::from_utf8_lossy(&decoded).to_string()
+}
+
+// ── Serving ───────────────────────────────────────────────────────────────────
+
+fn mime_for_extension(extension: &str) -> Option<&'static str> {
+    Some(match extension {
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "bmp" => "image/bmp",
+        "webp" => "image/webp",
+        "ico" => "image/x-icon",
+        "svg" => "image/svg+xml",
+        _ => return None,
+    })
+}
+
+/// Parse a single `bytes=start-end` range (also `start-` and `-suffix`).
+/// Returns an inclusive byte range within the file.
+fn parse_range(value: &str, file_size: u64) -> Option<(u64, u64)> {
+    let spec = value.strip_prefix("bytes=")?.split(',').next()?.trim();
+    let (start, end) = spec.split_once('-')?;
+    let last = file_size.checked_sub(1)?;
+
+    let (start, end) = if start.is_empty() {
+        let suffix: u64 = end.parse().ok()?;
+        (file_size.saturating_sub(suffix), last)
+    } else {
+        let start: u64 = start.parse().ok()?;
+        let end = if end.is_empty() { last } else { end.parse::<u64>().ok()?.min(last) };
+        (start, end)
+    };
+
+    (start <= end).then_some((start, end))
+}
+
+/// Serve an image file, honoring `Range` requests. Only image types are
+/// served, so the scheme can't be used to read arbitrary files, and only
+/// regular files, so a FIFO named like an image can't block the handler.
+pub fn handle_image_request(request: &Request<Vec<u8>>) -> Response<Vec<u8>> {
+    serve_image(request).unwrap_or_else(|status| {
+        let mut response = Response::new(Vec::new());
+        *response.status_mut() = status;
+        response
+    })
+}
+
+fn serve_image(request: &Request<Vec<u8>>) -> Result<Response<Vec<u8>>, StatusCode> {
+    let path = crate::paths::resolve_path(&percent_decode(request.uri().path().trim_start_matches('/')));
+    let extension = Path::new(&path)
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or("")
+        .to_lowercase();
+    let mime = mime_for_extension(&extension).ok_or(StatusCode::FORBIDDEN)?;
+
+    let file_size = crate::regular_file_metadata(&path)
+        .map_err(|e| match e {
+            PeekError::NotFound { .. } => StatusCode::NOT_FOUND,
+            _ => StatusCode::FORBIDDEN,
+        })?
+        .len();
+    let mut file = File::open(&path).map_err(|_| StatusCode::NOT_FOUND)?;
+
+    let range = request.headers().get(header::RANGE).and_then(|v| v.to_str().ok());
+    let builder = Response::builder()
+        .header(header::CONTENT_TYPE, mime)
+        .header(header::ACCEPT_RANGES, "bytes");
+
+    let (start, end) = match range {
+        Some(range) => parse_range(range, file_size).ok_or(StatusCode::RANGE_NOT_SATISFIABLE)?,
+        None if file_size <= MAX_RESPONSE_BYTES => {
+            let mut body = Vec::with_capacity(file_size as usize);
+            file.read_to_end(&mut body).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+            return builder
+                .status(StatusCode::OK)
+                .header(header::CONTENT_LENGTH, body.len())
+                .body(body)
+                .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR);
+        }
+        None => (0, file_size - 1),
+    };
+    let end = end.min(start + MAX_RESPONSE_BYTES - 1);
+
+    file.seek(SeekFrom::Start(start)).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let mut body = Vec::new();
+    file.take(end - start + 1)
+        .read_to_end(&mut body)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    builder
+        .status(StatusCode::PARTIAL_CONTENT)
+        .header(header::CONTENT_RANGE, format!("bytes {}-{}/{}", start, end, file_size))
+        .header(header::CONTENT_LENGTH, body.len())
+        .body(body)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
+}
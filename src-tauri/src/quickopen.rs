@@ -0,0 +1,286 @@
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+use notify::event::{EventKind, ModifyKind};
+use notify::{RecommendedWatcher, RecursiveMode, Watcher};
+use serde::Serialize;
+use tauri::{AppHandle, Manager};
+
+use crate::error::PeekError;
+
+const DEFAULT_MAX_RESULTS: usize = 50;
+/// Past this many files the list is cut off; quick-open over more is noise.
+const MAX_LISTED_FILES: usize = 500_000;
+/// Roots whose file lists (and recursive watchers) are kept; switching
+/// between more projects than this drops the least recently used.
+const MAX_CACHED_ROOTS: usize = 4;
+
+// Scoring, in the spirit of fzf/Skim: matches at word starts and runs of
+// consecutive matches count most, gaps cost a little per skipped character.
+const SCORE_MATCH: i64 = 16;
+const BONUS_BOUNDARY: i64 = 8;
+const BONUS_CAMEL: i64 = 7;
+const BONUS_CONSECUTIVE: i64 = 5;
+/// Per matched character in the file name rather than its directories.
+const BONUS_FILE_NAME: i64 = 2;
+const PENALTY_GAP: i64 = 1;
+
+// ── Types ─────────────────────────────────────────────────────────────────────
+
+#[derive(Serialize)]
+pub struct FuzzyMatch {
+    /// Relative to the root, `/`-separated on every platform.
+    path: String,
+    score: i64,
+    /// Matched characters of `path`, as indices in Unicode scalar values.
+    positions: Vec<usize>,
+}
+
+/// A root's file list and the watcher that marks it stale.
+struct FileList {
+    files: Arc<Vec<String>>,
+    stale: Arc<AtomicBool>,
+    last_used: Instant,
+    _watcher: RecommendedWatcher,
+}
+
+/// Managed state: file lists for quick-open by root and gitignore setting,
+/// reused until a file under the root is created, removed or renamed. At
+/// most `MAX_CACHED_ROOTS` are kept, each with a watcher.
+pub struct FileLists(Mutex<HashMap<(String, bool), FileList>>);
+
+impl FileLists {
+    pub fn new() -> Self {
+        FileLists(Mutex::new(HashMap::new()))
+    }
+
+    /// Watchers held, one per cached list.
+    pub fn count(&self) -> usize {
+        self.0.lock().map_or(0, |lists| lists.len())
+    }
+}
+
+// ── Helpers ───────────────────────────────────────────────────────────────────
+
+fn list_files(root: &Path, respect_gitignore: bool) -> Vec<String> {
+    let walker = ignore::WalkBuilder::new(root)
+        .hidden(false)
+        .git_ignore(respect_gitignore)
+        .git_global(respect_gitignore)
+        .git_exclude(respect_gitignore)
+        .ignore(respect_gitignore)
+        .filter_entry(|entry| entry.file_name() != ".git")
+        .build();
+
+    let mut files = Vec::new();
+    for entry in walker.flatten() {
+        if !entry.file_type().is_some_and(|kind| kind.is_file()) {
+            continue;
+        }
+        let relative = entry.path().strip_prefix(root).unwrap_or(entry.path());
+        files.push(relative.components().map(|c| c.as_os_str().to_string_lossy()).collect::<Vec<_>>().join("/"));
+        if files.len() >= MAX_LISTED_FILES {
+            break;
+        }
+    }
+    files.sort();
+    files
+}
+
+/// A watcher that sets `stale` when the set of files under `root` changes.
+/// Content changes don't affect the list and are ignored, as is `.git`.
+fn watch_for_changes(root: &Path, stale: Arc<AtomicBool>) -> Result<RecommendedWatcher, PeekError> {
+    let mut watcher = notify::recommended_watcher(move |result: notify::Result<notify::Event>| {
+        let Ok(event) = result else {
+            // Dropped events (queue overflow) may have been anything
+            stale.store(true, Ordering::Relaxed);
+            return;
+        };
+        let changes_listing = matches!(
+            event.kind,
+            EventKind::Create(_) | EventKind::Remove(_) | EventKind::Modify(ModifyKind::Name(_))
+        );
+        let outside_git = event.paths.iter().any(|path| !path.components().any(|c| c.as_os_str() == ".git"));
+        if changes_listing && outside_git {
+            stale.store(true, Ordering::Relaxed);
+        }
+    })
+    .map_err(|e| e.to_string())?;
+    watcher.watch(root, RecursiveMode::Recursive).map_err(|e| e.to_string())?;
+    Ok(watcher)
+}
+
+/// The cached file list for a root, walking the tree if there is none or it's
+/// stale. The watcher starts before the walk so nothing slips between them.
+/// Without a watcher (e.g. out of inotify watches) nothing is cached. A stale
+/// list is dropped with its watcher, and caching a new one past
+/// `MAX_CACHED_ROOTS` drops the least recently used.
+fn files_for(app: &AppHandle, root: &str, respect_gitignore: bool) -> Result<Arc<Vec<String>>, PeekError> {
+    let lists = app.state::<FileLists>();
+    let key = (root.to_string(), respect_gitignore);
+    {
+        let mut lists = lists.0.lock()?;
+        if let Some(list) = lists.get_mut(&key) {
+            if !list.stale.load(Ordering::Relaxed) {
+                list.last_used = Instant::now();
+                return Ok(list.files.clone());
+            }
+        }
+        lists.retain(|_, list| !list.stale.load(Ordering::Relaxed));
+    }
+
+    let stale = Arc::new(AtomicBool::new(false));
+    let watcher = watch_for_changes(Path::new(root), stale.clone());
+    let files = Arc::new(list_files(Path::new(root), respect_gitignore));
+    let mut lists = lists.0.lock()?;
+    match watcher {
+        Ok(watcher) => {
+            lists.insert(key, FileList { files: files.clone(), stale, last_used: Instant::now(), _watcher: watcher });
+            while lists.len() > MAX_CACHED_ROOTS {
+                let oldest = lists.iter().min_by_key(|(_, list)| list.last_used).map(|(key, _)| key.clone());
+                let Some(oldest) = oldest else { break };
+                lists.remove(&oldest);
+            }
+        }
+        Err(_) => {
+            lists.remove(&key);
+        }
+    }
+    Ok(files)
+}
+
+fn is_separator(c: char) -> bool {
+    matches!(c, '/' | '\\' | '_' | '-' | '.' | ' ')
+}
+
+/// Best alignment of `query` as a subsequence of `candidate`, or `None` if it
+/// isn't one. Dynamic programming over (query char, candidate position), so
+/// an early weak match doesn't hide a better one later ("fb" in
+/// "foo/bar/foobar.rs" picks the file name). Case-insensitive unless
+/// `case_sensitive`.
+fn fuzzy_score(candidate: &str, query: &[char], case_sensitive: bool) -> Option<(i64, Vec<usize>)> {
+    let chars: Vec<char> = candidate.chars().collect();
+    let fold = |c: char| if case_sensitive { c } else { c.to_lowercase().next().unwrap_or(c) };
+    let folded: Vec<char> = chars.iter().map(|&c| fold(c)).collect();
+
+    // Cheap rejection before the quadratic part
+    let mut rest = folded.iter();
+    if !query.iter().all(|&q| rest.any(|&c| c == q)) {
+        return None;
+    }
+
+    let (n, m) = (chars.len(), query.len());
+    let name_start = chars.iter().rposition(|&c| c == '/').map_or(0, |i| i + 1);
+    let bonus = |j: usize| {
+        let base = SCORE_MATCH + if j >= name_start { BONUS_FILE_NAME } else { 0 };
+        match j.checked_sub(1).map(|p| chars[p]) {
+            None => base + BONUS_BOUNDARY,
+            Some(prev) if is_separator(prev) => base + BONUS_BOUNDARY,
+            Some(prev) if prev.is_lowercase() && chars[j].is_uppercase() => base + BONUS_CAMEL,
+            _ => base,
+        }
+    };
+
+    // score[i][j]: best with query[i] matched at j; from[i][j]: where query[i - 1] was
+    let mut score = vec![vec![i64::MIN; n]; m];
+    let mut from = vec![vec![0usize; n]; m];
+    for j in 0..n {
+        if folded[j] == query[0] {
+            score[0][j] = bonus(j);
+        }
+    }
+    for i in 1..m {
+        // Best of score[i - 1][k] + PENALTY_GAP * k over k < j - 1, for a gap
+        // of j - k - 1 characters
+        let mut best_gap: Option<(i64, usize)> = None;
+        for j in 1..n {
+            if j >= 2 && score[i - 1][j - 2] != i64::MIN {
+                let candidate = score[i - 1][j - 2] + PENALTY_GAP * (j - 2) as i64;
+                if best_gap.is_none_or(|(best, _)| candidate > best) {
+                    best_gap = Some((candidate, j - 2));
+                }
+            }
+            if folded[j] != query[i] {
+                continue;
+            }
+            let consecutive = (score[i - 1][j - 1] != i64::MIN).then(|| (score[i - 1][j - 1] + BONUS_CONSECUTIVE, j - 1));
+            let gapped = best_gap.map(|(value, k)| (value - PENALTY_GAP * (j - 1) as i64, k));
+            let best = match (consecutive, gapped) {
+                (Some(a), Some(b)) => Some(if a.0 >= b.0 { a } else { b }),
+                (a, b) => a.or(b),
+            };
+            if let Some((value, k)) = best {
+                score[i][j] = value + bonus(j);
+                from[i][j] = k;
+            }
+        }
+    }
+
+    let (mut j, &total) = score[m - 1].iter().enumerate().filter(|(_, &s)| s != i64::MIN).max_by_key(|(_, &s)| s)?;
+    let mut positions = vec![0; m];
+    for i in (0..m).rev() {
+        positions[i] = j;
+        j = from[i][j];
+    }
+    Some((total, positions))
+}
+
+// ── Commands ──────────────────────────────────────────────────────────────────
+
+/// Quick-open: files under `root` whose relative path fuzzily matches `query`
+/// (its characters in order, not necessarily together), best first, with
+/// the matched positions for highlighting. Word starts, camelCase humps,
+/// runs and the file name score higher; ties go to the shorter path. The
+/// query is case-insensitive unless it has an uppercase letter. With
+/// `respect_gitignore` (default on) ignored files are left out; `.git`
+/// always is. The file list is cached per root until a file is created,
+/// removed or renamed under it. An empty query lists files in path order.
+#[tauri::command]
+pub async fn fuzzy_find_files(
+    root: String,
+    query: String,
+    max_results: Option<usize>,
+    respect_gitignore: Option<bool>,
+    app: AppHandle,
+) -> Result<Vec<FuzzyMatch>, PeekError> {
+    let root = crate::paths::resolve(&root);
+    if !Path::new(&root).is_dir() {
+        return Err(format!("Not a directory: {}", root).into());
+    }
+    let max_results = max_results.unwrap_or(DEFAULT_MAX_RESULTS);
+    let found = tauri::async_runtime::spawn_blocking(move || -> Result<Vec<FuzzyMatch>, PeekError> {
+        let files = files_for(&app, &root, respect_gitignore.unwrap_or(true))?;
+        let query: Vec<char> = query.chars().filter(|c| !c.is_whitespace()).collect();
+        if query.is_empty() {
+            return Ok(files
+                .iter()
+                .take(max_results)
+                .map(|path| FuzzyMatch { path: path.clone(), score: 0, positions: Vec::new() })
+                .collect());
+        }
+        let case_sensitive = query.iter().any(|c| c.is_uppercase());
+
+        let mut matches: Vec<FuzzyMatch> = files
+            .iter()
+            .filter_map(|path| {
+                let (score, positions) = fuzzy_score(path, &query, case_sensitive)?;
+                Some(FuzzyMatch { path: path.clone(), score, positions })
+            })
+            .collect();
+        let order = |a: &FuzzyMatch, b: &FuzzyMatch| {
+            b.score.cmp(&a.score).then(a.path.len().cmp(&b.path.len())).then_with(|| a.path.cmp(&b.path))
+        };
+        if matches.len() > max_results && max_results > 0 {
+            matches.select_nth_unstable_by(max_results - 1, order);
+        }
+        matches.truncate(max_results);
+        matches.sort_by(order);
+        Ok(matches)
+    })
+    .await
+    ;
+    found?
+}
@@ -0,0 +1,100 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+
+use crate::error::PeekError;
+
+const RECENT_FILES_NAME: &str = "recent_files.json";
+const MAX_RECENT_FILES: usize = 20;
+
+// ── Types ─────────────────────────────────────────────────────────────────────
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct RecentFile {
+    path: String,
+    opened_at: u64, // Unix timestamp (seconds)
+}
+
+/// Managed state: the recent-files list, loaded from disk on first use.
+/// The mutex also serializes read-modify-write cycles of the JSON store.
+pub struct RecentFiles(Mutex<Option<Vec<RecentFile>>>);
+
+impl RecentFiles {
+    pub fn new() -> Self {
+        RecentFiles(Mutex::new(None))
+    }
+}
+
+// ── Helpers ───────────────────────────────────────────────────────────────────
+
+fn store_path(app: &AppHandle) -> Result<PathBuf, PeekError> {
+    let dir = app.path().app_config_dir()?;
+    Ok(dir.join(RECENT_FILES_NAME))
+}
+
+/// A missing or corrupt store just means "no recent files yet".
+fn load(path: &Path) -> Vec<RecentFile> {
+    fs::read_to_string(path)
+        .ok()
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default()
+}
+
+fn save(path: &Path, files: &[RecentFile]) -> Result<(), PeekError> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let json = serde_json::to_string_pretty(files).map_err(|e| e.to_string())?;
+    let temp_path = path.with_extension("tmp");
+    fs::write(&temp_path, json)?;
+    Ok(fs::rename(&temp_path, path)?)
+}
+
+// ── Commands ──────────────────────────────────────────────────────────────────
+
+/// Recent files, most recent first. Paths that no longer exist are dropped.
+#[tauri::command]
+pub fn get_recent_files(
+    app: AppHandle,
+    state: tauri::State<'_, RecentFiles>,
+) -> Result<Vec<RecentFile>, PeekError> {
+    let path = store_path(&app)?;
+    let mut cached = state.0.lock()?;
+    let files = cached.get_or_insert_with(|| load(&path));
+
+    let before = files.len();
+    files.retain(|f| Path::new(&f.path).exists());
+    if files.len() != before {
+        save(&path, files)?;
+    }
+
+    Ok(files.clone())
+}
+
+/// Record that a file was opened, moving it to the top of the list.
+#[tauri::command]
+pub fn add_recent_file(
+    path: String,
+    app: AppHandle,
+    state: tauri::State<'_, RecentFiles>,
+) -> Result<(), PeekError> {
+    let path = crate::paths::resolve(&path);
+    let store = store_path(&app)?;
+    let mut cached = state.0.lock()?;
+    let files = cached.get_or_insert_with(|| load(&store));
+
+    let opened_at = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+
+    files.retain(|f| f.path != path);
+    files.insert(0, RecentFile { path, opened_at });
+    files.truncate(MAX_RECENT_FILES);
+
+    save(&store, files)
+}
@@ -0,0 +1,271 @@
+use std::io::{BufRead, BufReader, Read, Write};
+use std::path::Path;
+
+use serde::Serialize;
+
+use crate::error::{AtPath, PeekError};
+use crate::lock::FileLocks;
+use crate::LineIndexCache;
+
+/// Where comments and strings start in a language, so renames can leave them
+/// alone. Strings end at the end of their line unless the quote is listed in
+/// `multiline_quotes` (JS template literals).
+struct Syntax {
+    line_comments: &'static [&'static str],
+    block_comment: Option<(&'static str, &'static str)>,
+    quotes: &'static [char],
+    multiline_quotes: &'static [char],
+}
+
+const C_LIKE: Syntax = Syntax {
+    line_comments: &["//"],
+    block_comment: Some(("/*", "*/")),
+    quotes: &['"', '\''],
+    multiline_quotes: &[],
+};
+
+/// Rust's `'` starts lifetimes more often than char literals.
+const RUST: Syntax = Syntax {
+    line_comments: &["//"],
+    block_comment: Some(("/*", "*/")),
+    quotes: &['"'],
+    multiline_quotes: &[],
+};
+
+const JAVASCRIPT: Syntax = Syntax {
+    line_comments: &["//"],
+    block_comment: Some(("/*", "*/")),
+    quotes: &['"', '\'', '`'],
+    multiline_quotes: &['`'],
+};
+
+const HASH_COMMENTS: Syntax = Syntax {
+    line_comments: &["#"],
+    block_comment: None,
+    quotes: &['"', '\''],
+    multiline_quotes: &[],
+};
+
+const SQL: Syntax = Syntax {
+    line_comments: &["--"],
+    block_comment: Some(("/*", "*/")),
+    quotes: &['\'', '"'],
+    multiline_quotes: &[],
+};
+
+// ── Types ─────────────────────────────────────────────────────────────────────
+
+#[derive(Serialize)]
+pub struct RenameResult {
+    replacements: usize,
+    /// 0-based lines that changed, ascending.
+    lines: Vec<usize>,
+}
+
+/// What the scanner is inside of when a line ends.
+enum Region {
+    Code,
+    BlockComment(&'static str),
+    Quoted(char),
+}
+
+// ── Helpers ───────────────────────────────────────────────────────────────────
+
+/// Language ids (as `probe_file` names them) or extensions.
+fn syntax_for(language: &str) -> Option<&'static Syntax> {
+    match language {
+        "rust" | "rs" => Some(&RUST),
+        "javascript" | "js" | "mjs" | "cjs" | "jsx" | "typescript" | "ts" | "tsx" => Some(&JAVASCRIPT),
+        "c" | "h" | "cpp" | "hpp" | "cc" | "cs" | "csharp" | "java" | "kotlin" | "kt" | "swift" | "go" | "php"
+        | "scala" | "dart" => Some(&C_LIKE),
+        "python" | "py" | "ruby" | "rb" | "bash" | "sh" | "zsh" | "perl" | "yaml" | "yml" | "toml"
+        | "makefile" | "dockerfile" => Some(&HASH_COMMENTS),
+        "sql" => Some(&SQL),
+        _ => None,
+    }
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+fn starts_with_at(chars: &[char], at: usize, token: &str) -> bool {
+    (at..).zip(token.chars()).all(|(i, expected)| chars.get(i) == Some(&expected))
+}
+
+/// Whether `name` occurs at `at` as a whole word.
+fn matches_word(chars: &[char], at: usize, name: &[char], case_sensitive: bool) -> bool {
+    let end = at + name.len();
+    if end > chars.len() || (end < chars.len() && is_word_char(chars[end])) {
+        return false;
+    }
+    chars[at..end].iter().zip(name).all(|(&a, &b)| {
+        a == b || (!case_sensitive && a.to_lowercase().eq(b.to_lowercase()))
+    })
+}
+
+/// Replace whole-word occurrences of `from` in one line, skipping comments
+/// and strings when `syntax` is known. `region` carries block comments and
+/// multi-line strings over to the next line.
+fn rename_in_line(
+    line: &str,
+    from: &[char],
+    to: &str,
+    case_sensitive: bool,
+    syntax: Option<&Syntax>,
+    region: &mut Region,
+) -> (String, usize) {
+    let chars: Vec<char> = line.chars().collect();
+    let mut out = String::with_capacity(line.len());
+    let mut count = 0;
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        match *region {
+            Region::BlockComment(end) => {
+                if starts_with_at(&chars, i, end) {
+                    *region = Region::Code;
+                    out.push_str(end);
+                    i += end.chars().count();
+                    continue;
+                }
+            }
+            Region::Quoted(quote) => {
+                if c == '\\' && i + 1 < chars.len() {
+                    out.push(c);
+                    out.push(chars[i + 1]);
+                    i += 2;
+                    continue;
+                }
+                if c == quote {
+                    *region = Region::Code;
+                }
+            }
+            Region::Code => {
+                if let Some(syntax) = syntax {
+                    if syntax.line_comments.iter().any(|token| starts_with_at(&chars, i, token)) {
+                        out.extend(&chars[i..]);
+                        break;
+                    }
+                    if let Some((start, end)) = syntax.block_comment.filter(|(start, _)| starts_with_at(&chars, i, start)) {
+                        *region = Region::BlockComment(end);
+                        out.push_str(start);
+                        i += start.chars().count();
+                        continue;
+                    }
+                    if syntax.quotes.contains(&c) {
+                        *region = Region::Quoted(c);
+                        out.push(c);
+                        i += 1;
+                        continue;
+                    }
+                }
+                let at_word_start = i == 0 || !is_word_char(chars[i - 1]);
+                if at_word_start && matches_word(&chars, i, from, case_sensitive) {
+                    out.push_str(to);
+                    count += 1;
+                    i += from.len();
+                    continue;
+                }
+            }
+        }
+        out.push(c);
+        i += 1;
+    }
+
+    if let Region::Quoted(quote) = *region {
+        if !syntax.is_some_and(|syntax| syntax.multiline_quotes.contains(&quote)) {
+            *region = Region::Code;
+        }
+    }
+    (out, count)
+}
+
+// ── Commands ──────────────────────────────────────────────────────────────────
+
+/// Rename an identifier throughout a file: only whole-word occurrences are
+/// replaced, so `count` never touches `counter` or `account`. With a known
+/// `language` (a `probe_file` id or an extension) occurrences inside comments
+/// and string literals are left alone; otherwise every occurrence counts.
+/// The file is streamed line by line into a temp file that replaces it, and
+/// its cached line index (if any) is rebuilt. Lines that aren't valid in the
+/// file's encoding are copied unchanged.
+#[tauri::command]
+pub fn rename_identifier(
+    path: String,
+    old_name: String,
+    new_name: String,
+    case_sensitive: Option<bool>,
+    language: Option<String>,
+    cache: tauri::State<'_, LineIndexCache>,
+    locks: tauri::State<'_, FileLocks>,
+) -> Result<RenameResult, PeekError> {
+    let path = crate::paths::resolve(&path);
+    for name in [&old_name, &new_name] {
+        if name.is_empty() || !name.chars().all(is_word_char) {
+            return Err(format!("Not an identifier: {:?}", name).into());
+        }
+    }
+    let case_sensitive = case_sensitive.unwrap_or(true);
+    let syntax = language.as_deref().map(str::to_lowercase).and_then(|language| syntax_for(&language));
+
+    let file_path = Path::new(&path);
+    let metadata = crate::regular_file_metadata(file_path).at(file_path)?;
+    let mut sample = Vec::new();
+    std::fs::File::open(file_path)
+        .at(file_path)?
+        .take(crate::encoding::SAMPLE_BYTES as u64)
+        .read_to_end(&mut sample)?;
+    let file_encoding = crate::encoding::detect(&sample);
+    if !crate::encoding::is_line_indexable(file_encoding) {
+        return Err(PeekError::unsupported_encoding(file_encoding.name()));
+    }
+    crate::ensure_disk_space(file_path, metadata.len())?;
+
+    let temp_path = file_path.with_extension("tmp");
+    let mut reader = BufReader::new(std::fs::File::open(file_path).at(file_path)?);
+    let mut dest = std::io::BufWriter::new(std::fs::File::create(&temp_path).at(&temp_path)?);
+    let from: Vec<char> = old_name.chars().collect();
+    let mut region = Region::Code;
+    let mut result = RenameResult { replacements: 0, lines: Vec::new() };
+    let mut raw = Vec::new();
+
+    let mut rewrite = || -> Result<(), PeekError> {
+        let mut line_number = 0;
+        loop {
+            raw.clear();
+            if reader.read_until(b'\n', &mut raw)? == 0 {
+                return Ok(());
+            }
+            // A BOM decodes to U+FEFF and is encoded back unchanged
+            let decoded = file_encoding.decode_without_bom_handling_and_without_replacement(&raw);
+            let renamed = decoded.map(|text| rename_in_line(&text, &from, &new_name, case_sensitive, syntax, &mut region));
+            match renamed {
+                Some((text, count)) if count > 0 => {
+                    dest.write_all(&crate::encoding::encode(&text, file_encoding)?)?;
+                    result.replacements += count;
+                    result.lines.push(line_number);
+                }
+                _ => dest.write_all(&raw)?,
+            }
+            line_number += 1;
+        }
+    };
+    let written = rewrite().and_then(|_| Ok(dest.into_inner().map_err(|e| e.into_error())?.sync_all()?));
+    if let Err(e) = written {
+        let _ = std::fs::remove_file(&temp_path);
+        return Err(e);
+    }
+    if result.replacements == 0 {
+        let _ = std::fs::remove_file(&temp_path);
+        return Ok(result);
+    }
+    locks.replace_file(&temp_path, &path)?;
+
+    let indexed = cache.0.lock()?.entries.contains_key(&path);
+    if indexed {
+        crate::index_and_cache(path, false, &cache)?;
+    }
+    Ok(result)
+}
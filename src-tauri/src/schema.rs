@@ -0,0 +1,149 @@
+use std::collections::HashMap;
+
+use serde::Serialize;
+
+use crate::error::PeekError;
+use crate::format::line_column;
+
+#[derive(Serialize)]
+pub struct SchemaError {
+    /// JSON Pointer to the offending value (`""` for the document root).
+    path: String,
+    message: String,
+    /// 1-based position of the offending value in the source.
+    line: usize,
+    column: usize,
+}
+
+// ── Position Mapping ──────────────────────────────────────────────────────────
+
+/// Walks JSON text recording the byte offset at which every value starts,
+/// keyed by its JSON Pointer. Only run on content serde_json already accepted,
+/// so it doesn't re-validate syntax.
+struct PointerScanner<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+    offsets: HashMap<String, usize>,
+}
+
+impl PointerScanner<'_> {
+    fn scan(content: &str) -> HashMap<String, usize> {
+        let mut scanner = PointerScanner { bytes: content.as_bytes(), pos: 0, offsets: HashMap::new() };
+        scanner.value(String::new());
+        scanner.offsets
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.pos < self.bytes.len() && self.bytes[self.pos].is_ascii_whitespace() {
+            self.pos += 1;
+        }
+    }
+
+    fn value(&mut self, pointer: String) {
+        self.skip_whitespace();
+        self.offsets.insert(pointer.clone(), self.pos);
+        match self.bytes.get(self.pos) {
+            Some(b'{') => self.object(pointer),
+            Some(b'[') => self.array(pointer),
+            Some(b'"') => {
+                self.string();
+            }
+            // Numbers and literals run up to the next delimiter
+            Some(_) => {
+                while self.pos < self.bytes.len()
+                    && !matches!(self.bytes[self.pos], b',' | b'}' | b']')
+                    && !self.bytes[self.pos].is_ascii_whitespace()
+                {
+                    self.pos += 1;
+                }
+            }
+            None => {}
+        }
+    }
+
+    fn object(&mut self, pointer: String) {
+        self.pos += 1; // {
+        loop {
+            self.skip_whitespace();
+            match self.bytes.get(self.pos) {
+                Some(b'"') => {
+                    let key = self.string();
+                    self.skip_whitespace();
+                    self.pos += 1; // :
+                    self.value(format!("{}/{}", pointer, key.replace('~', "~0").replace('/', "~1")));
+                }
+                Some(b',') => self.pos += 1,
+                Some(b'}') => {
+                    self.pos += 1;
+                    return;
+                }
+                _ => return,
+            }
+        }
+    }
+
+    fn array(&mut self, pointer: String) {
+        self.pos += 1; // [
+        let mut index = 0;
+        loop {
+            self.skip_whitespace();
+            match self.bytes.get(self.pos) {
+                Some(b']') => {
+                    self.pos += 1;
+                    return;
+                }
+                Some(b',') => self.pos += 1,
+                Some(_) => {
+                    self.value(format!("{}/{}", pointer, index));
+                    index += 1;
+                }
+                None => return,
+            }
+        }
+    }
+
+    /// Consume a string literal and return its decoded value (keys need the
+    /// decoded form to match the validator's pointers).
+    fn string(&mut self) -> String {
+        let start = self.pos;
+        self.pos += 1; // opening quote
+        while self.pos < self.bytes.len() {
+            match self.bytes[self.pos] {
+                b'\\' => self.pos += 2,
+                b'"' => {
+                    self.pos += 1;
+                    break;
+                }
+                _ => self.pos += 1,
+            }
+        }
+        let literal = std::str::from_utf8(&self.bytes[start..self.pos.min(self.bytes.len())]).unwrap_or("");
+        serde_json::from_str(literal).unwrap_or_default()
+    }
+}
+
+// ── Commands ──────────────────────────────────────────────────────────────────
+
+/// Validate JSON `content` against a JSON Schema (itself passed as JSON text),
+/// returning every violation with the source position of the value at fault.
+/// An empty list means the document is valid. `$ref`s are only resolved
+/// within the schema; nothing is fetched over the network.
+#[tauri::command]
+pub fn validate_json_schema(content: String, schema: String) -> Result<Vec<SchemaError>, PeekError> {
+    let schema: serde_json::Value = serde_json::from_str(&schema)
+        .map_err(|e| format!("Invalid schema: {}", e))?;
+    let validator = jsonschema::validator_for(&schema)
+        .map_err(|e| format!("Invalid schema: {}", e))?;
+    let instance: serde_json::Value = serde_json::from_str(&content)
+        .map_err(|e| PeekError::parse_error(e.line(), e.column(), e))?;
+
+    let offsets = PointerScanner::scan(&content);
+    Ok(validator
+        .iter_errors(&instance)
+        .map(|error| {
+            let path = error.instance_path.to_string();
+            let (line, column) = line_column(&content, offsets.get(&path).copied().unwrap_or(0));
+            SchemaError { path, message: error.to_string(), line, column }
+        })
+        .collect())
+}
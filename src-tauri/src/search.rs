@@ -0,0 +1,164 @@
+use std::io::{BufRead, BufReader, Read};
+use std::path::Path;
+
+use serde::Serialize;
+
+use crate::error::{AtPath, PeekError};
+
+/// Matches returned by one `search_file` call; past this the UI should narrow
+/// the query rather than page through results.
+const MAX_SEARCH_MATCHES: usize = 10_000;
+const DEFAULT_TAB_WIDTH: usize = 4;
+
+// ── Types ─────────────────────────────────────────────────────────────────────
+
+#[derive(Serialize)]
+pub struct SearchMatch {
+    line: usize,        // 0-based
+    char_column: usize, // 0-based, in Unicode scalar values
+    /// 0-based column with tabs expanded to `tab_width` stops, where the
+    /// cursor is drawn.
+    visual_column: usize,
+    length: usize, // in Unicode scalar values
+}
+
+#[derive(Serialize)]
+pub struct MatchCount {
+    total: usize,
+    /// Lines with at least one match.
+    matching_lines: usize,
+}
+
+#[derive(Serialize)]
+pub struct SearchResult {
+    matches: Vec<SearchMatch>,
+    /// Stopped at `MAX_SEARCH_MATCHES`.
+    truncated: bool,
+}
+
+// ── Helpers ───────────────────────────────────────────────────────────────────
+
+/// Display column after `prefix`, with tab stops every `tab_width` columns
+/// (the same rule as `expand_tabs`).
+fn visual_width(prefix: &str, tab_width: usize) -> usize {
+    prefix.chars().fold(0, |column, ch| {
+        if ch == '\t' {
+            column + tab_width - column % tab_width
+        } else {
+            column + 1
+        }
+    })
+}
+
+/// The regex for a find query: `query` itself with `is_regex`, else matched
+/// literally; case-insensitive unless `case_sensitive`. With `multi_line`,
+/// `^`/`$` match at every line, for text that spans lines.
+pub(crate) fn build_regex(
+    query: String,
+    is_regex: Option<bool>,
+    case_sensitive: Option<bool>,
+    multi_line: bool,
+) -> Result<regex::Regex, PeekError> {
+    let pattern = if is_regex.unwrap_or(false) {
+        query
+    } else {
+        regex::escape(&query)
+    };
+    regex::RegexBuilder::new(&pattern)
+        .case_insensitive(!case_sensitive.unwrap_or(false))
+        .multi_line(multi_line)
+        .build()
+        .map_err(|e| e.to_string().into())
+}
+
+/// Call `visit` with each decoded line of a text file (line ending removed)
+/// and its 0-based number until it returns `false`.
+pub fn for_each_line(path: &str, mut visit: impl FnMut(usize, &str) -> bool) -> Result<(), PeekError> {
+    let file_path = Path::new(path);
+    crate::regular_file_metadata(file_path).at(file_path)?;
+    let mut sample = Vec::new();
+    std::fs::File::open(file_path)
+        .at(file_path)?
+        .take(crate::encoding::SAMPLE_BYTES as u64)
+        .read_to_end(&mut sample)?;
+    let file_encoding = crate::encoding::detect(&sample);
+    if !crate::encoding::is_line_indexable(file_encoding) {
+        return Err(PeekError::unsupported_encoding(file_encoding.name()));
+    }
+
+    let mut reader = BufReader::new(std::fs::File::open(file_path).at(file_path)?);
+    let mut raw = Vec::new();
+    let mut line_number = 0;
+    loop {
+        raw.clear();
+        if reader.read_until(b'\n', &mut raw)? == 0 {
+            return Ok(());
+        }
+        let line = crate::encoding::decode(&raw, file_encoding, line_number == 0);
+        if !visit(line_number, line.trim_end_matches(['\n', '\r'])) {
+            return Ok(());
+        }
+        line_number += 1;
+    }
+}
+
+// ── Commands ──────────────────────────────────────────────────────────────────
+
+/// Find `query` (a literal, or a regex with `is_regex`) in a file, line by
+/// line. Each match carries both its character column and its visual column
+/// with tabs expanded to `tab_width` (default 4), so the editor can place the
+/// cursor in tab-indented files without redoing the expansion. Streams the
+/// file, so it works on large files too.
+#[tauri::command]
+pub fn search_file(
+    path: String,
+    query: String,
+    is_regex: Option<bool>,
+    case_sensitive: Option<bool>,
+    tab_width: Option<usize>,
+) -> Result<SearchResult, PeekError> {
+    let path = crate::paths::resolve(&path);
+    let tab_width = tab_width.unwrap_or(DEFAULT_TAB_WIDTH).max(1);
+    let re = build_regex(query, is_regex, case_sensitive, false)?;
+    let mut matches = Vec::new();
+    let mut truncated = false;
+    for_each_line(&path, |line_number, line| {
+        for m in re.find_iter(line).filter(|m| !m.is_empty()) {
+            let prefix = &line[..m.start()];
+            matches.push(SearchMatch {
+                line: line_number,
+                char_column: prefix.chars().count(),
+                visual_column: visual_width(prefix, tab_width),
+                length: m.as_str().chars().count(),
+            });
+            if matches.len() >= MAX_SEARCH_MATCHES {
+                truncated = true;
+                return false;
+            }
+        }
+        true
+    })?;
+    Ok(SearchResult { matches, truncated })
+}
+
+/// How many times `query` occurs in a file, for a "42 matches" badge while
+/// typing: the same matching as `search_file`, without building the results
+/// and without its match limit.
+#[tauri::command]
+pub fn count_matches_in_file(
+    path: String,
+    query: String,
+    is_regex: Option<bool>,
+    case_sensitive: Option<bool>,
+) -> Result<MatchCount, PeekError> {
+    let path = crate::paths::resolve(&path);
+    let re = build_regex(query, is_regex, case_sensitive, false)?;
+    let mut count = MatchCount { total: 0, matching_lines: 0 };
+    for_each_line(&path, |_, line| {
+        let found = re.find_iter(line).filter(|m| !m.is_empty()).count();
+        count.total += found;
+        count.matching_lines += usize::from(found > 0);
+        true
+    })?;
+    Ok(count)
+}
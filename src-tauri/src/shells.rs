@@ -0,0 +1,141 @@
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+
+use crate::error::PeekError;
+
+/// Shells looked up on `PATH` in addition to the platform's own list.
+#[cfg(unix)]
+const PATH_SHELLS: &[&str] = &["bash", "zsh", "fish", "pwsh", "nu"];
+
+#[derive(Serialize)]
+pub struct ShellInfo {
+    name: String,
+    /// Absolute path, for `spawn_terminal`'s `program`.
+    path: String,
+    /// Arguments to pass along with it (e.g. which WSL distro).
+    args: Vec<String>,
+    /// What `spawn_terminal` runs when no `program` is given.
+    is_default: bool,
+}
+
+// ── Helpers ───────────────────────────────────────────────────────────────────
+
+/// First match for an executable name on `PATH`.
+fn find_on_path(name: &str) -> Option<PathBuf> {
+    let path = std::env::var_os("PATH")?;
+    std::env::split_paths(&path).find_map(|dir| {
+        let candidate = dir.join(name);
+        #[cfg(windows)]
+        let candidate = candidate.with_extension("exe");
+        candidate.is_file().then_some(candidate)
+    })
+}
+
+fn shell(name: &str, path: &Path, args: Vec<String>, is_default: bool) -> ShellInfo {
+    ShellInfo {
+        name: name.to_string(),
+        path: path.to_string_lossy().to_string(),
+        args,
+        is_default,
+    }
+}
+
+/// Shells from `/etc/shells` that exist, then any well-known ones on `PATH`
+/// not already listed (under another path to the same binary).
+#[cfg(unix)]
+fn discover() -> Vec<ShellInfo> {
+    let canonical = |path: &Path| std::fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+    let default = std::env::var_os("SHELL").map(PathBuf::from);
+    let default_canonical = default.as_deref().map(canonical);
+    let mut paths: Vec<PathBuf> = std::fs::read_to_string("/etc/shells")
+        .unwrap_or_default()
+        .lines()
+        .map(str::trim)
+        .filter(|line| line.starts_with('/'))
+        .map(PathBuf::from)
+        .filter(|path| path.is_file())
+        .collect();
+    paths.extend(PATH_SHELLS.iter().filter_map(|name| find_on_path(name)));
+    if let Some(default) = &default {
+        paths.push(default.clone());
+    }
+
+    // `/bin/bash` and `/usr/bin/bash` are often the same file
+    let mut seen: Vec<PathBuf> = Vec::new();
+    let mut shells = Vec::new();
+    for path in paths {
+        let resolved = canonical(&path);
+        if seen.contains(&resolved) {
+            continue;
+        }
+        let is_default = default_canonical.as_ref() == Some(&resolved);
+        seen.push(resolved);
+        let name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+        shells.push(shell(&name, &path, Vec::new(), is_default));
+    }
+    shells
+}
+
+/// PowerShell, pwsh, cmd, Git Bash and one entry per installed WSL distro.
+#[cfg(windows)]
+fn discover() -> Vec<ShellInfo> {
+    use std::os::windows::process::CommandExt;
+    const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+
+    // portable-pty's default program is %COMSPEC%
+    let comspec = std::env::var_os("COMSPEC").map(PathBuf::from);
+    let is_default = |path: &Path| comspec.as_deref().is_some_and(|default| default == path);
+    let system_root = PathBuf::from(std::env::var_os("SystemRoot").unwrap_or_else(|| "C:\\Windows".into()));
+    let mut shells = Vec::new();
+
+    if let Some(cmd) = comspec.as_ref().filter(|path| path.is_file()) {
+        shells.push(shell("Command Prompt", cmd, Vec::new(), true));
+    }
+    let powershell = system_root.join("System32\\WindowsPowerShell\\v1.0\\powershell.exe");
+    if powershell.is_file() {
+        shells.push(shell("Windows PowerShell", &powershell, Vec::new(), is_default(&powershell)));
+    }
+    if let Some(pwsh) = find_on_path("pwsh") {
+        shells.push(shell("PowerShell", &pwsh, Vec::new(), is_default(&pwsh)));
+    }
+    for var in ["ProgramFiles", "ProgramW6432", "LOCALAPPDATA"] {
+        let Some(base) = std::env::var_os(var) else { continue };
+        let subdir = if var == "LOCALAPPDATA" { "Programs\\Git\\bin\\bash.exe" } else { "Git\\bin\\bash.exe" };
+        let bash = PathBuf::from(base).join(subdir);
+        if bash.is_file() && !shells.iter().any(|s: &ShellInfo| Path::new(&s.path) == bash.as_path()) {
+            shells.push(shell("Git Bash", &bash, vec!["--login".to_string(), "-i".to_string()], false));
+        }
+    }
+
+    // `wsl -l -q` prints distro names in UTF-16LE
+    let wsl = system_root.join("System32\\wsl.exe");
+    if wsl.is_file() {
+        let output = std::process::Command::new(&wsl)
+            .args(["-l", "-q"])
+            .creation_flags(CREATE_NO_WINDOW)
+            .output();
+        if let Ok(output) = output {
+            let units: Vec<u16> = output.stdout.chunks_exact(2).map(|b| u16::from_le_bytes([b[0], b[1]])).collect();
+            let listing = if output.status.success() { String::from_utf16_lossy(&units) } else { String::new() };
+            for distro in listing.lines() {
+                let distro = distro.trim_matches(|c: char| c.is_whitespace() || c == '\0');
+                if !distro.is_empty() {
+                    let name = format!("WSL: {}", distro);
+                    shells.push(shell(&name, &wsl, vec!["-d".to_string(), distro.to_string()], false));
+                }
+            }
+        }
+    }
+    shells
+}
+
+// ── Commands ──────────────────────────────────────────────────────────────────
+
+/// Shells installed on this machine for a terminal shell picker. Pass the
+/// chosen `path` and `args` to `spawn_terminal` as `program` and `args`.
+#[tauri::command]
+pub async fn list_available_shells() -> Result<Vec<ShellInfo>, PeekError> {
+    // Probing WSL starts a process, so keep it off the command workers
+    Ok(tauri::async_runtime::spawn_blocking(discover).await?)
+}
@@ -0,0 +1,274 @@
+/// Elements dropped together with their content: they run script or embed
+/// arbitrary HTML/documents.
+const DROPPED_ELEMENTS: &[&str] = &["script", "foreignobject", "iframe", "embed", "object"];
+
+/// Attributes that load a resource; only same-document refs and inline raster
+/// images are allowed through.
+const URL_ATTRIBUTES: &[&str] = &["href", "xlink:href", "src", "action", "formaction"];
+
+// ── Helpers ───────────────────────────────────────────────────────────────────
+
+/// Resolve character references (`&#106;`, `&#x6A;`) and XML's predefined
+/// entities the way the SVG parser will before the value is used.
+fn decode_references(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    let mut rest = value;
+    while let Some(amp) = rest.find('&') {
+        out.push_str(&rest[..amp]);
+        rest = &rest[amp..];
+        let end = rest.find(';');
+        let decoded = end.and_then(|end| match &rest[1..end] {
+            "amp" => Some('&'),
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            reference => {
+                let number = reference.strip_prefix('#')?;
+                let code = match number.strip_prefix(['x', 'X']) {
+                    Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+                    None => number.parse().ok()?,
+                };
+                char::from_u32(code)
+            }
+        });
+        match (decoded, end) {
+            (Some(c), Some(end)) => {
+                out.push(c);
+                rest = &rest[end + 1..];
+            }
+            _ => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Decoded and lowercased with whitespace removed, so neither `java\nscript:`
+/// nor `&#x6A;avascript:` can slip through.
+fn normalized(value: &str) -> String {
+    decode_references(value).chars().filter(|c| !c.is_whitespace()).collect::<String>().to_ascii_lowercase()
+}
+
+fn is_safe_url(value: &str) -> bool {
+    let value = normalized(value);
+    value.starts_with('#') || (value.starts_with("data:image/") && !value.starts_with("data:image/svg"))
+}
+
+/// CSS that can fetch or execute: imports, external `url()`s, old IE expressions.
+fn is_unsafe_css(css: &str) -> bool {
+    let css = normalized(css);
+    css.contains("@import")
+        || css.contains("javascript:")
+        || css.contains("expression(")
+        || css.match_indices("url(").any(|(i, _)| {
+            let target = css[i + 4..].trim_start_matches(['"', '\'']);
+            !target.starts_with('#') && !target.starts_with("data:image/")
+        })
+}
+
+fn keep_attribute(name: &str, value: &str) -> bool {
+    let name = name.to_ascii_lowercase();
+    if name.starts_with("on") {
+        return false;
+    }
+    if URL_ATTRIBUTES.contains(&name.as_str()) {
+        return is_safe_url(value);
+    }
+    if name == "style" {
+        return !is_unsafe_css(value);
+    }
+    // e.g. <animate to="javascript:..."> or <set> tricks
+    !normalized(value).contains("javascript:")
+}
+
+/// Byte index of the `>` closing the tag starting at `start`, skipping quoted values.
+fn tag_end(s: &str, start: usize) -> Option<usize> {
+    let mut quote: Option<u8> = None;
+    for (i, &b) in s.as_bytes().iter().enumerate().skip(start + 1) {
+        match quote {
+            Some(q) if b == q => quote = None,
+            Some(_) => {}
+            None if b == b'"' || b == b'\'' => quote = Some(b),
+            None if b == b'>' => return Some(i),
+            None => {}
+        }
+    }
+    None
+}
+
+/// Rebuild an opening tag (`<name ...>`, without brackets) keeping safe attributes.
+fn sanitize_tag(inner: &str) -> String {
+    let bytes = inner.as_bytes();
+    let name_end = inner.find(|c: char| c.is_whitespace() || c == '/').unwrap_or(inner.len());
+    let mut out = String::from(&inner[..name_end]);
+    let mut i = name_end;
+
+    while i < bytes.len() {
+        if bytes[i].is_ascii_whitespace() {
+            i += 1;
+            continue;
+        }
+        if bytes[i] == b'/' {
+            out.push_str(" /");
+            i += 1;
+            continue;
+        }
+
+        let name_start = i;
+        while i < bytes.len() && !bytes[i].is_ascii_whitespace() && bytes[i] != b'=' && bytes[i] != b'/' {
+            i += 1;
+        }
+        let name = &inner[name_start..i];
+
+        while i < bytes.len() && bytes[i].is_ascii_whitespace() {
+            i += 1;
+        }
+        let mut value = "";
+        if i < bytes.len() && bytes[i] == b'=' {
+            i += 1;
+            while i < bytes.len() && bytes[i].is_ascii_whitespace() {
+                i += 1;
+            }
+            if i < bytes.len() && (bytes[i] == b'"' || bytes[i] == b'\'') {
+                let quote = bytes[i];
+                let value_start = i + 1;
+                i = value_start;
+                while i < bytes.len() && bytes[i] != quote {
+                    i += 1;
+                }
+                value = &inner[value_start..i];
+                i += 1;
+            } else {
+                let value_start = i;
+                while i < bytes.len() && !bytes[i].is_ascii_whitespace() {
+                    i += 1;
+                }
+                value = &inner[value_start..i];
+            }
+        }
+
+        if !name.is_empty() && keep_attribute(name, value) {
+            out.push(' ');
+            out.push_str(name);
+            out.push_str("=\"");
+            out.push_str(&value.replace('"', "&quot;"));
+            out.push('"');
+        }
+    }
+
+    out
+}
+
+fn tag_name(inner: &str) -> String {
+    inner
+        .trim_start_matches('/')
+        .split(|c: char| c.is_whitespace() || c == '/' || c == '>')
+        .next()
+        .unwrap_or("")
+        .to_ascii_lowercase()
+}
+
+// ── Sanitizer ─────────────────────────────────────────────────────────────────
+
+/// Strip the active parts of an SVG document: script-like elements, event
+/// handler attributes, external references, unsafe CSS, comments and DOCTYPE
+/// declarations (entity tricks). Everything else is passed through.
+pub fn sanitize_svg(input: &str) -> String {
+    let lower = input.to_ascii_lowercase();
+    let mut out = String::with_capacity(input.len());
+    let mut pos = 0;
+
+    while let Some(offset) = input[pos..].find('<') {
+        let start = pos + offset;
+        out.push_str(&input[pos..start]);
+        let rest = &lower[start..];
+
+        if rest.starts_with("<!--") {
+            pos = lower[start..].find("-->").map_or(input.len(), |e| start + e + 3);
+            continue;
+        }
+        if rest.starts_with("<![cdata[") {
+            let end = lower[start..].find("]]>").map_or(input.len(), |e| start + e + 3);
+            out.push_str(&input[start..end]);
+            pos = end;
+            continue;
+        }
+        if rest.starts_with("<!") {
+            // DOCTYPE, possibly with an internal subset `[ ... ]`
+            let close = match (rest.find('['), rest.find('>')) {
+                (Some(bracket), Some(gt)) if bracket < gt => rest.find("]>").map(|e| e + 1),
+                (_, gt) => gt,
+            };
+            pos = close.map_or(input.len(), |e| start + e + 1);
+            continue;
+        }
+
+        let Some(end) = tag_end(input, start) else {
+            // Unterminated tag: drop the remainder rather than emit half a tag
+            pos = input.len();
+            break;
+        };
+        let inner = &input[start + 1..end];
+        let name = tag_name(inner);
+
+        if inner.starts_with('?') || inner.starts_with('/') {
+            if !DROPPED_ELEMENTS.contains(&name.as_str()) {
+                out.push_str(&input[start..=end]);
+            }
+            pos = end + 1;
+            continue;
+        }
+
+        if DROPPED_ELEMENTS.contains(&name.as_str()) {
+            pos = if inner.trim_end().ends_with('/') {
+                end + 1
+            } else {
+                // Skip to the matching close tag (or the end of the document)
+                let close = format!("</{}", name);
+                lower[end..]
+                    .find(&close)
+                    .and_then(|c| tag_end(input, end + c).map(|e| e + 1))
+                    .unwrap_or(input.len())
+            };
+            continue;
+        }
+
+        if name == "style" {
+            let content_end = lower[end..].find("</style").map_or(input.len(), |c| end + c);
+            if is_unsafe_css(&input[end + 1..content_end]) {
+                pos = tag_end(input, content_end).map_or(input.len(), |e| e + 1);
+                continue;
+            }
+        }
+
+        out.push('<');
+        out.push_str(&sanitize_tag(inner));
+        out.push('>');
+        pos = end + 1;
+    }
+
+    if pos < input.len() {
+        out.push_str(&input[pos..]);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encoded_javascript_urls_are_dropped() {
+        let svg = r#"<svg><set attributeName="href" to="&#x6A;avascript:alert(1)"/><a style="@im&#112;ort 'x'">a</a></svg>"#;
+        assert_eq!(sanitize_svg(svg), r#"<svg><set attributeName="href" /><a>a</a></svg>"#);
+    }
+
+    #[test]
+    fn references_decode_like_the_parser() {
+        assert_eq!(decode_references("&#106;&#X61;&amp;&lt;&bogus;&#xZZ;&"), "ja&<&bogus;&#xZZ;&");
+    }
+}
@@ -0,0 +1,172 @@
+use std::path::Path;
+
+use regex::Regex;
+use serde::Serialize;
+
+use crate::error::PeekError;
+
+/// Past this many symbols the outline stops being useful; stop.
+const MAX_SYMBOLS: usize = 10_000;
+
+/// A declaration pattern: a line matching `pattern` declares its `name`
+/// group as a `kind`. With `method_if_indented`, an indented match is a
+/// method (a `fn` in an `impl`, a `def` in a class) rather than a function.
+struct Rule {
+    kind: &'static str,
+    pattern: &'static str,
+    method_if_indented: bool,
+}
+
+const fn rule(kind: &'static str, pattern: &'static str) -> Rule {
+    Rule { kind, pattern, method_if_indented: false }
+}
+
+const fn function_rule(pattern: &'static str) -> Rule {
+    Rule { kind: "function", pattern, method_if_indented: true }
+}
+
+const RUST: &[Rule] = &[
+    function_rule(r#"^\s*(?:pub(?:\([^)]*\))?\s+)?(?:const\s+)?(?:async\s+)?(?:unsafe\s+)?(?:extern\s+"[^"]*"\s+)?fn\s+(?P<name>\w+)"#),
+    rule("struct", r"^\s*(?:pub(?:\([^)]*\))?\s+)?struct\s+(?P<name>\w+)"),
+    rule("enum", r"^\s*(?:pub(?:\([^)]*\))?\s+)?enum\s+(?P<name>\w+)"),
+    rule("interface", r"^\s*(?:pub(?:\([^)]*\))?\s+)?(?:unsafe\s+)?trait\s+(?P<name>\w+)"),
+    rule("const", r"^\s*(?:pub(?:\([^)]*\))?\s+)?(?:const|static)\s+(?:mut\s+)?(?P<name>[A-Z_][A-Z0-9_]*)\s*:"),
+];
+
+const JAVASCRIPT: &[Rule] = &[
+    rule("function", r"^\s*(?:export\s+)?(?:default\s+)?(?:async\s+)?function\s*\*?\s*(?P<name>[\w$]+)"),
+    rule("function", r"^\s*(?:export\s+)?(?:const|let|var)\s+(?P<name>[\w$]+)\s*(?::[^=]+)?=\s*(?:async\s+)?(?:\([^)]*\)|[\w$]+)\s*(?::[^=]+)?=>"),
+    rule("class", r"^\s*(?:export\s+)?(?:default\s+)?(?:abstract\s+)?class\s+(?P<name>[\w$]+)"),
+    rule("interface", r"^\s*(?:export\s+)?(?:interface|type)\s+(?P<name>[\w$]+)"),
+    rule("enum", r"^\s*(?:export\s+)?(?:const\s+)?enum\s+(?P<name>[\w$]+)"),
+    rule("const", r"^(?:export\s+)?const\s+(?P<name>[A-Z_$][A-Z0-9_$]*)\s*[:=]"),
+    rule("method", r"^\s+(?:(?:public|private|protected|static|async|readonly|override|get|set)\s+)*\*?(?P<name>[\w$]+)\s*\([^)]*\)\s*(?::[^{]+)?\{"),
+];
+
+const PYTHON: &[Rule] = &[
+    function_rule(r"^\s*(?:async\s+)?def\s+(?P<name>\w+)"),
+    rule("class", r"^\s*class\s+(?P<name>\w+)"),
+    rule("const", r"^(?P<name>[A-Z_][A-Z0-9_]*)\s*(?::[^=]+)?=[^=]"),
+];
+
+const GO: &[Rule] = &[
+    rule("method", r"^func\s+\([^)]*\)\s*(?P<name>\w+)"),
+    rule("function", r"^func\s+(?P<name>\w+)"),
+    rule("struct", r"^type\s+(?P<name>\w+)\s+struct\b"),
+    rule("interface", r"^type\s+(?P<name>\w+)\s+interface\b"),
+    rule("const", r"^\s*const\s+(?P<name>\w+)"),
+];
+
+const C_FAMILY: &[Rule] = &[
+    rule("struct", r"^\s*(?:typedef\s+)?struct\s+(?P<name>\w+)"),
+    rule("class", r"^\s*(?:template\s*<[^>]*>\s*)?class\s+(?P<name>\w+)"),
+    rule("enum", r"^\s*(?:typedef\s+)?enum\s+(?:class\s+)?(?P<name>\w+)"),
+    rule("const", r"^\s*#\s*define\s+(?P<name>\w+)"),
+    rule("function", r"^(?:[\w:*&<>,]+\s+)+\**(?P<name>[A-Za-z_][\w:~]*)\s*\([^;]*$"),
+];
+
+const JVM_AND_DOTNET: &[Rule] = &[
+    rule("class", r"^\s*(?:(?:public|private|protected|internal|static|abstract|final|sealed|partial|data|open)\s+)*(?:class|record|object)\s+(?P<name>\w+)"),
+    rule("interface", r"^\s*(?:(?:public|private|protected|internal|static|sealed|partial)\s+)*interface\s+(?P<name>\w+)"),
+    rule("enum", r"^\s*(?:(?:public|private|protected|internal|static)\s+)*enum\s+(?:class\s+)?(?P<name>\w+)"),
+    function_rule(r"^\s*(?:(?:public|private|protected|internal|override|open|suspend|inline)\s+)*fun\s+(?:<[^>]*>\s*)?(?:[\w.]+\.)?(?P<name>\w+)\s*\("),
+    rule("method", r"^\s+(?:(?:public|private|protected|internal|static|final|abstract|override|virtual|async|synchronized)\s+)+[\w<>\[\],.? ]+\s+(?P<name>\w+)\s*\("),
+];
+
+const RUBY: &[Rule] = &[
+    rule("method", r"^\s*def\s+(?:self\.)?(?P<name>[\w?!=]+)"),
+    rule("class", r"^\s*(?:class|module)\s+(?P<name>[\w:]+)"),
+];
+
+const PHP: &[Rule] = &[
+    function_rule(r"^\s*(?:(?:public|private|protected|static|abstract|final)\s+)*function\s+(?P<name>\w+)"),
+    rule("class", r"^\s*(?:(?:abstract|final)\s+)?class\s+(?P<name>\w+)"),
+    rule("interface", r"^\s*(?:interface|trait)\s+(?P<name>\w+)"),
+];
+
+/// Words the loose method and C function patterns would take for names.
+const KEYWORDS: &[&str] = &[
+    "if", "for", "while", "switch", "catch", "return", "function", "else", "do", "new", "sizeof", "typeof",
+];
+
+// ── Types ─────────────────────────────────────────────────────────────────────
+
+#[derive(Serialize)]
+pub struct Symbol {
+    name: String,
+    kind: String, // "function" | "method" | "class" | "struct" | "enum" | "interface" | "const"
+    line: usize,   // 0-based
+    column: usize, // 0-based, in Unicode scalar values
+}
+
+// ── Helpers ───────────────────────────────────────────────────────────────────
+
+/// Language ids (as `probe_file` names them) or extensions.
+fn rules_for(language: &str) -> Option<&'static [Rule]> {
+    match language {
+        "rust" | "rs" => Some(RUST),
+        "javascript" | "js" | "mjs" | "cjs" | "jsx" | "typescript" | "ts" | "tsx" => Some(JAVASCRIPT),
+        "python" | "py" => Some(PYTHON),
+        "go" => Some(GO),
+        "c" | "h" | "cpp" | "hpp" | "cc" | "cxx" => Some(C_FAMILY),
+        "java" | "kotlin" | "kt" | "cs" | "csharp" | "scala" => Some(JVM_AND_DOTNET),
+        "ruby" | "rb" => Some(RUBY),
+        "php" => Some(PHP),
+        _ => None,
+    }
+}
+
+fn is_comment(line: &str) -> bool {
+    let trimmed = line.trim_start();
+    if let Some(directive) = trimmed.strip_prefix('#') {
+        // C preprocessor definitions are symbols; `#` lines elsewhere are comments or attributes
+        return !directive.trim_start().starts_with("define");
+    }
+    ["//", "/*", "*", "--"].iter().any(|token| trimmed.starts_with(token))
+}
+
+// ── Commands ──────────────────────────────────────────────────────────────────
+
+/// Declarations in a source file for an outline panel and symbol quick-jump:
+/// functions, methods, classes, structs, enums, interfaces (traits, TS
+/// types) and constants, in file order. Matching is by per-language patterns
+/// over each line, not a parser, so unusual formatting can hide a symbol.
+/// `language` is a `probe_file` id or an extension and defaults to the
+/// file's extension; languages without patterns give an empty list.
+#[tauri::command]
+pub fn extract_symbols(path: String, language: Option<String>) -> Result<Vec<Symbol>, PeekError> {
+    let path = crate::paths::resolve(&path);
+    let language = language
+        .or_else(|| Path::new(&path).extension().and_then(|e| e.to_str()).map(str::to_string))
+        .unwrap_or_default()
+        .to_lowercase();
+    let Some(rules) = rules_for(&language) else { return Ok(Vec::new()) };
+    let compiled = rules
+        .iter()
+        .map(|rule| Regex::new(rule.pattern).map(|re| (rule, re)))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    let mut symbols = Vec::new();
+    crate::search::for_each_line(&path, |line_number, line| {
+        if is_comment(line) {
+            return true;
+        }
+        let found = compiled.iter().find_map(|(rule, re)| {
+            let name = re.captures(line)?.name("name")?;
+            (!KEYWORDS.contains(&name.as_str())).then_some((rule, name))
+        });
+        if let Some((rule, name)) = found {
+            let indented = line.starts_with(char::is_whitespace);
+            let kind = if rule.method_if_indented && indented { "method" } else { rule.kind };
+            symbols.push(Symbol {
+                name: name.as_str().to_string(),
+                kind: kind.to_string(),
+                line: line_number,
+                column: line[..name.start()].chars().count(),
+            });
+        }
+        symbols.len() < MAX_SYMBOLS
+    })?;
+    Ok(symbols)
+}
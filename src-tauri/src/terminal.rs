@@ -1,31 +1,116 @@
-use std::collections::HashMap;
-use std::io::{Read, Write};
-use std::sync::{Arc, Mutex};
+use std::collections::{HashMap, VecDeque};
+use std::fs::{File, OpenOptions};
+use std::io::{BufWriter, Read, Write};
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Mutex;
 use std::thread;
+use std::time::{Duration, Instant};
 
-use portable_pty::{native_pty_system, CommandBuilder, MasterPty, PtySize};
+use portable_pty::{native_pty_system, CommandBuilder, MasterPty, PtyPair, PtySize};
 use serde::Serialize;
 use tauri::{AppHandle, Emitter, Manager};
 
+use crate::error::PeekError;
+
 // ── Types ─────────────────────────────────────────────────────────────────────
 
 /// Holds a single terminal session: the master writer + child process.
 struct TerminalSession {
     writer: Box<dyn Write + Send>,
     pair_master: Box<dyn MasterPty + Send>,
+    /// Name given by the user (`rename_terminal`); unlike `title`, the
+    /// running program can't change it.
+    label: Option<String>,
+    /// Last title set by the running program via OSC 0/1/2.
+    title: Option<String>,
+    /// Last working directory reported by the shell via OSC 7.
+    cwd: Option<String>,
+    /// PID of the shell process, if the platform reports one.
+    pid: Option<u32>,
+    /// Whether the program has enabled bracketed paste (`ESC [ ? 2004 h`).
+    bracketed_paste: bool,
+    /// Transcript file the output is being appended to, if logging.
+    log: Option<TerminalLog>,
+    /// Raw output history, capped at `MAX_SCROLLBACK_BYTES`.
+    scrollback: VecDeque<u8>,
+    /// Set by `pause_terminal_output`: output still goes to scrollback and the
+    /// log, but no `terminal-output` events are sent.
+    paused: bool,
+    /// Bytes received since the pause; the tail of `scrollback` that
+    /// `resume_terminal_output` sends (less whatever the cap dropped).
+    paused_bytes: usize,
+}
+
+const MAX_SCROLLBACK_BYTES: usize = 1024 * 1024; // 1 MB
+/// How far into trimmed scrollback to look for a clean place to start it.
+const SCROLLBACK_TRIM_WINDOW: usize = 4 * 1024;
+/// Size of each `terminal-output` event `replay_terminal` sends.
+const REPLAY_CHUNK_BYTES: usize = 16 * 1024;
+
+/// How often a session log is flushed, bounding what a crash can lose.
+const LOG_FLUSH_INTERVAL: Duration = Duration::from_secs(1);
+
+/// `initial_input` waits this long at most for the shell's first output...
+const INITIAL_INPUT_TIMEOUT: Duration = Duration::from_secs(3);
+/// ...then this long for the rest of the prompt to be drawn.
+const INITIAL_INPUT_SETTLE: Duration = Duration::from_millis(150);
+
+/// How long the login shell gets to print its environment before it's
+/// killed and the app's own environment is used instead.
+#[cfg_attr(not(unix), allow(dead_code))]
+const LOGIN_SHELL_TIMEOUT_MS: u64 = 5000;
+
+struct TerminalLog {
+    file: BufWriter<File>,
+    /// Set when writing a plain-text transcript instead of raw output.
+    stripper: Option<AnsiStripper>,
+    last_flush: Instant,
+}
+
+impl TerminalLog {
+    fn write(&mut self, bytes: &[u8]) -> std::io::Result<()> {
+        match &mut self.stripper {
+            Some(stripper) => self.file.write_all(&stripper.feed(bytes))?,
+            None => self.file.write_all(bytes)?,
+        }
+        if self.last_flush.elapsed() >= LOG_FLUSH_INTERVAL {
+            self.file.flush()?;
+            self.last_flush = Instant::now();
+        }
+        Ok(())
+    }
 }
 
 /// Managed state: map of terminal ID → session.
 pub struct PtyState {
     sessions: Mutex<HashMap<u32, TerminalSession>>,
-    next_id: Mutex<u32>,
+    next_id: AtomicU32,
+    /// Environment captured from the user's login shell, loaded once on demand.
+    login_env: Mutex<Option<HashMap<String, String>>>,
 }
 
 impl PtyState {
     pub fn new() -> Self {
         PtyState {
             sessions: Mutex::new(HashMap::new()),
-            next_id: Mutex::new(1),
+            next_id: AtomicU32::new(1),
+            login_env: Mutex::new(None),
+        }
+    }
+
+    /// Number of live sessions.
+    pub fn count(&self) -> usize {
+        self.sessions.lock().map_or(0, |sessions| sessions.len())
+    }
+
+    /// Pick the next free terminal ID. The counter wraps on overflow, so IDs
+    /// still held by live sessions (and 0) are skipped.
+    fn allocate_id(&self, sessions: &HashMap<u32, TerminalSession>) -> u32 {
+        loop {
+            let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+            if id != 0 && !sessions.contains_key(&id) {
+                return id;
+            }
         }
     }
 }
@@ -42,47 +127,668 @@ struct TerminalExit {
     code: i32,
 }
 
-// ── Commands ──────────────────────────────────────────────────────────────────
+#[derive(Serialize, Clone)]
+struct TerminalReplayDone {
+    id: u32,
+}
 
-/// Spawn a new terminal session. Returns the terminal ID.
-#[tauri::command]
-pub fn spawn_terminal(
-    rows: u16,
-    cols: u16,
+#[derive(Serialize, Clone)]
+struct TerminalTitle {
+    id: u32,
+    title: String,
+}
+
+#[derive(Serialize, Clone)]
+struct TerminalCwd {
+    id: u32,
+    cwd: String,
+}
+
+/// A scrollback match: 0-based line and character column in the
+/// ANSI-stripped transcript, and its length in characters.
+#[derive(Serialize)]
+pub struct ScrollbackMatch {
+    line: usize,
+    column: usize,
+    length: usize,
+}
+
+const MAX_SCROLLBACK_MATCHES: usize = 1000;
+
+#[derive(Serialize)]
+pub struct TerminalInfo {
+    id: u32,
+    label: Option<String>,
+    title: Option<String>,
     cwd: Option<String>,
-    app: AppHandle,
-    state: tauri::State<'_, PtyState>,
-) -> Result<u32, String> {
-    let pty_system = native_pty_system();
+    pid: Option<u32>,
+}
 
-    let pair = pty_system
-        .openpty(PtySize {
-            rows,
-            cols,
-            pixel_width: 0,
-            pixel_height: 0,
+/// A process running in a terminal, with the processes it started.
+#[derive(Serialize)]
+pub struct ProcessNode {
+    pid: u32,
+    /// Executable name ("cargo", "node").
+    name: String,
+    /// Full command line, when the OS lets us read it.
+    command: Vec<String>,
+    /// In the terminal's foreground process group, i.e. what keyboard input
+    /// and Ctrl-C reach. Only known on Linux.
+    foreground: bool,
+    children: Vec<ProcessNode>,
+}
+
+/// One process as listed by the OS, before it's placed in a tree.
+struct ProcessEntry {
+    pid: u32,
+    parent: u32,
+    group: Option<u32>,
+    name: String,
+    command: Vec<String>,
+}
+
+// ── OSC Parsing ───────────────────────────────────────────────────────────────
+
+/// Longest OSC payload we buffer before giving up on a runaway sequence.
+const MAX_OSC_LEN: usize = 4096;
+
+#[derive(Default, Clone, Copy, PartialEq)]
+enum OscState {
+    #[default]
+    Ground,
+    Escape,
+    Osc,
+    OscEscape,
+}
+
+/// Incremental parser for `ESC ] code ; payload (BEL | ESC \)` sequences.
+/// State is kept between reads so sequences split across chunks still parse.
+#[derive(Default)]
+struct OscParser {
+    state: OscState,
+    buf: Vec<u8>,
+}
+
+impl OscParser {
+    /// Feed raw PTY bytes, returning every OSC sequence completed in this chunk
+    /// as `(code, payload)`. The bytes themselves are left untouched.
+    fn feed(&mut self, bytes: &[u8]) -> Vec<(u16, String)> {
+        let mut completed = Vec::new();
+        for &byte in bytes {
+            match self.state {
+                OscState::Ground => {
+                    if byte == 0x1b {
+                        self.state = OscState::Escape;
+                    }
+                }
+                OscState::Escape => self.escape(byte),
+                OscState::Osc => match byte {
+                    0x07 => {
+                        completed.extend(self.finish());
+                        self.state = OscState::Ground;
+                    }
+                    0x1b => self.state = OscState::OscEscape,
+                    // CAN / SUB abort the sequence
+                    0x18 | 0x1a => self.state = OscState::Ground,
+                    _ if self.buf.len() < MAX_OSC_LEN => self.buf.push(byte),
+                    _ => self.state = OscState::Ground,
+                },
+                OscState::OscEscape => {
+                    if byte == b'\\' {
+                        completed.extend(self.finish());
+                        self.state = OscState::Ground;
+                    } else {
+                        // Unterminated OSC interrupted by another escape sequence
+                        self.escape(byte);
+                    }
+                }
+            }
+        }
+        completed
+    }
+
+    /// Handle the byte following an ESC.
+    fn escape(&mut self, byte: u8) {
+        self.state = match byte {
+            b']' => {
+                self.buf.clear();
+                OscState::Osc
+            }
+            0x1b => OscState::Escape,
+            _ => OscState::Ground,
+        };
+    }
+
+    /// Split the buffered sequence into its numeric code and payload.
+    fn finish(&mut self) -> Option<(u16, String)> {
+        let buf = std::mem::take(&mut self.buf);
+        let split = buf.iter().position(|&b| b == b';')?;
+        let code = std::str::from_utf8(&buf[..split]).ok()?.parse().ok()?;
+        Some((code, String::from_utf8_lossy(&buf[split + 1..]).to_string()))
+    }
+}
+
+/// Extract the local path from an OSC 7 `file://host/path` payload.
+fn parse_osc7_path(payload: &str) -> Option<String> {
+    let rest = payload.strip_prefix("file://")?;
+    let path = &rest[rest.find('/')?..];
+
+    // Shells escape spaces and non-ASCII bytes
+    let decoded = crate::protocol::percent_decode(path);
+
+    // Windows shells report `file://host/C:/dir`
+    let is_drive_path = decoded.len() >= 3
+        && decoded.as_bytes()[2] == b':'
+        && decoded.as_bytes()[1].is_ascii_alphabetic();
+    if cfg!(windows) && is_drive_path {
+        return Some(decoded[1..].to_string());
+    }
+    Some(decoded)
+}
+
+// ── ANSI Stripping ────────────────────────────────────────────────────────────
+
+#[derive(Default, Clone, Copy, PartialEq)]
+enum AnsiState {
+    #[default]
+    Ground,
+    Escape,
+    /// `ESC` followed by intermediate bytes, e.g. the charset switch `ESC ( B`.
+    EscapeIntermediate,
+    Csi,
+    /// OSC / DCS / SOS / PM / APC: a string terminated by BEL or `ESC \`.
+    String,
+    StringEscape,
+}
+
+/// Incremental filter that removes escape sequences (CSI, OSC, DCS, charset
+/// switches, ...) and control characters other than `\n` and `\t`, leaving
+/// plain text. State carries across reads, so split sequences are still
+/// removed. Only ASCII bytes are ever dropped: UTF-8 text passes through
+/// intact (8-bit C1 controls are not recognized, as they'd clash with UTF-8).
+#[derive(Default)]
+struct AnsiStripper {
+    state: AnsiState,
+    /// Also keep `\r`, for callers that interpret line redraws themselves.
+    keep_carriage_returns: bool,
+}
+
+impl AnsiStripper {
+    fn feed(&mut self, bytes: &[u8]) -> Vec<u8> {
+        let mut out = Vec::with_capacity(bytes.len());
+        for &byte in bytes {
+            self.state = match (self.state, byte) {
+                (_, 0x18 | 0x1a) => AnsiState::Ground, // CAN / SUB abort any sequence
+                (AnsiState::Ground, 0x1b) => AnsiState::Escape,
+                (AnsiState::Ground, b'\n' | b'\t' | 0x20..=0x7e | 0x80..) => {
+                    out.push(byte);
+                    AnsiState::Ground
+                }
+                (AnsiState::Ground, b'\r') if self.keep_carriage_returns => {
+                    out.push(byte);
+                    AnsiState::Ground
+                }
+                (AnsiState::Ground, _) => AnsiState::Ground,
+                (AnsiState::Escape, b'[') => AnsiState::Csi,
+                (AnsiState::Escape, b']' | b'P' | b'X' | b'^' | b'_') => AnsiState::String,
+                (AnsiState::Escape, 0x1b) => AnsiState::Escape,
+                (AnsiState::Escape | AnsiState::EscapeIntermediate, 0x20..=0x2f) => AnsiState::EscapeIntermediate,
+                (AnsiState::Escape | AnsiState::EscapeIntermediate, _) => AnsiState::Ground,
+                // Parameter and intermediate bytes, then a final byte in 0x40..=0x7e
+                (AnsiState::Csi, 0x40..=0x7e) => AnsiState::Ground,
+                (AnsiState::Csi, 0x1b) => AnsiState::Escape,
+                (AnsiState::Csi, _) => AnsiState::Csi,
+                (AnsiState::String, 0x07) => AnsiState::Ground,
+                (AnsiState::String | AnsiState::StringEscape, 0x1b) => AnsiState::StringEscape,
+                (AnsiState::StringEscape, b'\\') => AnsiState::Ground,
+                (AnsiState::String | AnsiState::StringEscape, _) => AnsiState::String,
+            };
+        }
+        out
+    }
+}
+
+// ── Mode Tracking ─────────────────────────────────────────────────────────────
+
+const BRACKETED_PASTE_ON: &[u8] = b"\x1b[?2004h";
+const BRACKETED_PASTE_OFF: &[u8] = b"\x1b[?2004l";
+const PASTE_START: &str = "\x1b[200~";
+const PASTE_END: &str = "\x1b[201~";
+
+/// Watches PTY output for the bracketed-paste mode switches. The last few
+/// bytes of each chunk are kept so a sequence split across reads is still seen.
+#[derive(Default)]
+struct PasteModeScanner {
+    tail: Vec<u8>,
+}
+
+impl PasteModeScanner {
+    /// Feed raw PTY bytes; returns the mode the chunk leaves the terminal in,
+    /// or `None` if it didn't toggle it.
+    fn feed(&mut self, bytes: &[u8]) -> Option<bool> {
+        let mut window = std::mem::take(&mut self.tail);
+        window.extend_from_slice(bytes);
+
+        let on = memchr::memmem::rfind(&window, BRACKETED_PASTE_ON);
+        let off = memchr::memmem::rfind(&window, BRACKETED_PASTE_OFF);
+        let keep = BRACKETED_PASTE_ON.len() - 1;
+        self.tail = window[window.len().saturating_sub(keep)..].to_vec();
+
+        match (on, off) {
+            (Some(on), Some(off)) => Some(on > off),
+            (Some(_), None) => Some(true),
+            (None, Some(_)) => Some(false),
+            (None, None) => None,
+        }
+    }
+}
+
+/// Wrap pasted text in bracketed-paste markers. An end marker inside the text
+/// is dropped so the paste can't break out and execute the rest as typed input.
+fn bracket_paste(data: &str) -> String {
+    format!("{}{}{}", PASTE_START, data.replace(PASTE_END, ""), PASTE_END)
+}
+
+// ── Helpers ───────────────────────────────────────────────────────────────────
+
+/// Run the user's login shell once and capture its environment. The app process
+/// is often launched without the interactive PATH (nvm, pyenv, homebrew...).
+/// A shell that hasn't finished after `LOGIN_SHELL_TIMEOUT_MS` (a profile
+/// waiting for input or the network) is killed along with everything it
+/// started: it runs in its own process group so nothing is left holding its
+/// stdout.
+#[cfg(unix)]
+fn load_login_shell_env() -> Result<HashMap<String, String>, PeekError> {
+    use std::os::unix::process::CommandExt;
+    use std::process::{Command, Stdio};
+    use std::sync::mpsc;
+
+    let shell = std::env::var("SHELL").unwrap_or_else(|_| "/bin/sh".to_string());
+    let mut child = Command::new(&shell)
+        .args(["-l", "-c", "env"])
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .process_group(0)
+        .spawn()
+        .map_err(|e| format!("Failed to run login shell: {}", e))?;
+    let mut stdout = child.stdout.take().ok_or("Login shell has no stdout")?;
+
+    // Reading ends once every process in the group has closed stdout
+    let (tx, rx) = mpsc::channel();
+    thread::spawn(move || {
+        let mut output = Vec::new();
+        let _ = tx.send(stdout.read_to_end(&mut output).map(|_| output));
+    });
+    let output = match rx.recv_timeout(Duration::from_millis(LOGIN_SHELL_TIMEOUT_MS)) {
+        Ok(output) => output?,
+        Err(_) => {
+            // Not reaped yet, so the shell's pid still names its group
+            unsafe { libc::killpg(child.id() as libc::pid_t, libc::SIGKILL) };
+            let _ = child.kill();
+            let _ = child.wait();
+            return Err(PeekError::timeout("Login shell didn't print its environment in time"));
+        }
+    };
+
+    let status = child.wait()?;
+    if !status.success() {
+        return Err(format!("Login shell exited with {}", status).into());
+    }
+
+    Ok(parse_env_output(&String::from_utf8_lossy(&output)))
+}
+
+/// Windows has no login-shell concept; the process environment is already complete.
+#[cfg(not(unix))]
+fn load_login_shell_env() -> Result<HashMap<String, String>, PeekError> {
+    Ok(std::env::vars().collect())
+}
+
+/// Parse `env` output. Lines that don't start with `KEY=` are treated as
+/// continuations of a multi-line value.
+#[cfg_attr(not(unix), allow(dead_code))]
+fn parse_env_output(output: &str) -> HashMap<String, String> {
+    let mut env = HashMap::new();
+    let mut last_key: Option<String> = None;
+
+    for line in output.lines() {
+        let entry = line.split_once('=').filter(|(key, _)| {
+            !key.is_empty() && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+        });
+        match entry {
+            Some((key, value)) => {
+                env.insert(key.to_string(), value.to_string());
+                last_key = Some(key.to_string());
+            }
+            None => {
+                if let Some(value) = last_key.as_ref().and_then(|k| env.get_mut(k)) {
+                    value.push('\n');
+                    value.push_str(line);
+                }
+            }
+        }
+    }
+
+    env
+}
+
+/// Return the cached login-shell environment, loading it on first use. If the
+/// login shell fails or times out, the app's own environment is cached instead
+/// so later calls don't wait on it again.
+fn login_shell_env(state: &PtyState) -> Result<HashMap<String, String>, PeekError> {
+    let mut cached = state.login_env.lock()?;
+    if let Some(env) = cached.as_ref() {
+        return Ok(env.clone());
+    }
+    let env = load_login_shell_env().unwrap_or_else(|_| std::env::vars().collect());
+    *cached = Some(env.clone());
+    Ok(env)
+}
+
+/// Record a new title for a session and notify the frontend.
+fn set_session_title(app: &AppHandle, id: u32, title: String) {
+    let state = app.state::<PtyState>();
+    if let Ok(mut sessions) = state.sessions.lock() {
+        if let Some(session) = sessions.get_mut(&id) {
+            session.title = Some(title.clone());
+        }
+    }
+    let _ = app.emit("terminal-title", TerminalTitle { id, title });
+}
+
+/// Record a new working directory for a session and notify the frontend.
+fn set_session_cwd(app: &AppHandle, id: u32, cwd: String) {
+    let state = app.state::<PtyState>();
+    if let Ok(mut sessions) = state.sessions.lock() {
+        if let Some(session) = sessions.get_mut(&id) {
+            session.cwd = Some(cwd.clone());
+        }
+    }
+    let _ = app.emit("terminal-cwd", TerminalCwd { id, cwd });
+}
+
+fn set_bracketed_paste(app: &AppHandle, id: u32, enabled: bool) {
+    let state = app.state::<PtyState>();
+    if let Ok(mut sessions) = state.sessions.lock() {
+        if let Some(session) = sessions.get_mut(&id) {
+            session.bracketed_paste = enabled;
+        }
+    };
+}
+
+/// Add output to the session's scrollback and log, if it has one. A log
+/// write error (disk full, file removed) stops logging rather than retrying
+/// on every read. Returns whether output is paused, in which case the caller
+/// shouldn't emit it.
+fn record_output(app: &AppHandle, id: u32, bytes: &[u8]) -> bool {
+    let state = app.state::<PtyState>();
+    let mut paused = false;
+    if let Ok(mut sessions) = state.sessions.lock() {
+        if let Some(session) = sessions.get_mut(&id) {
+            if session.paused {
+                paused = true;
+                session.paused_bytes += bytes.len();
+            }
+            let scrollback = &mut session.scrollback;
+            scrollback.extend(bytes);
+            if scrollback.len() > MAX_SCROLLBACK_BYTES {
+                scrollback.drain(..scrollback.len() - MAX_SCROLLBACK_BYTES);
+                // Restart at a line boundary rather than mid-sequence or mid-character.
+                // Redraws and `\r` progress bars can go long without a newline, so
+                // only look near the front, then settle for an escape or a character
+                let window = || scrollback.iter().take(SCROLLBACK_TRIM_WINDOW);
+                let start = window()
+                    .position(|&b| b == b'\n')
+                    .map(|newline| newline + 1)
+                    .or_else(|| window().position(|&b| b == 0x1b))
+                    .or_else(|| window().position(|&b| b & 0xC0 != 0x80))
+                    .unwrap_or(0);
+                scrollback.drain(..start);
+            }
+
+            if let Some(log) = session.log.as_mut() {
+                if log.write(bytes).is_err() {
+                    session.log = None;
+                }
+            }
+        }
+    };
+    paused
+}
+
+fn flush_log(app: &AppHandle, id: u32) {
+    let state = app.state::<PtyState>();
+    if let Ok(mut sessions) = state.sessions.lock() {
+        if let Some(log) = sessions.get_mut(&id).and_then(|session| session.log.as_mut()) {
+            let _ = log.file.flush();
+        }
+    };
+}
+
+fn scrollback_text(session: &TerminalSession, strip_ansi: bool) -> String {
+    let (front, back) = session.scrollback.as_slices();
+    let mut bytes = [front, back].concat();
+    if strip_ansi {
+        bytes = AnsiStripper::default().feed(&bytes);
+    }
+    String::from_utf8_lossy(&bytes).to_string()
+}
+
+/// The scrollback as a clean transcript: escape sequences removed, a line
+/// redrawn after `\r` (progress bars, prompt redraws) kept only in its final
+/// form, trailing whitespace and blank lines trimmed. The buffer is decoded
+/// whole; a character cut off at either end (by the scrollback cap at the
+/// start, or by a read still in progress at the end) is dropped rather than
+/// shown as U+FFFD.
+fn transcript_text(session: &TerminalSession) -> String {
+    let (front, back) = session.scrollback.as_slices();
+    let bytes = [front, back].concat();
+    let stripped = AnsiStripper { keep_carriage_returns: true, ..Default::default() }.feed(&bytes);
+
+    let start = stripped.iter().position(|&b| !(0x80..0xc0).contains(&b)).unwrap_or(stripped.len());
+    let mut end = stripped.len();
+    if let Err(e) = std::str::from_utf8(&stripped[start..]) {
+        if e.error_len().is_none() {
+            end = start + e.valid_up_to();
+        }
+    }
+    let text = String::from_utf8_lossy(&stripped[start..end]);
+
+    let lines: Vec<&str> = text
+        .split('\n')
+        .map(|line| line.split('\r').rev().find(|redraw| !redraw.is_empty()).unwrap_or("").trim_end())
+        .collect();
+    let used = lines.iter().rposition(|line| !line.is_empty()).map_or(0, |last| last + 1);
+    lines[..used].join("\n")
+}
+
+/// Best-effort cwd lookup for shells that don't emit OSC 7.
+#[cfg(target_os = "linux")]
+fn process_cwd(pid: u32) -> Option<String> {
+    std::fs::read_link(format!("/proc/{}/cwd", pid))
+        .ok()
+        .map(|p| p.to_string_lossy().to_string())
+}
+
+#[cfg(not(target_os = "linux"))]
+fn process_cwd(_pid: u32) -> Option<String> {
+    None
+}
+
+#[cfg(target_os = "linux")]
+fn list_processes() -> Vec<ProcessEntry> {
+    let Ok(dir) = std::fs::read_dir("/proc") else { return Vec::new() };
+    dir.filter_map(|entry| {
+        let pid: u32 = entry.ok()?.file_name().to_str()?.parse().ok()?;
+        let stat = std::fs::read_to_string(format!("/proc/{}/stat", pid)).ok()?;
+        // `pid (comm) state ppid pgrp ...`; comm may itself contain spaces or ')'
+        let open = stat.find('(')?;
+        let close = stat.rfind(')')?;
+        let name = stat[open + 1..close].to_string();
+        let mut fields = stat[close + 1..].split_whitespace().skip(1);
+        let parent = fields.next()?.parse().ok()?;
+        let group = fields.next().and_then(|g| g.parse().ok());
+        let command = std::fs::read(format!("/proc/{}/cmdline", pid))
+            .map(|raw| {
+                raw.split(|&b| b == 0)
+                    .filter(|arg| !arg.is_empty())
+                    .map(|arg| String::from_utf8_lossy(arg).to_string())
+                    .collect()
+            })
+            .unwrap_or_default();
+        Some(ProcessEntry { pid, parent, group, name, command })
+    })
+    .collect()
+}
+
+#[cfg(not(target_os = "linux"))]
+fn list_processes() -> Vec<ProcessEntry> {
+    let mut system = sysinfo::System::new();
+    system.refresh_processes(sysinfo::ProcessesToUpdate::All, true);
+    system
+        .processes()
+        .iter()
+        .filter_map(|(pid, process)| {
+            Some(ProcessEntry {
+                pid: pid.as_u32(),
+                parent: process.parent()?.as_u32(),
+                group: None,
+                name: process.name().to_string_lossy().to_string(),
+                command: process.cmd().iter().map(|arg| arg.to_string_lossy().to_string()).collect(),
+            })
         })
-        .map_err(|e| format!("Failed to open PTY: {}", e))?;
+        .collect()
+}
 
-    // Build shell command
-    let mut cmd = CommandBuilder::new_default_prog();
-    if let Some(dir) = cwd {
-        cmd.cwd(dir);
+/// Build the tree under `pid` from a process listing, depth-first by PID.
+fn process_subtree(
+    pid: u32,
+    entries: &HashMap<u32, &ProcessEntry>,
+    children: &HashMap<u32, Vec<u32>>,
+    foreground_group: Option<u32>,
+) -> Option<ProcessNode> {
+    let entry = entries.get(&pid)?;
+    let mut kids = children.get(&pid).cloned().unwrap_or_default();
+    kids.sort_unstable();
+    Some(ProcessNode {
+        pid,
+        name: entry.name.clone(),
+        command: entry.command.clone(),
+        foreground: foreground_group.is_some_and(|group| entry.group == Some(group)),
+        children: kids
+            .into_iter()
+            .filter_map(|kid| process_subtree(kid, entries, children, foreground_group))
+            .collect(),
+    })
+}
+
+/// Deliver `signal` (`INT`, `TERM`, `KILL`, `TSTP`, `QUIT`, `HUP`, `CONT`;
+/// a `SIG` prefix is optional) to the terminal's foreground process group,
+/// i.e. whatever Ctrl-C would reach, falling back to the shell's own group.
+#[cfg(unix)]
+fn send_signal(session: &mut TerminalSession, signal: &str) -> Result<(), PeekError> {
+    let signal = signal.to_ascii_uppercase();
+    let signal = match signal.strip_prefix("SIG").unwrap_or(&signal) {
+        "INT" => libc::SIGINT,
+        "TERM" => libc::SIGTERM,
+        "KILL" => libc::SIGKILL,
+        "TSTP" => libc::SIGTSTP,
+        "QUIT" => libc::SIGQUIT,
+        "HUP" => libc::SIGHUP,
+        "CONT" => libc::SIGCONT,
+        other => return Err(format!("Unsupported signal: {}", other).into()),
+    };
+    let pgid = session
+        .pair_master
+        .process_group_leader()
+        .or(session.pid.map(|pid| pid as libc::pid_t))
+        .ok_or("Terminal has no process to signal")?;
+
+    // SAFETY: killpg only takes plain integers
+    if unsafe { libc::killpg(pgid, signal) } != 0 {
+        return Err(std::io::Error::last_os_error().into());
     }
+    Ok(())
+}
+
+/// Windows has no signals: `INT` goes through ConPTY as Ctrl-C, and
+/// `TERM`/`KILL` terminate the shell's whole process tree.
+#[cfg(not(unix))]
+fn send_signal(session: &mut TerminalSession, signal: &str) -> Result<(), PeekError> {
+    let signal = signal.to_ascii_uppercase();
+    match signal.strip_prefix("SIG").unwrap_or(&signal) {
+        "INT" => {
+            session.writer.write_all(b"\x03")?;
+            Ok(session.writer.flush()?)
+        }
+        "TERM" | "KILL" => {
+            let pid = session.pid.ok_or("Terminal has no process to signal")?;
+            let status = std::process::Command::new("taskkill")
+                .args(["/PID", &pid.to_string(), "/T", "/F"])
+                .status()?;
+            if status.success() {
+                Ok(())
+            } else {
+                Err(format!("taskkill failed for PID {}", pid).into())
+            }
+        }
+        other => Err(format!("Unsupported signal: {}", other).into()),
+    }
+}
 
+/// Type `text` and Enter into a new terminal once its shell is ready: after
+/// its first output (the prompt, usually) and a moment for the rest of it,
+/// or after `INITIAL_INPUT_TIMEOUT` if it stays silent. Runs on its own
+/// thread; a session that's gone by then is skipped.
+fn send_initial_input(app: AppHandle, id: u32, text: String) {
+    thread::spawn(move || {
+        let state = app.state::<PtyState>();
+        let started = Instant::now();
+        loop {
+            let ready = match state.sessions.lock() {
+                Ok(sessions) => match sessions.get(&id) {
+                    Some(session) => !session.scrollback.is_empty(),
+                    None => return,
+                },
+                Err(_) => return,
+            };
+            if ready || started.elapsed() >= INITIAL_INPUT_TIMEOUT {
+                break;
+            }
+            thread::sleep(Duration::from_millis(20));
+        }
+        thread::sleep(INITIAL_INPUT_SETTLE);
+
+        if let Ok(mut sessions) = state.sessions.lock() {
+            if let Some(session) = sessions.get_mut(&id) {
+                // Enter is CR on a terminal, whatever the platform
+                let _ = session.writer.write_all(format!("{}\r", text.trim_end_matches(['\r', '\n'])).as_bytes());
+                let _ = session.writer.flush();
+            }
+        };
+    });
+}
+
+/// Spawn `cmd` on the PTY, register the session and start its reader thread,
+/// which forwards output as `terminal-output` events. A `one_shot` session
+/// reports the program's exit code and removes itself when it exits;
+/// interactive ones stay until `kill_terminal`.
+fn start_session(
+    pair: PtyPair,
+    cmd: CommandBuilder,
+    one_shot: bool,
+    app: &AppHandle,
+    state: &PtyState,
+) -> Result<u32, PeekError> {
     // Spawn child
-    let _child = pair
+    let mut child = pair
         .slave
         .spawn_command(cmd)
         .map_err(|e| format!("Failed to spawn shell: {}", e))?;
-
-    // Assign ID
-    let id = {
-        let mut next = state.next_id.lock().map_err(|e| e.to_string())?;
-        let id = *next;
-        *next += 1;
-        id
-    };
+    let pid = child.process_id();
+    // Only the child should hold the slave end, so its exit ends our reads
+    drop(pair.slave);
 
     // Get reader from master
     let mut reader = pair
@@ -96,27 +802,59 @@ pub fn spawn_terminal(
         .take_writer()
         .map_err(|e| format!("Failed to take writer: {}", e))?;
 
-    // Store session
-    {
-        let mut sessions = state.sessions.lock().map_err(|e| e.to_string())?;
+    // Assign ID and store session
+    let id = {
+        let mut sessions = state.sessions.lock()?;
+        let id = state.allocate_id(&sessions);
         sessions.insert(
             id,
             TerminalSession {
                 writer,
                 pair_master: pair.master,
+                label: None,
+                title: None,
+                cwd: None,
+                pid,
+                bracketed_paste: false,
+                log: None,
+                scrollback: VecDeque::new(),
+                paused: false,
+                paused_bytes: 0,
             },
         );
-    }
+        id
+    };
 
     // Background reader thread: reads PTY output and emits events
     let app_handle = app.clone();
     let terminal_id = id;
     thread::spawn(move || {
         let mut buf = [0u8; 4096];
+        let mut osc = OscParser::default();
+        let mut paste_mode = PasteModeScanner::default();
         loop {
             match reader.read(&mut buf) {
                 Ok(0) => break, // EOF
                 Ok(n) => {
+                    for (code, payload) in osc.feed(&buf[..n]) {
+                        match code {
+                            // OSC 0 / 1 / 2: icon name and/or window title
+                            0..=2 => set_session_title(&app_handle, terminal_id, payload),
+                            // OSC 7: current working directory
+                            7 => {
+                                if let Some(cwd) = parse_osc7_path(&payload) {
+                                    set_session_cwd(&app_handle, terminal_id, cwd);
+                                }
+                            }
+                            _ => {}
+                        }
+                    }
+                    if let Some(enabled) = paste_mode.feed(&buf[..n]) {
+                        set_bracketed_paste(&app_handle, terminal_id, enabled);
+                    }
+                    if record_output(&app_handle, terminal_id, &buf[..n]) {
+                        continue;
+                    }
                     let data = String::from_utf8_lossy(&buf[..n]).to_string();
                     let _ = app_handle.emit(
                         "terminal-output",
@@ -130,11 +868,21 @@ pub fn spawn_terminal(
             }
         }
         // Terminal exited
+        flush_log(&app_handle, terminal_id);
+        let code = if one_shot {
+            let code = child.wait().map(|status| status.exit_code() as i32).unwrap_or(-1);
+            if let Ok(mut sessions) = app_handle.state::<PtyState>().sessions.lock() {
+                sessions.remove(&terminal_id);
+            }
+            code
+        } else {
+            0
+        };
         let _ = app_handle.emit(
             "terminal-exit",
             TerminalExit {
                 id: terminal_id,
-                code: 0,
+                code,
             },
         );
     });
@@ -142,17 +890,136 @@ pub fn spawn_terminal(
     Ok(id)
 }
 
-/// Write data (user input) to terminal.
+// ── Commands ──────────────────────────────────────────────────────────────────
+
+/// Get the environment of the user's login shell (cached after the first
+/// call), or the app's own if the login shell fails or hangs.
+#[tauri::command]
+pub fn get_login_shell_env(
+    state: tauri::State<'_, PtyState>,
+) -> Result<HashMap<String, String>, PeekError> {
+    login_shell_env(&state)
+}
+
+/// Spawn a new terminal session. Returns the terminal ID.
+///
+/// `program` (with `args`) replaces the platform's default shell, e.g. with
+/// one from `list_available_shells`. `use_login_env` merges the login-shell
+/// environment into the child, and `env` entries are applied last so they
+/// override anything inherited. `initial_input` is typed into the shell once
+/// its prompt is up, followed by Enter (activate a venv, start a dev server);
+/// the terminal stays interactive, unlike `spawn_command_terminal`.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub fn spawn_terminal(
+    rows: u16,
+    cols: u16,
+    cwd: Option<String>,
+    program: Option<String>,
+    args: Option<Vec<String>>,
+    env: Option<HashMap<String, String>>,
+    use_login_env: Option<bool>,
+    initial_input: Option<String>,
+    app: AppHandle,
+    state: tauri::State<'_, PtyState>,
+) -> Result<u32, PeekError> {
+    let pty_system = native_pty_system();
+
+    let pair = pty_system
+        .openpty(PtySize {
+            rows,
+            cols,
+            pixel_width: 0,
+            pixel_height: 0,
+        })
+        .map_err(|e| format!("Failed to open PTY: {}", e))?;
+
+    // Build shell command
+    let mut cmd = match program {
+        Some(program) => {
+            let mut cmd = CommandBuilder::new(program);
+            cmd.args(args.unwrap_or_default());
+            cmd
+        }
+        None => CommandBuilder::new_default_prog(),
+    };
+    if let Some(dir) = cwd {
+        cmd.cwd(crate::paths::resolve_path(&dir));
+    }
+    if use_login_env.unwrap_or(false) {
+        // Best effort: a broken login profile shouldn't stop the terminal from opening
+        if let Ok(login_env) = login_shell_env(&state) {
+            for (key, value) in login_env {
+                cmd.env(key, value);
+            }
+        }
+    }
+    for (key, value) in env.unwrap_or_default() {
+        cmd.env(key, value);
+    }
+
+    let id = start_session(pair, cmd, false, &app, &state)?;
+    if let Some(text) = initial_input.filter(|text| !text.trim().is_empty()) {
+        send_initial_input(app, id, text);
+    }
+    Ok(id)
+}
+
+/// Run a single command (not an interactive shell) in a PTY, for build and
+/// test tasks: its output arrives as `terminal-output` like any terminal's,
+/// and `terminal-exit` carries its real exit code. The session is removed as
+/// soon as the command exits, so there's nothing to kill afterwards.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub fn spawn_command_terminal(
+    program: String,
+    args: Option<Vec<String>>,
+    cwd: Option<String>,
+    env: Option<HashMap<String, String>>,
+    rows: Option<u16>,
+    cols: Option<u16>,
+    app: AppHandle,
+    state: tauri::State<'_, PtyState>,
+) -> Result<u32, PeekError> {
+    let pair = native_pty_system()
+        .openpty(PtySize {
+            rows: rows.unwrap_or(24),
+            cols: cols.unwrap_or(80),
+            pixel_width: 0,
+            pixel_height: 0,
+        })
+        .map_err(|e| format!("Failed to open PTY: {}", e))?;
+
+    let mut cmd = CommandBuilder::new(program);
+    cmd.args(args.unwrap_or_default());
+    if let Some(dir) = cwd {
+        cmd.cwd(crate::paths::resolve_path(&dir));
+    }
+    for (key, value) in env.unwrap_or_default() {
+        cmd.env(key, value);
+    }
+    start_session(pair, cmd, true, &app, &state)
+}
+
+/// Write data (user input) to terminal. With `paste`, the data is wrapped in
+/// bracketed-paste markers if the running program has enabled them, so a
+/// multi-line paste isn't executed line by line.
 #[tauri::command]
 pub fn write_terminal(
     id: u32,
     data: String,
+    paste: Option<bool>,
     state: tauri::State<'_, PtyState>,
-) -> Result<(), String> {
-    let mut sessions = state.sessions.lock().map_err(|e| e.to_string())?;
+) -> Result<(), PeekError> {
+    let mut sessions = state.sessions.lock()?;
     let session = sessions
         .get_mut(&id)
-        .ok_or_else(|| format!("Terminal {} not found", id))?;
+        .ok_or_else(|| PeekError::not_found(format!("Terminal {} not found", id)))?;
+    let data = if paste.unwrap_or(false) && session.bracketed_paste {
+        bracket_paste(&data)
+    } else {
+        data
+    };
     session
         .writer
         .write_all(data.as_bytes())
@@ -164,6 +1031,34 @@ pub fn write_terminal(
     Ok(())
 }
 
+/// Write raw bytes (base64-encoded) to a terminal, for input a `String`
+/// can't carry: arbitrary key sequences or binary data piped to a program.
+#[tauri::command]
+pub fn write_terminal_bytes(
+    id: u32,
+    data: String,
+    state: tauri::State<'_, PtyState>,
+) -> Result<(), PeekError> {
+    use base64::{Engine as _, engine::general_purpose};
+    let bytes = general_purpose::STANDARD
+        .decode(data)
+        .map_err(|e| format!("Invalid base64: {}", e))?;
+
+    let mut sessions = state.sessions.lock()?;
+    let session = sessions
+        .get_mut(&id)
+        .ok_or_else(|| PeekError::not_found(format!("Terminal {} not found", id)))?;
+    session
+        .writer
+        .write_all(&bytes)
+        .map_err(|e| format!("Write failed: {}", e))?;
+    session
+        .writer
+        .flush()
+        .map_err(|e| format!("Flush failed: {}", e))?;
+    Ok(())
+}
+
 /// Resize terminal.
 #[tauri::command]
 pub fn resize_terminal(
@@ -171,11 +1066,11 @@ pub fn resize_terminal(
     rows: u16,
     cols: u16,
     state: tauri::State<'_, PtyState>,
-) -> Result<(), String> {
-    let sessions = state.sessions.lock().map_err(|e| e.to_string())?;
+) -> Result<(), PeekError> {
+    let sessions = state.sessions.lock()?;
     let session = sessions
         .get(&id)
-        .ok_or_else(|| format!("Terminal {} not found", id))?;
+        .ok_or_else(|| PeekError::not_found(format!("Terminal {} not found", id)))?;
     session
         .pair_master
         .resize(PtySize {
@@ -190,8 +1085,276 @@ pub fn resize_terminal(
 
 /// Kill a terminal session.
 #[tauri::command]
-pub fn kill_terminal(id: u32, state: tauri::State<'_, PtyState>) -> Result<(), String> {
-    let mut sessions = state.sessions.lock().map_err(|e| e.to_string())?;
+pub fn kill_terminal(id: u32, state: tauri::State<'_, PtyState>) -> Result<(), PeekError> {
+    let mut sessions = state.sessions.lock()?;
     sessions.remove(&id);
     Ok(())
 }
+
+/// Send a signal to the program running in a terminal. Unlike writing `\x03`,
+/// this reaches programs that put the TTY in raw mode or ignore Ctrl-C input.
+#[tauri::command]
+pub fn signal_terminal(id: u32, signal: String, state: tauri::State<'_, PtyState>) -> Result<(), PeekError> {
+    let mut sessions = state.sessions.lock()?;
+    let session = sessions
+        .get_mut(&id)
+        .ok_or_else(|| PeekError::not_found(format!("Terminal {} not found", id)))?;
+    send_signal(session, &signal)
+}
+
+/// Stop sending `terminal-output` events for a terminal, so the UI can catch
+/// up during a flood of output. The program keeps running; its output is kept
+/// in scrollback (so at most `MAX_SCROLLBACK_BYTES` is held) until
+/// `resume_terminal_output`.
+#[tauri::command]
+pub fn pause_terminal_output(id: u32, state: tauri::State<'_, PtyState>) -> Result<(), PeekError> {
+    let mut sessions = state.sessions.lock()?;
+    let session = sessions
+        .get_mut(&id)
+        .ok_or_else(|| PeekError::not_found(format!("Terminal {} not found", id)))?;
+    if !session.paused {
+        session.paused = true;
+        session.paused_bytes = 0;
+    }
+    Ok(())
+}
+
+/// Send the output held since `pause_terminal_output` as one
+/// `terminal-output` event and resume streaming. Returns `true` if output
+/// overflowed the scrollback while paused and its start was dropped; the UI
+/// may then prefer to redraw from `get_terminal_scrollback`.
+#[tauri::command]
+pub fn resume_terminal_output(
+    id: u32,
+    app: AppHandle,
+    state: tauri::State<'_, PtyState>,
+) -> Result<bool, PeekError> {
+    let mut sessions = state.sessions.lock()?;
+    let session = sessions
+        .get_mut(&id)
+        .ok_or_else(|| PeekError::not_found(format!("Terminal {} not found", id)))?;
+    if !session.paused {
+        return Ok(false);
+    }
+    let held = session.paused_bytes.min(session.scrollback.len());
+    let dropped = held < session.paused_bytes;
+    if held > 0 {
+        let start = session.scrollback.len() - held;
+        let bytes: Vec<u8> = session.scrollback.range(start..).copied().collect();
+        // Sent under the lock so the reader thread's next chunk can't overtake it
+        let _ = app.emit(
+            "terminal-output",
+            TerminalOutput {
+                id,
+                data: String::from_utf8_lossy(&bytes).to_string(),
+            },
+        );
+    }
+    session.paused = false;
+    session.paused_bytes = 0;
+    Ok(dropped)
+}
+
+/// Send a terminal's scrollback again as ordinary `terminal-output` events of
+/// up to 16 KB each, then `terminal-replay-done`, so a reloaded webview can
+/// rebuild the screen through its usual rendering path instead of writing
+/// one huge string at once. Chunks end on character boundaries. Live output
+/// waits until the replay is sent, so it follows in order. A paused terminal
+/// stays paused, but `resume_terminal_output` won't repeat what was replayed.
+#[tauri::command]
+pub fn replay_terminal(id: u32, app: AppHandle, state: tauri::State<'_, PtyState>) -> Result<(), PeekError> {
+    let mut sessions = state.sessions.lock()?;
+    let session = sessions
+        .get_mut(&id)
+        .ok_or_else(|| PeekError::not_found(format!("Terminal {} not found", id)))?;
+    let (front, back) = session.scrollback.as_slices();
+    let bytes = [front, back].concat();
+
+    // The scrollback cap may have cut the first character
+    let mut start = bytes.iter().position(|&b| !(0x80..0xc0).contains(&b)).unwrap_or(bytes.len());
+    while start < bytes.len() {
+        let mut end = (start + REPLAY_CHUNK_BYTES).min(bytes.len());
+        while end > start + 1 && end < bytes.len() && (0x80..0xc0).contains(&bytes[end]) {
+            end -= 1;
+        }
+        let _ = app.emit(
+            "terminal-output",
+            TerminalOutput {
+                id,
+                data: String::from_utf8_lossy(&bytes[start..end]).to_string(),
+            },
+        );
+        start = end;
+    }
+    session.paused_bytes = 0;
+    let _ = app.emit("terminal-replay-done", TerminalReplayDone { id });
+    Ok(())
+}
+
+/// The terminal's shell and everything it's running, as a tree rooted at the
+/// shell, so the UI can show "running: cargo build" and pick a process to
+/// signal. Empty if the session or its process has already exited.
+#[tauri::command]
+pub fn terminal_process_tree(id: u32, state: tauri::State<'_, PtyState>) -> Result<Vec<ProcessNode>, PeekError> {
+    let (root, foreground_group) = {
+        let sessions = state.sessions.lock()?;
+        let Some(session) = sessions.get(&id) else { return Ok(Vec::new()) };
+        let Some(pid) = session.pid else { return Ok(Vec::new()) };
+        #[cfg(unix)]
+        let foreground_group = session.pair_master.process_group_leader().map(|group| group as u32);
+        #[cfg(not(unix))]
+        let foreground_group = None;
+        (pid, foreground_group)
+    };
+
+    let processes = list_processes();
+    let entries: HashMap<u32, &ProcessEntry> = processes.iter().map(|entry| (entry.pid, entry)).collect();
+    let mut children: HashMap<u32, Vec<u32>> = HashMap::new();
+    for entry in &processes {
+        children.entry(entry.parent).or_default().push(entry.pid);
+    }
+    Ok(process_subtree(root, &entries, &children, foreground_group).into_iter().collect())
+}
+
+/// Start appending a terminal's output to `path` (created if missing). With
+/// `strip_ansi`, escape sequences are removed for a plain-text transcript.
+/// Replaces any log already running for the session.
+#[tauri::command]
+pub fn log_terminal(
+    id: u32,
+    path: String,
+    strip_ansi: Option<bool>,
+    state: tauri::State<'_, PtyState>,
+) -> Result<(), PeekError> {
+    let file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(crate::paths::resolve_path(&path))
+        .map_err(|e| format!("Failed to open log file: {}", e))?;
+
+    let mut sessions = state.sessions.lock()?;
+    let session = sessions
+        .get_mut(&id)
+        .ok_or_else(|| PeekError::not_found(format!("Terminal {} not found", id)))?;
+    session.log = Some(TerminalLog {
+        file: BufWriter::new(file),
+        stripper: strip_ansi.unwrap_or(false).then(AnsiStripper::default),
+        last_flush: Instant::now(),
+    });
+    Ok(())
+}
+
+/// Stop logging a terminal, flushing what's buffered.
+#[tauri::command]
+pub fn stop_logging_terminal(id: u32, state: tauri::State<'_, PtyState>) -> Result<(), PeekError> {
+    let mut sessions = state.sessions.lock()?;
+    let session = sessions
+        .get_mut(&id)
+        .ok_or_else(|| PeekError::not_found(format!("Terminal {} not found", id)))?;
+    if let Some(mut log) = session.log.take() {
+        log.file.flush()?;
+    }
+    Ok(())
+}
+
+/// The terminal's recent output (up to 1 MB), decoded as a whole so
+/// characters split across reads come out intact. With `strip_ansi`, escape
+/// sequences and control characters are removed, leaving plain text.
+#[tauri::command]
+pub fn get_terminal_scrollback(
+    id: u32,
+    strip_ansi: Option<bool>,
+    state: tauri::State<'_, PtyState>,
+) -> Result<String, PeekError> {
+    let sessions = state.sessions.lock()?;
+    let session = sessions
+        .get(&id)
+        .ok_or_else(|| PeekError::not_found(format!("Terminal {} not found", id)))?;
+    Ok(scrollback_text(session, strip_ansi.unwrap_or(false)))
+}
+
+/// The terminal's whole buffered output (up to 1 MB) as plain text for "Copy
+/// All": escape sequences removed, lines redrawn with `\r` shown as they
+/// ended up, trailing whitespace trimmed. Unlike `get_terminal_scrollback`
+/// this is a transcript for people, not a byte-faithful copy.
+#[tauri::command]
+pub fn get_terminal_buffer_as_text(id: u32, state: tauri::State<'_, PtyState>) -> Result<String, PeekError> {
+    let sessions = state.sessions.lock()?;
+    let session = sessions
+        .get(&id)
+        .ok_or_else(|| PeekError::not_found(format!("Terminal {} not found", id)))?;
+    Ok(transcript_text(session))
+}
+
+/// Find `query` (a literal, or a regex with `is_regex`) in the terminal's
+/// ANSI-stripped scrollback. Positions refer to `get_terminal_scrollback`
+/// with `strip_ansi`; at most `MAX_SCROLLBACK_MATCHES` are returned.
+#[tauri::command]
+pub fn search_terminal_scrollback(
+    id: u32,
+    query: String,
+    is_regex: Option<bool>,
+    case_sensitive: Option<bool>,
+    state: tauri::State<'_, PtyState>,
+) -> Result<Vec<ScrollbackMatch>, PeekError> {
+    let re = crate::search::build_regex(query, is_regex, case_sensitive, false)?;
+
+    let text = {
+        let sessions = state.sessions.lock()?;
+        let session = sessions
+            .get(&id)
+            .ok_or_else(|| PeekError::not_found(format!("Terminal {} not found", id)))?;
+        scrollback_text(session, true)
+    };
+
+    let mut matches = Vec::new();
+    for (line_number, line) in text.split('\n').enumerate() {
+        for m in re.find_iter(line).filter(|m| !m.is_empty()) {
+            matches.push(ScrollbackMatch {
+                line: line_number,
+                column: line[..m.start()].chars().count(),
+                length: m.as_str().chars().count(),
+            });
+            if matches.len() >= MAX_SCROLLBACK_MATCHES {
+                return Ok(matches);
+            }
+        }
+    }
+    Ok(matches)
+}
+
+/// Name a terminal ("build", "server"). An empty or missing label clears it.
+#[tauri::command]
+pub fn rename_terminal(
+    id: u32,
+    label: Option<String>,
+    state: tauri::State<'_, PtyState>,
+) -> Result<(), PeekError> {
+    let mut sessions = state.sessions.lock()?;
+    let session = sessions
+        .get_mut(&id)
+        .ok_or_else(|| PeekError::not_found(format!("Terminal {} not found", id)))?;
+    session.label = label.filter(|label| !label.trim().is_empty());
+    Ok(())
+}
+
+/// List open terminal sessions with their label, title and working directory.
+#[tauri::command]
+pub fn list_terminals(state: tauri::State<'_, PtyState>) -> Result<Vec<TerminalInfo>, PeekError> {
+    let sessions = state.sessions.lock()?;
+    let mut terminals: Vec<TerminalInfo> = sessions
+        .iter()
+        .map(|(&id, session)| TerminalInfo {
+            id,
+            label: session.label.clone(),
+            title: session.title.clone(),
+            cwd: session
+                .cwd
+                .clone()
+                .or_else(|| session.pid.and_then(process_cwd)),
+            pid: session.pid,
+        })
+        .collect();
+    terminals.sort_by_key(|t| t.id);
+    Ok(terminals)
+}
@@ -0,0 +1,130 @@
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::Serialize;
+
+use crate::error::{AtPath, PeekError};
+
+/// How many deletions the "recently deleted" list remembers.
+const MAX_TRASHED: usize = 100;
+
+// ── Types ─────────────────────────────────────────────────────────────────────
+
+#[derive(Serialize, Clone)]
+pub struct TrashedFile {
+    token: String,
+    path: String,
+    deleted_at: u64, // Unix timestamp (seconds)
+}
+
+/// Managed state: files this session moved to the trash, oldest first.
+/// Tokens are only meaningful for the lifetime of the app.
+pub struct TrashLog {
+    items: Mutex<Vec<TrashedFile>>,
+    next_token: AtomicU64,
+}
+
+impl TrashLog {
+    pub fn new() -> Self {
+        TrashLog {
+            items: Mutex::new(Vec::new()),
+            next_token: AtomicU64::new(1),
+        }
+    }
+}
+
+// ── Helpers ───────────────────────────────────────────────────────────────────
+
+/// Put `path` back using the trash's own metadata (FreeDesktop trash info
+/// files / the Windows Recycle Bin). The newest entry wins if it was trashed
+/// more than once.
+#[cfg(any(
+    target_os = "windows",
+    all(unix, not(target_os = "macos"), not(target_os = "ios"), not(target_os = "android"))
+))]
+fn restore(path: &Path) -> Result<(), PeekError> {
+    let item = trash::os_limited::list()
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .filter(|item| item.original_path() == path)
+        .max_by_key(|item| item.time_deleted)
+        .ok_or_else(|| PeekError::not_found("File is no longer in the trash"))?;
+    trash::os_limited::restore_all([item]).map_err(|e| match e {
+        trash::Error::RestoreCollision { path, .. } => {
+            PeekError::already_exists("Destination already exists").at(&path)
+        }
+        e => e.to_string().into(),
+    })
+}
+
+/// No trash metadata API here: look for the file under its own name in
+/// `~/.Trash`, which is where it lands unless the name was already taken.
+#[cfg(not(any(
+    target_os = "windows",
+    all(unix, not(target_os = "macos"), not(target_os = "ios"), not(target_os = "android"))
+)))]
+fn restore(path: &Path) -> Result<(), PeekError> {
+    let home = std::env::var_os("HOME").ok_or("Home directory not found")?;
+    let name = path.file_name().ok_or("Invalid path")?;
+    let trashed = PathBuf::from(home).join(".Trash").join(name);
+    if !trashed.exists() {
+        return Err(PeekError::not_found("File not found in the trash; restore it from Finder instead"));
+    }
+    if path.exists() {
+        return Err(PeekError::already_exists("Destination already exists").at(&path));
+    }
+    Ok(std::fs::rename(&trashed, path)?)
+}
+
+// ── Commands ──────────────────────────────────────────────────────────────────
+
+/// Move a file or directory to the OS trash. Returns a token for
+/// `restore_from_trash`.
+#[tauri::command]
+pub fn delete_to_trash(path: String, state: tauri::State<'_, TrashLog>) -> Result<String, PeekError> {
+    let path = crate::paths::resolve(&path);
+    // The trash records absolute paths, so match against the canonical one
+    let absolute: PathBuf = std::fs::canonicalize(&path).at(&path)?;
+    trash::delete(&absolute).map_err(|e| e.to_string())?;
+
+    let token = state.next_token.fetch_add(1, Ordering::Relaxed).to_string();
+    let deleted_at = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+
+    let mut items = state.items.lock()?;
+    items.push(TrashedFile {
+        token: token.clone(),
+        path: absolute.to_string_lossy().to_string(),
+        deleted_at,
+    });
+    if items.len() > MAX_TRASHED {
+        let excess = items.len() - MAX_TRASHED;
+        items.drain(..excess);
+    }
+    Ok(token)
+}
+
+/// Put a file deleted with `delete_to_trash` back where it was. Fails if
+/// something now occupies the original path or the trash was emptied.
+#[tauri::command]
+pub fn restore_from_trash(token: String, state: tauri::State<'_, TrashLog>) -> Result<String, PeekError> {
+    let mut items = state.items.lock()?;
+    let index = items
+        .iter()
+        .position(|item| item.token == token)
+        .ok_or_else(|| PeekError::not_found("Unknown trash token"))?;
+
+    restore(Path::new(&items[index].path))?;
+    Ok(items.remove(index).path)
+}
+
+/// Files deleted this session that can still be restored, newest first.
+#[tauri::command]
+pub fn list_trashed(state: tauri::State<'_, TrashLog>) -> Result<Vec<TrashedFile>, PeekError> {
+    let items = state.items.lock()?;
+    Ok(items.iter().rev().cloned().collect())
+}
@@ -0,0 +1,382 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use notify::event::{EventKind, ModifyKind, RenameMode};
+use notify::{RecommendedWatcher, RecursiveMode, Watcher};
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::error::{AtPath, PeekError};
+
+/// A burst is over once it has been quiet this long...
+const DEBOUNCE: Duration = Duration::from_millis(200);
+/// ...but a long-running one (a big checkout) still reports this often.
+const MAX_BATCH_DELAY: Duration = Duration::from_secs(1);
+/// Past this many changes in one batch the UI is told to rescan instead.
+const MAX_BATCH_CHANGES: usize = 500;
+/// Directory names whose churn is ignored unless the caller passes its own list.
+const DEFAULT_IGNORED: &[&str] = &[".git", "node_modules", "target"];
+/// Largest file whose content a diffing file watch keeps to compare against.
+const MAX_SNAPSHOT_BYTES: usize = 16 * 1024 * 1024;
+/// Above this (old or new) a change is reported as a byte range, not a line diff.
+const MAX_LINE_DIFF_BYTES: usize = 2 * 1024 * 1024;
+
+// ── Types ─────────────────────────────────────────────────────────────────────
+
+#[derive(Serialize, Clone, PartialEq)]
+struct DirChange {
+    kind: String, // "create" | "remove" | "rename" | "modify"
+    path: String,
+    /// For renames reported as one event: where the entry was before.
+    old_path: Option<String>,
+}
+
+#[derive(Serialize, Clone)]
+struct DirChanged {
+    root: String,
+    changes: Vec<DirChange>,
+    /// Too much changed to list: re-read the whole tree.
+    overflow: bool,
+}
+
+#[derive(Serialize, Clone)]
+struct FileChanged {
+    path: String,
+    /// The file is gone (deleted, or renamed away and not replaced).
+    removed: bool,
+}
+
+/// Lines added to the end of an indexed file, whose index was extended
+/// rather than rebuilt. `start_line` is the old last line, which the append
+/// may have continued; `read_lines` from there shows everything new.
+#[derive(Serialize, Clone)]
+struct FileAppended {
+    path: String,
+    start_line: usize,
+    total_lines: usize,
+}
+
+/// The changed bytes of a file too large to line-diff: everything before
+/// `start` and after the ends is the same in both versions.
+#[derive(Serialize, Clone)]
+struct ChangedRange {
+    start: u64,
+    old_end: u64,
+    new_end: u64,
+}
+
+#[derive(Serialize, Clone)]
+struct FileChangedDiff {
+    path: String,
+    /// Changed line ranges, old → new (no "equal" ops).
+    hunks: Option<Vec<crate::diff::DiffOp>>,
+    /// Set instead of `hunks` for large files.
+    changed_range: Option<ChangedRange>,
+}
+
+/// Managed state: active directory watches by the path they were started
+/// with. Dropping a watcher removes its OS watches and ends its debounce
+/// thread (the event channel closes with it).
+pub struct DirWatchers(Mutex<HashMap<String, RecommendedWatcher>>);
+
+impl DirWatchers {
+    pub fn new() -> Self {
+        DirWatchers(Mutex::new(HashMap::new()))
+    }
+
+    pub fn count(&self) -> usize {
+        self.0.lock().map_or(0, |watchers| watchers.len())
+    }
+}
+
+/// Managed state: active file watches by path. As with `DirWatchers`,
+/// dropping a watcher ends its thread.
+pub struct FileWatchers(Mutex<HashMap<String, RecommendedWatcher>>);
+
+impl FileWatchers {
+    pub fn new() -> Self {
+        FileWatchers(Mutex::new(HashMap::new()))
+    }
+
+    pub fn count(&self) -> usize {
+        self.0.lock().map_or(0, |watchers| watchers.len())
+    }
+
+    /// Stop watching `path`, if it is watched. Used when a file is closed.
+    pub fn remove(&self, path: &str) -> Result<(), PeekError> {
+        self.0.lock()?.remove(path);
+        Ok(())
+    }
+}
+
+// ── Helpers ───────────────────────────────────────────────────────────────────
+
+/// The changes a notify event describes, minus ignored paths and the kinds
+/// (access, metadata-less "other") the tree doesn't care about.
+fn to_changes(event: notify::Event, root: &Path, ignored: &[String]) -> Vec<DirChange> {
+    let is_ignored = |path: &Path| {
+        path.strip_prefix(root).unwrap_or(path).components().any(|component| {
+            ignored.iter().any(|name| component.as_os_str() == name.as_str())
+        })
+    };
+    let display = |path: &Path| path.to_string_lossy().to_string();
+
+    let kind = match event.kind {
+        EventKind::Create(_) => "create",
+        EventKind::Remove(_) => "remove",
+        EventKind::Modify(ModifyKind::Name(RenameMode::Both)) if event.paths.len() == 2 => {
+            let (from, to) = (&event.paths[0], &event.paths[1]);
+            if is_ignored(from) && is_ignored(to) {
+                return Vec::new();
+            }
+            return vec![DirChange { kind: "rename".to_string(), path: display(to), old_path: Some(display(from)) }];
+        }
+        EventKind::Modify(ModifyKind::Name(_)) => "rename",
+        EventKind::Modify(_) => "modify",
+        _ => return Vec::new(),
+    };
+    event.paths
+        .iter()
+        .filter(|path| !is_ignored(path))
+        .map(|path| DirChange { kind: kind.to_string(), path: display(path), old_path: None })
+        .collect()
+}
+
+/// Add changes to a batch, skipping ones it already has: editors and git
+/// touch the same file several times per save.
+fn coalesce(batch: &mut Vec<DirChange>, changes: Vec<DirChange>) {
+    for change in changes {
+        if batch.len() > MAX_BATCH_CHANGES {
+            return; // already an overflow, no point listing more
+        }
+        if !batch.contains(&change) {
+            batch.push(change);
+        }
+    }
+}
+
+/// Collect changes into batches (see `DEBOUNCE`) and emit each as one
+/// `dir-changed` event. Returns when the watcher is dropped.
+fn debounce_loop(app: AppHandle, root: String, rx: Receiver<Vec<DirChange>>) {
+    let emit = |changes: Vec<DirChange>| {
+        let overflow = changes.len() > MAX_BATCH_CHANGES;
+        let changes = if overflow { Vec::new() } else { changes };
+        let _ = app.emit("dir-changed", DirChanged { root: root.clone(), changes, overflow });
+    };
+
+    while let Ok(first) = rx.recv() {
+        let mut batch: Vec<DirChange> = Vec::new();
+        coalesce(&mut batch, first);
+
+        let deadline = Instant::now() + MAX_BATCH_DELAY;
+        loop {
+            let wait = DEBOUNCE.min(deadline.saturating_duration_since(Instant::now()));
+            match rx.recv_timeout(wait) {
+                Ok(changes) => coalesce(&mut batch, changes),
+                Err(RecvTimeoutError::Timeout) => break,
+                Err(RecvTimeoutError::Disconnected) => {
+                    if !batch.is_empty() {
+                        emit(batch);
+                    }
+                    return;
+                }
+            }
+        }
+        if !batch.is_empty() {
+            emit(batch);
+        }
+    }
+}
+
+/// Content kept for diffing, or `None` when the file is missing or too large.
+fn snapshot(path: &Path) -> Option<Vec<u8>> {
+    let metadata = std::fs::metadata(path).ok()?;
+    if metadata.len() > MAX_SNAPSHOT_BYTES as u64 {
+        return None;
+    }
+    std::fs::read(path).ok()
+}
+
+/// What changed between two versions of a file: a line diff when both are
+/// small, otherwise the byte range between their common prefix and suffix.
+fn describe_change(path: &str, old: &[u8], new: &[u8]) -> FileChangedDiff {
+    if old.len() <= MAX_LINE_DIFF_BYTES && new.len() <= MAX_LINE_DIFF_BYTES {
+        let hunks = crate::diff::diff_lines(&String::from_utf8_lossy(old), &String::from_utf8_lossy(new))
+            .into_iter()
+            .filter(|op| op.op != "equal")
+            .collect();
+        return FileChangedDiff { path: path.to_string(), hunks: Some(hunks), changed_range: None };
+    }
+    let prefix = old.iter().zip(new).take_while(|(a, b)| a == b).count();
+    let max_suffix = old.len().min(new.len()) - prefix;
+    let suffix = old.iter().rev().zip(new.iter().rev()).take(max_suffix).take_while(|(a, b)| a == b).count();
+    FileChangedDiff {
+        path: path.to_string(),
+        hunks: None,
+        changed_range: Some(ChangedRange {
+            start: prefix as u64,
+            old_end: (old.len() - suffix) as u64,
+            new_end: (new.len() - suffix) as u64,
+        }),
+    }
+}
+
+/// Wait out each burst of writes (see `DEBOUNCE`), then emit `file-changed`
+/// and, when diffing, `file-changed-diff` against the last content seen.
+/// Returns when the watcher is dropped.
+fn file_watch_loop(app: AppHandle, path: String, diff: bool, rx: Receiver<()>) {
+    let file_path = PathBuf::from(&path);
+    let mut last = if diff { snapshot(&file_path) } else { None };
+
+    while rx.recv().is_ok() {
+        let deadline = Instant::now() + MAX_BATCH_DELAY;
+        loop {
+            let wait = DEBOUNCE.min(deadline.saturating_duration_since(Instant::now()));
+            match rx.recv_timeout(wait) {
+                Ok(()) => continue,
+                Err(RecvTimeoutError::Timeout) => break,
+                Err(RecvTimeoutError::Disconnected) => return,
+            }
+        }
+
+        let removed = !file_path.is_file();
+        if !removed {
+            // Keep `read_lines` current without waiting for the UI to re-index
+            let cache = app.state::<crate::LineIndexCache>();
+            if let Ok(Some((start_line, total_lines))) = crate::follow_index(&path, &cache) {
+                let _ = app.emit("file-appended", FileAppended { path: path.clone(), start_line, total_lines });
+            }
+        }
+        if !diff {
+            let _ = app.emit("file-changed", FileChanged { path: path.clone(), removed });
+            continue;
+        }
+        let current = if removed { None } else { snapshot(&file_path) };
+        if current.is_some() && current == last {
+            continue; // touched or rewritten with the same content
+        }
+        let _ = app.emit("file-changed", FileChanged { path: path.clone(), removed });
+        if let (Some(old), Some(new)) = (&last, &current) {
+            let _ = app.emit("file-changed-diff", describe_change(&path, old, new));
+        }
+        last = current;
+    }
+}
+
+// ── Commands ──────────────────────────────────────────────────────────────────
+
+/// Watch a directory (and with `recursive`, everything below it) for entries
+/// being created, removed, renamed or modified. Changes arrive as batched
+/// `dir-changed` events; paths under a directory named in `ignore` (default
+/// `.git`, `node_modules`, `target`) are left out. Watching a path again
+/// replaces the previous watch.
+#[tauri::command]
+pub fn watch_directory(
+    path: String,
+    recursive: Option<bool>,
+    ignore: Option<Vec<String>>,
+    app: AppHandle,
+    state: tauri::State<'_, DirWatchers>,
+) -> Result<(), PeekError> {
+    let path = crate::paths::resolve(&path);
+    let root = PathBuf::from(&path);
+    if !root.is_dir() {
+        return Err(format!("Not a directory: {}", path).into());
+    }
+    let ignored = ignore.unwrap_or_else(|| DEFAULT_IGNORED.iter().map(|name| name.to_string()).collect());
+
+    let (tx, rx) = mpsc::channel();
+    let event_root = root.clone();
+    let mut watcher = notify::recommended_watcher(move |result: notify::Result<notify::Event>| {
+        if let Ok(event) = result {
+            let changes = to_changes(event, &event_root, &ignored);
+            if !changes.is_empty() {
+                let _ = tx.send(changes);
+            }
+        }
+    })
+    .map_err(|e| e.to_string())?;
+
+    let mode = if recursive.unwrap_or(false) { RecursiveMode::Recursive } else { RecursiveMode::NonRecursive };
+    watcher.watch(&root, mode).map_err(|e| e.to_string())?;
+
+    let thread_root = path.clone();
+    thread::spawn(move || debounce_loop(app, thread_root, rx));
+
+    state.0.lock()?.insert(path, watcher);
+    Ok(())
+}
+
+/// Stop watching a directory started with `watch_directory`, including every
+/// subdirectory a recursive watch added.
+#[tauri::command]
+pub fn unwatch_directory(path: String, state: tauri::State<'_, DirWatchers>) -> Result<(), PeekError> {
+    let path = crate::paths::resolve(&path);
+    let mut watcher = state
+        .0
+        .lock()?
+        .remove(&path)
+        .ok_or_else(|| PeekError::not_found(format!("Not watching {}", path)))?;
+    // Explicit so backends that track subdirectories drop them now, not on drop
+    let _ = watcher.unwatch(Path::new(&path));
+    Ok(())
+}
+
+/// Watch one file for changes made outside the editor, emitting a debounced
+/// `file-changed` event. With `diff`, the watch keeps the file's content
+/// (files up to 16 MB) and also emits `file-changed-diff` with what changed:
+/// line hunks for files up to 2 MB, the changed byte range above that. Saves
+/// that don't change the content are not reported. If the file is indexed,
+/// its index is kept up to date: growth that leaves the start of the file
+/// alone (a log being written) extends it and emits `file-appended`, anything
+/// else re-indexes. The watch follows the path, so it survives editors that
+/// save by replacing the file; it ends with `unwatch_file` or `close_file`.
+#[tauri::command]
+pub fn watch_file(
+    path: String,
+    diff: Option<bool>,
+    app: AppHandle,
+    state: tauri::State<'_, FileWatchers>,
+) -> Result<(), PeekError> {
+    let path = crate::paths::resolve(&path);
+    let file_path = PathBuf::from(&path);
+    crate::regular_file_metadata(&file_path).at(&file_path)?;
+    let dir = crate::parent_dir(&file_path).to_path_buf();
+    let name = file_path.file_name().map(|name| name.to_os_string());
+
+    let (tx, rx) = mpsc::channel();
+    let mut watcher = notify::recommended_watcher(move |result: notify::Result<notify::Event>| {
+        let Ok(event) = result else { return };
+        let relevant = matches!(event.kind, EventKind::Create(_) | EventKind::Remove(_) | EventKind::Modify(_))
+            && event.paths.iter().any(|changed| changed.file_name() == name.as_deref());
+        if relevant {
+            let _ = tx.send(());
+        }
+    })
+    .map_err(|e| e.to_string())?;
+    // The directory, not the file: a save by rename replaces the inode a
+    // file watch would be attached to
+    watcher.watch(&dir, RecursiveMode::NonRecursive).map_err(|e| e.to_string())?;
+
+    let thread_path = path.clone();
+    thread::spawn(move || file_watch_loop(app, thread_path, diff.unwrap_or(false), rx));
+
+    state.0.lock()?.insert(path, watcher);
+    Ok(())
+}
+
+/// Stop watching a file started with `watch_file`.
+#[tauri::command]
+pub fn unwatch_file(path: String, state: tauri::State<'_, FileWatchers>) -> Result<(), PeekError> {
+    let path = crate::paths::resolve(&path);
+    state
+        .0
+        .lock()?
+        .remove(&path)
+        .ok_or_else(|| PeekError::not_found(format!("Not watching {}", path)))?;
+    Ok(())
+}
@@ -21,6 +21,7 @@ import DiffViewer from "./components/DiffViewer";
 import { useAutoSave, getAllDrafts } from "./hooks/useAutoSave";
 import { useSettings } from "./hooks/useSettings";
 import { getThemeById, applyTheme } from "./lib/themes";
+import { errorMessage } from "./lib/errors";
 import { FileMetadata, getFileType } from "./fileTypes";
 import { FolderOpen, Save, Eye, FileText, Columns, AlertTriangle, Settings, TerminalSquare, FileDiff } from "lucide-react";
 import "./App.css";
@@ -133,7 +134,7 @@ function App() {
                 }
             }
 
-            await invoke("safe_save_file", { path: activeTab.filePath, content: activeTab.editContent });
+            await invoke("safe_save_file", { path: activeTab.filePath, content: activeTab.editContent, encoding: activeTab.fileData.encoding });
 
             // Sync new mtime
             try {
@@ -301,7 +302,7 @@ function App() {
                 const savedDraft = localStorage.getItem(`peek_draft_${path}`);
                 if (savedDraft) {
                     try {
-                        await invoke("write_file_content", { path, content: savedDraft });
+                        await invoke("write_file_content", { path, content: savedDraft, encoding: data.encoding });
                         contentToUse = savedDraft;
                         newFileData = { ...data, content: savedDraft };
                         localStorage.removeItem(`peek_draft_${path}`);
@@ -332,7 +333,7 @@ function App() {
             setAvailableDrafts(getAllDrafts());
         } catch (err) {
             console.error("Failed to read file:", err);
-            setError(String(err));
+            setError(errorMessage(err));
         }
     };
 
@@ -393,7 +394,7 @@ function App() {
             setModalSaveLabel("Save");
             setModalSaveAction(() => async () => {
                 try {
-                    await invoke("write_file_content", { path: tab.filePath, content: tab.editContent });
+                    await invoke("write_file_content", { path: tab.filePath, content: tab.editContent, encoding: tab.fileData.encoding });
                     showNotification("File saved successfully");
                 } catch (err) {
                     showNotification("Failed to save file", "error");
@@ -414,7 +415,7 @@ function App() {
         const newContent = draftContent;
         setEditContent(newContent);
 
-        invoke("write_file_content", { path: filePath, content: newContent })
+        invoke("write_file_content", { path: filePath, content: newContent, encoding: fileData?.encoding })
             .then(() => {
                 if (activeTabId) {
                     updateTab(activeTabId, {
@@ -1,6 +1,7 @@
 import { useState, useEffect, useRef, useCallback } from 'react';
 import { invoke } from "@tauri-apps/api/core";
 import { Save } from 'lucide-react';
+import { errorMessage } from '../lib/errors';
 
 interface LargeFileViewerProps {
     filePath: string;
@@ -106,7 +107,7 @@ export default function LargeFileViewer({ filePath, fileSize }: LargeFileViewerP
             await fetchLines(currentStartLine);
         } catch (err) {
             console.error("Failed to save:", err);
-            alert("Failed to save changes: " + err);
+            alert("Failed to save changes: " + errorMessage(err));
         } finally {
             setIsLoading(false);
         }
@@ -3,6 +3,7 @@ import { Terminal } from '@xterm/xterm';
 import { FitAddon } from '@xterm/addon-fit';
 import { invoke } from '@tauri-apps/api/core';
 import { listen } from '@tauri-apps/api/event';
+import { errorMessage } from '../lib/errors';
 import '@xterm/xterm/css/xterm.css';
 
 interface TerminalPanelProps {
@@ -120,7 +121,7 @@ export default function TerminalPanel({ visible, onClose, cwd }: TerminalPanelPr
                 // Cleanup listeners on dispose - handled in useEffect return
             } catch (err) {
                 console.error('Failed to spawn terminal:', err);
-                term.write(`\x1b[31mFailed to spawn terminal: ${err}\x1b[0m\r\n`);
+                term.write(`\x1b[31mFailed to spawn terminal: ${errorMessage(err)}\x1b[0m\r\n`);
             }
         };
 
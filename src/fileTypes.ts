@@ -8,6 +8,8 @@ export interface FileMetadata {
     mtime: number;
     is_binary: boolean;
     is_large_file: boolean;
+    /** Encoding the content was decoded from; saves write it back the same way. */
+    encoding?: string | null;
 }
 
 export const getFileType = (extension: string): FileType => {
@@ -0,0 +1,18 @@
+/** Error shape every backend command rejects with. */
+export interface PeekError {
+    code: string;
+    message: string;
+    path: string | null;
+}
+
+export function isPeekError(err: unknown): err is PeekError {
+    return typeof err === 'object' && err !== null && 'code' in err && 'message' in err;
+}
+
+/** Human-readable text for a rejected `invoke`, whatever it rejected with. */
+export function errorMessage(err: unknown): string {
+    if (isPeekError(err)) {
+        return err.message ? `${err.code}: ${err.message}` : err.code;
+    }
+    return String(err);
+}